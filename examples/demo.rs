@@ -0,0 +1,47 @@
+use common::{application::StandardPlonk, create_proof_checked, prelude::*};
+
+use halo2_solidity_verifier::{
+    compile_solidity, encode_calldata, BatchOpenScheme::Bdfg21, Evm, SolidityGenerator,
+};
+
+mod common;
+
+const K: u32 = 10;
+const NUM_INSTANCES: usize = 4;
+
+fn main() {
+    let mut rng = seeded_std_rng();
+
+    println!("[1/5] Setup SRS with 2^{K} rows");
+    let params = ParamsKZG::<Bn256>::setup(K, &mut rng);
+
+    println!("[2/5] Keygen standard Plonk with {NUM_INSTANCES} instances");
+    let circuit = StandardPlonk::rand(NUM_INSTANCES, &mut rng);
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk, &circuit).unwrap();
+
+    println!("[3/5] Create proof");
+    let instances = circuit.instances();
+    let proof = create_proof_checked(&params, &pk, circuit, &instances, &mut rng);
+    println!("Proof size: {}", proof.len());
+
+    println!("[4/5] Generate and compile verifier");
+    let generator = SolidityGenerator::new(&params, pk.get_vk(), Bdfg21, NUM_INSTANCES);
+    let verifier_solidity = generator.render().unwrap();
+    let verifier_creation_code = compile_solidity(&verifier_solidity);
+    println!(
+        "Verifier creation code size: {}",
+        verifier_creation_code.len()
+    );
+
+    println!("[5/5] Deploy verifier and verify proof");
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+    println!(
+        "Verifier runtime code size: {}",
+        evm.code_size(verifier_address)
+    );
+    let (gas_cost, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Verification passed with gas cost: {gas_cost}");
+}
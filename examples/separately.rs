@@ -1,11 +1,11 @@
-use application::StandardPlonk;
-use prelude::*;
+use common::{application::StandardPlonk, create_proof_checked, prelude::*};
 
 use halo2_solidity_verifier::{
-    compile_solidity, encode_calldata, BatchOpenScheme::Bdfg21, Evm, Keccak256Transcript,
-    SolidityGenerator,
+    compile_solidity, encode_calldata, BatchOpenScheme::Bdfg21, Evm, SolidityGenerator,
 };
 
+mod common;
+
 const K_RANGE: Range<u32> = 10..17;
 
 fn main() {
@@ -69,174 +69,3 @@ fn setup(k_range: Range<u32>, mut rng: impl RngCore) -> HashMap<u32, ParamsKZG<B
         .zip(k_range.map(|k| ParamsKZG::<Bn256>::setup(k, &mut rng)))
         .collect()
 }
-
-fn create_proof_checked(
-    params: &ParamsKZG<Bn256>,
-    pk: &ProvingKey<G1Affine>,
-    circuit: impl Circuit<Fr>,
-    instances: &[Fr],
-    mut rng: impl RngCore,
-) -> Vec<u8> {
-    use halo2_proofs::{
-        poly::kzg::{
-            multiopen::{ProverSHPLONK, VerifierSHPLONK},
-            strategy::SingleStrategy,
-        },
-        transcript::TranscriptWriterBuffer,
-    };
-
-    let proof = {
-        let mut transcript = Keccak256Transcript::new(Vec::new());
-        create_proof::<_, ProverSHPLONK<_>, _, _, _, _>(
-            params,
-            pk,
-            &[circuit],
-            &[&[instances]],
-            &mut rng,
-            &mut transcript,
-        )
-        .unwrap();
-        transcript.finalize()
-    };
-
-    let result = {
-        let mut transcript = Keccak256Transcript::new(proof.as_slice());
-        verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
-            params,
-            pk.get_vk(),
-            SingleStrategy::new(params),
-            &[&[instances]],
-            &mut transcript,
-        )
-    };
-    assert!(result.is_ok());
-
-    proof
-}
-
-mod application {
-    use crate::prelude::*;
-
-    #[derive(Clone)]
-    pub struct StandardPlonkConfig {
-        selectors: [Column<Fixed>; 5],
-        wires: [Column<Advice>; 3],
-    }
-
-    impl StandardPlonkConfig {
-        fn configure(meta: &mut ConstraintSystem<impl PrimeField>) -> Self {
-            let [w_l, w_r, w_o] = [(); 3].map(|_| meta.advice_column());
-            let [q_l, q_r, q_o, q_m, q_c] = [(); 5].map(|_| meta.fixed_column());
-            let pi = meta.instance_column();
-            [w_l, w_r, w_o].map(|column| meta.enable_equality(column));
-            meta.create_gate(
-                "q_l·w_l + q_r·w_r + q_o·w_o + q_m·w_l·w_r + q_c + pi = 0",
-                |meta| {
-                    let [w_l, w_r, w_o] =
-                        [w_l, w_r, w_o].map(|column| meta.query_advice(column, Rotation::cur()));
-                    let [q_l, q_r, q_o, q_m, q_c] = [q_l, q_r, q_o, q_m, q_c]
-                        .map(|column| meta.query_fixed(column, Rotation::cur()));
-                    let pi = meta.query_instance(pi, Rotation::cur());
-                    Some(
-                        q_l * w_l.clone()
-                            + q_r * w_r.clone()
-                            + q_o * w_o
-                            + q_m * w_l * w_r
-                            + q_c
-                            + pi,
-                    )
-                },
-            );
-            StandardPlonkConfig {
-                selectors: [q_l, q_r, q_o, q_m, q_c],
-                wires: [w_l, w_r, w_o],
-            }
-        }
-    }
-
-    #[derive(Clone, Debug, Default)]
-    pub struct StandardPlonk<F>(Vec<F>);
-
-    impl<F: PrimeField> StandardPlonk<F> {
-        pub fn rand<R: RngCore>(num_instances: usize, mut rng: R) -> Self {
-            Self((0..num_instances).map(|_| F::random(&mut rng)).collect())
-        }
-
-        pub fn instances(&self) -> Vec<F> {
-            self.0.clone()
-        }
-    }
-
-    impl<F: PrimeField> Circuit<F> for StandardPlonk<F> {
-        type Config = StandardPlonkConfig;
-        type FloorPlanner = SimpleFloorPlanner;
-
-        fn without_witnesses(&self) -> Self {
-            unimplemented!()
-        }
-
-        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-            meta.set_minimum_degree(4);
-            StandardPlonkConfig::configure(meta)
-        }
-
-        fn synthesize(
-            &self,
-            config: Self::Config,
-            mut layouter: impl Layouter<F>,
-        ) -> Result<(), Error> {
-            let [q_l, q_r, q_o, q_m, q_c] = config.selectors;
-            let [w_l, w_r, w_o] = config.wires;
-            layouter.assign_region(
-                || "",
-                |mut region| {
-                    for (offset, instance) in self.0.iter().enumerate() {
-                        region.assign_advice(|| "", w_l, offset, || Value::known(*instance))?;
-                        region.assign_fixed(|| "", q_l, offset, || Value::known(-F::ONE))?;
-                    }
-                    let offset = self.0.len();
-                    let a = region.assign_advice(|| "", w_l, offset, || Value::known(F::ONE))?;
-                    a.copy_advice(|| "", &mut region, w_r, offset)?;
-                    a.copy_advice(|| "", &mut region, w_o, offset)?;
-                    let offset = offset + 1;
-                    region.assign_advice(|| "", w_l, offset, || Value::known(-F::from(5)))?;
-                    for (column, idx) in [q_l, q_r, q_o, q_m, q_c].iter().zip(1..) {
-                        region.assign_fixed(
-                            || "",
-                            *column,
-                            offset,
-                            || Value::known(F::from(idx)),
-                        )?;
-                    }
-                    Ok(())
-                },
-            )
-        }
-    }
-}
-
-mod prelude {
-    pub use halo2_proofs::{
-        circuit::{Layouter, SimpleFloorPlanner, Value},
-        halo2curves::{
-            bn256::{Bn256, Fr, G1Affine},
-            ff::PrimeField,
-        },
-        plonk::*,
-        poly::{commitment::Params, kzg::commitment::ParamsKZG, Rotation},
-    };
-    pub use rand::{
-        rngs::{OsRng, StdRng},
-        RngCore, SeedableRng,
-    };
-    pub use std::{
-        collections::HashMap,
-        fs::{create_dir_all, File},
-        io::Write,
-        ops::Range,
-    };
-
-    pub fn seeded_std_rng() -> impl RngCore {
-        StdRng::seed_from_u64(OsRng.next_u64())
-    }
-}
@@ -1,29 +1,68 @@
-use crate::codegen::{
-    evaluator::Evaluator,
-    pcs::{
-        bdfg21_computations, queries, rotation_sets,
-        BatchOpenScheme::{Bdfg21, Gwc19},
+use crate::{
+    codegen::{
+        evaluator::{hoist_constants, map_tokens, Evaluator},
+        pcs::{
+            bdfg21_computations, queries, rotation_sets,
+            BatchOpenScheme::{Bdfg21, Gwc19},
+        },
+        template::{
+            Halo2Verifier, Halo2VerifierAssembly, Halo2VerifierConstants, Halo2VerifierDeploy,
+            Halo2VerifierEncoder, Halo2VerifierInterface, Halo2VerifierPausable,
+            Halo2VerifierRouter, Halo2VerifierTest, Halo2VerifyingKey,
+        },
+        util::{fr_to_u256, g1_to_u256s, g2_to_u256s, ConstraintSystemMeta, Data, Ptr},
     },
-    template::{Halo2Verifier, Halo2VerifyingKey},
-    util::{fr_to_u256, g1_to_u256s, g2_to_u256s, ConstraintSystemMeta, Data, Ptr},
+    encode_calldata,
+    proof::{PointOrder, ProofField},
+    transcript::ChallengeReduction,
 };
 use halo2_proofs::{
-    halo2curves::{bn256, ff::Field},
+    halo2curves::{
+        bn256,
+        ff::{Field, PrimeField},
+    },
     plonk::VerifyingKey,
     poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG, Rotation},
 };
-use itertools::{chain, Itertools};
+use itertools::{chain, izip, Itertools};
 use ruint::aliases::U256;
-use std::fmt::{self, Debug};
+use sha3::{Digest, Keccak256};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt::{self, Debug, Display, Formatter, Write},
+    fs, io,
+    ops::RangeInclusive,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
 mod evaluator;
+mod patch;
 mod pcs;
 mod template;
 pub(crate) mod util;
+mod yul;
 
+pub use patch::{check_num_instances, export_vk_json, import_vk_json, patch_vk, PatchVkError};
 pub use pcs::BatchOpenScheme;
+pub use yul::{check_yul_instructions, YulError};
+
+/// Version of solc pinned in project written by [`SolidityGenerator::scaffold_project`].
+pub const SOLC_VERSION: &str = "0.8.21";
+
+// Lowest version of solc compiling rendered contracts, which use custom errors and ABI encoding
+// features that older versions lack or miscompile.
+const MIN_SOLC_VERSION: [u64; 3] = [0, 8, 13];
 
 /// Solidity verifier generator for [`halo2`] proof with KZG polynomial commitment scheme on BN254.
+///
+/// It renders from verifying key directly instead of transpiling Yul output of `snark-verifier`,
+/// and rendering only writes into `fmt::Write` or returns `String` without touching filesystem,
+/// except [`SolidityGenerator::scaffold_project`] and [`SolidityGenerator::render_cached`]. So it
+/// works in sandboxes with read-only `/tmp` and on targets without filesystem like WASM.
+///
+/// Options are set by chaining `set_*` methods after [`SolidityGenerator::new`] before rendering.
 #[derive(Debug)]
 pub struct SolidityGenerator<'a> {
     params: &'a ParamsKZG<bn256::Bn256>,
@@ -31,6 +70,41 @@ pub struct SolidityGenerator<'a> {
     scheme: BatchOpenScheme,
     num_instances: usize,
     acc_encoding: Option<AccumulatorEncoding>,
+    vk_source: VkSource,
+    deciding_key_source: DecidingKeySource,
+    return_mode: ReturnMode,
+    point_order: PointOrder,
+    calldata_bounds_check: bool,
+    result_index: Option<usize>,
+    allowlist_index: Option<usize>,
+    transcript_prefix: bool,
+    nullifier_interface: Option<NullifierInterface>,
+    stored_accumulator_index: Option<usize>,
+    verbose_gas: bool,
+    batch_verify: bool,
+    batch_aggregate: bool,
+    non_canonical_point_check: bool,
+    pairing_failure_context: bool,
+    debug_errors: bool,
+    section_comments: bool,
+    challenge_reduction: ChallengeReduction,
+    instance_permutation: bool,
+    signed_request: bool,
+    nonce_index: Option<usize>,
+    instance_bit_widths: Vec<(usize, usize)>,
+    hex_proof: bool,
+    memory_proof: bool,
+    dynamic_instances: bool,
+    inputs_root_index: Option<usize>,
+    poseidon_preimage: Option<PoseidonPreimage>,
+    upgradeable: bool,
+    deployment_check: bool,
+    domain_constants: bool,
+    hoist_constants: bool,
+    contract_name: String,
+    verify_fn_name: String,
+    pragma: String,
+    license: String,
     meta: ConstraintSystemMeta,
 }
 
@@ -67,6 +141,11 @@ pub struct SolidityGenerator<'a> {
 ///
 /// In the end of `verifyProof`, the accumulator will be used to do batched pairing with the
 /// pairing input of incoming proof.
+///
+/// The accumulator is always read from instances, which is the layout `snark-verifier` uses for
+/// aggregation circuit verified on EVM. Accumulator carried in proof bytes is not supported. Note
+/// that without `AccumulatorEncoding` the verifier still accepts the proof but never checks the
+/// accumulator, so it must be set for recursion to be sound.
 #[derive(Clone, Copy, Debug)]
 pub struct AccumulatorEncoding {
     /// Offset of accumulator limbs in instances.
@@ -88,8 +167,251 @@ impl AccumulatorEncoding {
     }
 }
 
+/// Source of the verifying key embedded in `Halo2Verifier`.
+///
+/// It only takes effect when rendering verifier and verifying key in a single contract.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VkSource {
+    /// Verifying key is pushed as immediates and stored into memory word by word by `mstore`.
+    #[default]
+    Inline,
+    /// Verifying key is placed as a Solidity `bytes` constant, which `solc` keeps in runtime code
+    /// and copies into free memory by `codecopy` at once, then it's copied again to `VK_MPTR` by
+    /// identity precompile (`0x04`), which keeps executable code minimal.
+    BytesConstant,
+    /// Verifying key is given as constructor argument `uint256[] vk` in the order of
+    /// [`VerifierArtifact::constructor_vk`], stored in storage, and copied into memory by `sload`
+    /// word by word, which costs extra gas of cold `sload` per word. The rendered verifier
+    /// depends only on the shape of circuit, so the same bytecode can be deployed for verifying
+    /// keys differing in constants, like fixed commitments or the SRS.
+    Constructor,
+}
+
+/// Source of the deciding key (`[1]_2` and `[s]_2` of SRS) used in final pairing check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecidingKeySource {
+    /// Deciding key is embedded as part of verifying key.
+    #[default]
+    Embedded,
+    /// Deciding key is given as constructor arguments `(uint256[4] g2, uint256[4] s_g2)` and
+    /// stored as immutables, so the same verifier can be deployed against outputs of different
+    /// ceremonies of the same degree. Both points are checked to be valid by pairing precompile in
+    /// constructor.
+    ///
+    /// Each point is given as `(x_1, x_2, y_1, y_2)` with the coefficient of `u` of each `Fq2`
+    /// coordinate first, see [`encode_deciding_key`] for the exact layout.
+    ///
+    /// [`encode_deciding_key`]: crate::encode_deciding_key
+    Constructor,
+}
+
+/// How `verifyProof` of `Halo2Verifier` reports the result.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReturnMode {
+    /// Return `true` if proof is valid, otherwise revert without data.
+    #[default]
+    Bool,
+    /// Return nothing if proof is valid, otherwise revert with `InvalidProof()`, so callers can't
+    /// proceed with an invalid proof by forgetting to check the returned value.
+    Revert,
+}
+
+/// Chain which `Halo2Verifier` is generated for.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Target {
+    /// Ethereum and EVM equivalent chains, which have precompiles `ecAdd` (0x06), `ecMul` (0x07)
+    /// and `ecPairing` (0x08) from EIP-196 and EIP-197.
+    #[default]
+    Evm,
+    /// zkSync Era, which doesn't provide precompiles `ecAdd`, `ecMul` and `ecPairing`, so the
+    /// verifier can't be generated for it.
+    ZkSyncEra,
+}
+
+/// Mapping from fields of a Semaphore-style external nullifier interface
+/// `verify(uint256 signal, uint256 root, uint256 nullifierHash, bytes proof)` to indices of
+/// instances.
+///
+/// Unlike Groth16 based tooling, the proof is given as `bytes` since it doesn't fit in
+/// `uint256[8]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NullifierInterface {
+    /// Index of instance `signal` maps to.
+    pub signal: usize,
+    /// Index of instance `root` maps to.
+    pub root: usize,
+    /// Index of instance `nullifierHash` maps to.
+    pub nullifier_hash: usize,
+}
+
+impl NullifierInterface {
+    /// Return a new `NullifierInterface`.
+    pub fn new(signal: usize, root: usize, nullifier_hash: usize) -> Self {
+        Self {
+            signal,
+            root,
+            nullifier_hash,
+        }
+    }
+}
+
+/// Poseidon hash check of an additional entrypoint
+/// `verifyWithPoseidonPreimage(uint256[],bytes,uint256[])`, which requires the instance at
+/// `instance_index` to be Poseidon hash of given `preimage_len` field elements, computed on-chain
+/// by calling `poseidon(uint256[preimage_len])` of contract at `hasher`.
+///
+/// The `hasher` is expected to have the same interface as circomlib generated Poseidon contract,
+/// and its parameters (width, number of rounds, round constants and MDS matrix) must match the
+/// ones used in circuit, otherwise no preimage passes the check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoseidonPreimage {
+    /// Index of instance to be checked against Poseidon hash of preimage.
+    pub instance_index: usize,
+    /// Number of field elements in preimage.
+    pub preimage_len: usize,
+    /// Address of contract computing Poseidon hash.
+    pub hasher: [u8; 20],
+}
+
+impl PoseidonPreimage {
+    /// Return a new `PoseidonPreimage`.
+    pub fn new(instance_index: usize, preimage_len: usize, hasher: [u8; 20]) -> Self {
+        Self {
+            instance_index,
+            preimage_len,
+            hasher,
+        }
+    }
+}
+
+/// Error returned by [`SolidityGenerator::encode_verify_calldata`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CalldataError {
+    /// Instances are given in more than 1 column.
+    NumInstanceColumns(usize),
+    /// Number of instances differs from the one `Halo2Verifier` is generated for.
+    NumInstances {
+        /// Expected number of instances.
+        expected: usize,
+        /// Actual number of instances.
+        actual: usize,
+    },
+    /// Length of proof differs from the one `Halo2Verifier` expects.
+    ProofLen {
+        /// Expected length of proof.
+        expected: usize,
+        /// Actual length of proof.
+        actual: usize,
+    },
+}
+
+impl Display for CalldataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NumInstanceColumns(num_columns) => write!(
+                f,
+                "instances are given in {num_columns} columns but at most 1 is supported"
+            ),
+            Self::NumInstances { expected, actual } => write!(
+                f,
+                "{actual} instances are given but {expected} instances are expected"
+            ),
+            Self::ProofLen { expected, actual } => write!(
+                f,
+                "proof has {actual} bytes but {expected} bytes are expected"
+            ),
+        }
+    }
+}
+
+impl Error for CalldataError {}
+
+/// Error returned by rendering methods of [`SolidityGenerator`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GenerateError {
+    /// Options are set that can't be rendered together.
+    IncompatibleOptions(&'static str),
+    /// Option is set to a value invalid for the circuit or the option itself.
+    InvalidOption {
+        /// Name of the option.
+        option: &'static str,
+        /// Why the value is invalid.
+        reason: String,
+    },
+    /// Contract or function name is not a valid Solidity identifier.
+    InvalidIdentifier(String),
+    /// Version pragma is not a version range `solc` accepts, or admits version below `0.8.13`.
+    InvalidPragma(String),
+    /// License is not a single line SPDX license expression.
+    InvalidLicense(String),
+    /// Writer fails.
+    Fmt(fmt::Error),
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IncompatibleOptions(options) => {
+                write!(f, "{options} can't be rendered together")
+            }
+            Self::InvalidOption { option, reason } => write!(f, "invalid {option}: {reason}"),
+            Self::InvalidIdentifier(name) => write!(f, "invalid identifier {name:?}"),
+            Self::InvalidPragma(pragma) => write!(f, "invalid pragma {pragma:?}"),
+            Self::InvalidLicense(license) => write!(f, "invalid license {license:?}"),
+            Self::Fmt(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for GenerateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Fmt(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<fmt::Error> for GenerateError {
+    fn from(err: fmt::Error) -> Self {
+        Self::Fmt(err)
+    }
+}
+
+/// Rendered `Halo2Verifier` with verifying key embedded, together with what tooling needs to
+/// deploy and call it without parsing the source, see [`SolidityGenerator::render_artifact`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierArtifact {
+    /// Solidity source.
+    pub solidity: String,
+    /// Name of contract in `solidity`.
+    pub contract_name: String,
+    /// SPDX license identifier of `solidity`, see [`SolidityGenerator::set_license`].
+    pub license: String,
+    /// Version pragma of `solidity`, see [`SolidityGenerator::set_pragma`].
+    pub pragma: String,
+    /// Name of the verification function, see [`SolidityGenerator::set_verify_fn_name`].
+    pub verify_fn_name: String,
+    /// Whether the verification function takes an extra argument `bytes prefix` in the end, see
+    /// [`SolidityGenerator::set_transcript_prefix`].
+    pub transcript_prefix: bool,
+    /// Number of instances `verifyProof` expects.
+    pub num_instances: usize,
+    /// Length of proof in bytes `verifyProof` expects.
+    pub proof_len: usize,
+    /// Fields of proof in the order `Halo2Verifier` reads them.
+    pub proof_schema: Vec<ProofField>,
+    /// Named words of verifying key in the order constructor argument `uint256[] vk` expects, if
+    /// `VkSource` is [`VkSource::Constructor`].
+    pub constructor_vk: Option<Vec<(String, U256)>>,
+}
+
 impl<'a> SolidityGenerator<'a> {
     /// Return a new `SolidityGenerator`.
+    ///
+    /// Fixed commitments are taken from `vk` as they are, so the generated verifier follows
+    /// whatever selector compression was applied at key generation, as long as proofs are created
+    /// with the proving key derived from the same `vk`.
     pub fn new(
         params: &'a ParamsKZG<bn256::Bn256>,
         vk: &'a VerifyingKey<bn256::G1Affine>,
@@ -120,28 +442,702 @@ impl<'a> SolidityGenerator<'a> {
             scheme,
             num_instances,
             acc_encoding: None,
+            vk_source: VkSource::default(),
+            deciding_key_source: DecidingKeySource::default(),
+            return_mode: ReturnMode::default(),
+            point_order: PointOrder::default(),
+            calldata_bounds_check: false,
+            result_index: None,
+            allowlist_index: None,
+            transcript_prefix: false,
+            nullifier_interface: None,
+            stored_accumulator_index: None,
+            verbose_gas: false,
+            batch_verify: false,
+            batch_aggregate: false,
+            non_canonical_point_check: false,
+            pairing_failure_context: false,
+            debug_errors: false,
+            section_comments: false,
+            challenge_reduction: ChallengeReduction::default(),
+            instance_permutation: false,
+            signed_request: false,
+            nonce_index: None,
+            instance_bit_widths: Vec::new(),
+            hex_proof: false,
+            memory_proof: false,
+            dynamic_instances: false,
+            inputs_root_index: None,
+            poseidon_preimage: None,
+            upgradeable: false,
+            deployment_check: false,
+            domain_constants: false,
+            hoist_constants: false,
+            contract_name: "Halo2Verifier".to_string(),
+            verify_fn_name: "verifyProof".to_string(),
+            pragma: "^0.8.13".to_string(),
+            license: "MIT".to_string(),
             meta: ConstraintSystemMeta::new(vk.cs()),
         }
     }
 
     /// Set `AccumulatorEncoding`.
+    ///
+    /// Rendering fails if limbs can't hold a base field element, which is the case when the
+    /// accumulator comes from a circuit that represents the non-native base field with too few
+    /// limbs.
     pub fn set_acc_encoding(mut self, acc_encoding: Option<AccumulatorEncoding>) -> Self {
         self.acc_encoding = acc_encoding;
         self
     }
+
+    /// Set `VkSource`.
+    ///
+    /// [`VkSource::Constructor`] is not supported with deployment check, domain constants or
+    /// upgradeable verifier, which rely on verifying key known at generation time or at
+    /// construction of implementation.
+    pub fn set_vk_source(mut self, vk_source: VkSource) -> Self {
+        self.vk_source = vk_source;
+        self
+    }
+
+    /// Set `DecidingKeySource`.
+    pub fn set_deciding_key_source(mut self, deciding_key_source: DecidingKeySource) -> Self {
+        self.deciding_key_source = deciding_key_source;
+        self
+    }
+
+    /// Set `ReturnMode` of `verifyProof`, which additional entrypoints calling it also follow to
+    /// tell whether proof is accepted.
+    pub fn set_return_mode(mut self, return_mode: ReturnMode) -> Self {
+        self.return_mode = return_mode;
+        self
+    }
+
+    /// Set `PointOrder` of EC point coordinates in proof, which should match the encoding of proof
+    /// passed to verifier (see [`reorder_proof_points`]).
+    ///
+    /// [`reorder_proof_points`]: crate::reorder_proof_points
+    pub fn set_point_order(mut self, point_order: PointOrder) -> Self {
+        self.point_order = point_order;
+        self
+    }
+
+    /// Set `ChallengeReduction`, which should match the one of [`Keccak256Transcript`] used to
+    /// create proof.
+    ///
+    /// [`Keccak256Transcript`]: crate::Keccak256Transcript
+    pub fn set_challenge_reduction(mut self, challenge_reduction: ChallengeReduction) -> Self {
+        self.challenge_reduction = challenge_reduction;
+        self
+    }
+
+    /// Set whether to check the length of `instances` read at fixed calldata offset fits within
+    /// `calldatasize()` before reading them, which reverts with `CalldataOutOfBounds()` otherwise.
+    pub fn set_calldata_bounds_check(mut self, calldata_bounds_check: bool) -> Self {
+        self.calldata_bounds_check = calldata_bounds_check;
+        self
+    }
+
+    /// Set whether to revert with `NonCanonicalPoint()` as soon as a coordinate of proof point is
+    /// not less than base field modulus. Such point is always rejected regardless, this only makes
+    /// the failure explicit instead of reverting without data in the end.
+    pub fn set_non_canonical_point_check(mut self, non_canonical_point_check: bool) -> Self {
+        self.non_canonical_point_check = non_canonical_point_check;
+        self
+    }
+
+    /// Set whether to revert with `PairingFailed(uint256 theta)` when only the final pairing check
+    /// fails, where `theta` is the first challenge squeezed after advice commitments. It's meant
+    /// for debugging, since comparing `theta` against native verifier tells whether the divergence
+    /// happens before or after reading advice commitments.
+    pub fn set_pairing_failure_context(mut self, pairing_failure_context: bool) -> Self {
+        self.pairing_failure_context = pairing_failure_context;
+        self
+    }
+
+    /// Set whether to revert with a distinct error at each stage of `verifyProof`, for debugging:
+    /// - `TranscriptMismatch()` if anything read from calldata is invalid, e.g. proof length or
+    ///   point not on curve.
+    /// - `OpeningFailed()` if any precompile call fails when computing the batch opening.
+    /// - `PairingFailed(uint256 theta)` if only the final pairing check fails, as
+    ///   [`SolidityGenerator::set_pairing_failure_context`] does, which is also where wrong
+    ///   instances or their ordering end up since they only change the challenges.
+    ///
+    /// It takes precedence over [`ReturnMode`], and should be disabled in production since the
+    /// extra checks cost gas.
+    pub fn set_debug_errors(mut self, debug_errors: bool) -> Self {
+        self.debug_errors = debug_errors;
+        self
+    }
+
+    /// Set whether to label the top-level blocks of the assembly in `verifyProof` by section
+    /// comments for audit, which are `transcript`, `evaluate gates`, `opening` and `pairing` in
+    /// order. Each section starts at a block boundary, and the rendered code is otherwise the
+    /// same.
+    pub fn set_section_comments(mut self, section_comments: bool) -> Self {
+        self.section_comments = section_comments;
+        self
+    }
+
+    /// Set index of instance to be returned as result by an additional entrypoint
+    /// `verifyAndGet(uint256[],bytes)`, which verifies proof and reverts on failure.
+    pub fn set_result_index(mut self, result_index: Option<usize>) -> Self {
+        self.result_index = result_index;
+        self
+    }
+
+    /// Set index of instance to be checked against a merkle root of allowlist by an additional
+    /// entrypoint `verifyInAllowlist(uint256[],bytes,bytes32[])`, which also verifies proof.
+    ///
+    /// The merkle root is given as constructor argument `bytes32 allowlist_root`, after the
+    /// deciding key if [`DecidingKeySource::Constructor`] is set. The tree uses sorted pair hashing
+    /// by `keccak256`, and each leaf is `keccak256` of the 32 bytes big-endian instance.
+    pub fn set_allowlist_index(mut self, allowlist_index: Option<usize>) -> Self {
+        self.allowlist_index = allowlist_index;
+        self
+    }
+
+    /// Set whether `verifyProof` takes an extra argument `bytes prefix` in the end, whose
+    /// `keccak256` hash is absorbed into transcript before verifying key digest, to bind proof to
+    /// arbitrary context. Proof should be created with transcript given by
+    /// [`Keccak256Transcript::new_with_prefix`] with the same prefix.
+    ///
+    /// Additional entrypoints take no prefix to forward, so rendering fails with
+    /// [`GenerateError::IncompatibleOptions`] if any of them is set together.
+    ///
+    /// [`Keccak256Transcript::new_with_prefix`]: crate::Keccak256Transcript::new_with_prefix
+    pub fn set_transcript_prefix(mut self, transcript_prefix: bool) -> Self {
+        self.transcript_prefix = transcript_prefix;
+        self
+    }
+
+    /// Set `NullifierInterface` to have an additional entrypoint
+    /// `verify(uint256,uint256,uint256,bytes)`, which maps given fields to instances and verifies
+    /// proof.
+    ///
+    /// Rendering fails if number of instances is not 3, or given indices are not distinct.
+    pub fn set_nullifier_interface(
+        mut self,
+        nullifier_interface: Option<NullifierInterface>,
+    ) -> Self {
+        self.nullifier_interface = nullifier_interface;
+        self
+    }
+
+    /// Set index of instance to be bound to an accumulator commitment stored in `Halo2Verifier`,
+    /// which makes `verifyProof` revert with `StaleAccumulator()` when the instance differs from
+    /// the stored `accumulator`.
+    ///
+    /// The stored `accumulator` is initially zero, and can only be updated by the deployer via
+    /// `updateAccumulator(uint256)`. Note that this is unrelated to [`AccumulatorEncoding`], which
+    /// is the KZG accumulator of aggregated proofs checked in the pairing.
+    pub fn set_stored_accumulator_index(mut self, stored_accumulator_index: Option<usize>) -> Self {
+        self.stored_accumulator_index = stored_accumulator_index;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyVerboseGas(uint256[],bytes)`, which
+    /// returns `(bool ok, uint256 gasUsed)` with `ok` being the result of `verifyProof` and
+    /// `gasUsed` being the gas consumed by it measured by `gasleft()`, for gas profiling via RPC.
+    ///
+    /// It also has `verifyManyVerbose(uint256[][],bytes[])`, which returns
+    /// `(bool[] oks, uint256[] gasEach)` of the same measurement for each proof, to profile many
+    /// proofs without a round-trip per proof.
+    pub fn set_verbose_gas(mut self, verbose_gas: bool) -> Self {
+        self.verbose_gas = verbose_gas;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyBatch(uint256[][],bytes[])`, which
+    /// verifies each proof of the same circuit by calling `verifyProof` and returns `false` as soon
+    /// as any fails, to pay the transaction base cost once for many proofs. Each proof still goes
+    /// through a full verification including its own pairing.
+    pub fn set_batch_verify(mut self, batch_verify: bool) -> Self {
+        self.batch_verify = batch_verify;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyBatchAggregated(uint256[][],bytes[])`,
+    /// which verifies each proof of the same circuit but defers its pairing, then combines pairing
+    /// inputs of all proofs by random linear combination with challenge derived from all proofs,
+    /// instances and pairing inputs, and performs a single pairing for the whole batch. It returns
+    /// `false` if any proof fails.
+    ///
+    /// To defer pairing, `verifyProof` returns its pairing input instead when called by
+    /// `Halo2Verifier` itself with a word appended to canonical calldata.
+    pub fn set_batch_aggregate(mut self, batch_aggregate: bool) -> Self {
+        self.batch_aggregate = batch_aggregate;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyPermuted(uint256[],uint256[],bytes)`,
+    /// which takes instances in arbitrary order with a permutation, where `instances[i]` is moved
+    /// to index `permutation[i]` before verifying proof. It reverts with `InvalidPermutation()` if
+    /// the permutation is not a bijection over indices of instances.
+    pub fn set_instance_permutation(mut self, instance_permutation: bool) -> Self {
+        self.instance_permutation = instance_permutation;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint
+    /// `verifySigned(address,uint256[],bytes,uint8,bytes32,bytes32)` for relayed submission, which
+    /// takes `signer`, `instances`, `proof` and signature `(v, r, s)` of EIP-712 typed data
+    /// `ProofRequest(uint256[] instances,bytes32 proofHash,uint256 nonce)` signed by `signer`, where
+    /// `nonce` is `signerNonces(signer)` and is consumed on success so the request can't be
+    /// replayed. It reverts with `InvalidSignature()` if the signature doesn't match or has high
+    /// `s`, otherwise verifies proof.
+    ///
+    /// Any `signer` is accepted, so callers relying on it for authorization should check `signer`
+    /// against the expected one themselves.
+    ///
+    /// The domain is `EIP712Domain(string name,string version,uint256 chainId,address
+    /// verifyingContract)` with name of the contract (see [`SolidityGenerator::set_contract_name`])
+    /// and version `1`. The digest to sign can be computed by [`eip712_proof_request_digest`].
+    ///
+    /// [`eip712_proof_request_digest`]: crate::eip712_proof_request_digest
+    pub fn set_signed_request(mut self, signed_request: bool) -> Self {
+        self.signed_request = signed_request;
+        self
+    }
+
+    /// Set index of instance to be bound to a per-submitter nonce by an additional entrypoint
+    /// `verifyWithNonce(uint256[],bytes)`, which reverts with `InvalidNonce()` if the instance is
+    /// not `nonces(msg.sender)`, otherwise verifies proof and increments the nonce. Unlike other
+    /// entrypoints, it writes storage so it can't be called in static context.
+    ///
+    /// The nonce isn't bound to `msg.sender` inside the proof, so a pending call can be front-run
+    /// by anyone resubmitting the same instances and proof from their own address. Circuits that
+    /// need to prevent this should also expose the submitter as an instance that callers check
+    /// against `uint160(msg.sender)`.
+    pub fn set_nonce_index(mut self, nonce_index: Option<usize>) -> Self {
+        self.nonce_index = nonce_index;
+        self
+    }
+
+    /// Set `(index, bit_width)` of instances to be range checked by `verifyProof` before
+    /// verification, which reverts with `InstanceOutOfRange(index)` if the instance is not less
+    /// than `2^bit_width`, for circuits relying on verifier to reject instances outside intended
+    /// domain.
+    ///
+    /// Rendering fails if any index is not less than number of instances, or any bit width is not
+    /// in range `1..254`.
+    pub fn set_instance_bit_widths(mut self, instance_bit_widths: Vec<(usize, usize)>) -> Self {
+        self.instance_bit_widths = instance_bit_widths;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyHex(uint256[],string)`, which decodes
+    /// proof given as hex string with optional `0x` prefix and verifies it, for integrations that
+    /// pass proof as string. It reverts with `InvalidHex()` if the string is not valid hex.
+    ///
+    /// Decoding on-chain costs gas proportional to length of proof, so decoding off-chain by
+    /// [`decode_hex_proof`] and calling `verifyProof` should be preferred.
+    ///
+    /// [`decode_hex_proof`]: crate::decode_hex_proof
+    pub fn set_hex_proof(mut self, hex_proof: bool) -> Self {
+        self.hex_proof = hex_proof;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verifyMemory(uint256[],bytes)` taking proof
+    /// and instances as `memory`, for contracts inheriting `Halo2Verifier` that already have them
+    /// in memory. It renders the verification core of `verifyProof` a second time with loads from
+    /// memory instead of calldata, so they never diverge, at the cost of roughly doubled bytecode.
+    pub fn set_memory_proof(mut self, memory_proof: bool) -> Self {
+        self.memory_proof = memory_proof;
+        self
+    }
+
+    /// Set whether to have an additional entrypoint `verify(uint256[],bytes)` taking instances as
+    /// dynamic array before proof, with the same ABI for circuits of any number of instances, so a
+    /// single router contract can call verifiers of different circuits by selector. It reverts
+    /// when the number of instances differs, before touching proof.
+    pub fn set_dynamic_instances(mut self, dynamic_instances: bool) -> Self {
+        self.dynamic_instances = dynamic_instances;
+        self
+    }
+
+    /// Set index of instance to be checked against a governance-updatable merkle root of
+    /// authorized inputs by an additional entrypoint
+    /// `verifyWithInputsRoot(uint256[],bytes,bytes32[])`, which reverts with `InvalidInputs()` if
+    /// the instance is not a leaf under the current `validInputsRoot`, otherwise verifies proof.
+    ///
+    /// Unlike [`SolidityGenerator::set_allowlist_index`] whose root is immutable, the stored
+    /// `validInputsRoot` is initially zero, and can only be updated by the deployer via
+    /// `updateValidInputsRoot(bytes32)`. The tree uses the same sorted pair hashing by `keccak256`.
+    pub fn set_inputs_root_index(mut self, inputs_root_index: Option<usize>) -> Self {
+        self.inputs_root_index = inputs_root_index;
+        self
+    }
+
+    /// Set `PoseidonPreimage` to have an additional entrypoint
+    /// `verifyWithPoseidonPreimage(uint256[],bytes,uint256[])`, which reverts with
+    /// `InvalidPreimage()` if Poseidon hash of the preimage doesn't equal the designated instance,
+    /// otherwise verifies proof. Hashing costs an extra external call and is app-specific, so it's
+    /// disabled by default.
+    ///
+    /// Rendering fails if instance index is out of range or preimage is empty.
+    pub fn set_poseidon_preimage(mut self, poseidon_preimage: Option<PoseidonPreimage>) -> Self {
+        self.poseidon_preimage = poseidon_preimage;
+        self
+    }
+
+    /// Set whether `Halo2Verifier` is a UUPS implementation to be deployed behind an ERC-1967
+    /// proxy, so verification logic and verifying key can be swapped by upgrading.
+    ///
+    /// It has `upgradeToAndCall(address,bytes)` and `proxiableUUID()` by ERC-1822. The admin is the
+    /// deployer of the implementation, kept as immutable `ADMIN()` in its code instead of proxy
+    /// storage, so upgrading to an implementation hands over admin to its deployer. Upgrade by
+    /// anyone other than admin reverts with `Unauthorized()`, and revert data of the call after
+    /// upgrade is bubbled up.
+    pub fn set_upgradeable(mut self, upgradeable: bool) -> Self {
+        self.upgradeable = upgradeable;
+        self
+    }
+
+    /// Set whether the constructor checks embedded G1 points are on curve, and G2 points are on
+    /// curve and in subgroup by pairing precompile, to revert deployment of `Halo2Verifier` with
+    /// corrupted constants before any proof is submitted. It costs extra gas only once at
+    /// deployment. Only available when verifying key is embedded.
+    pub fn set_deployment_check(mut self, deployment_check: bool) -> Self {
+        self.deployment_check = deployment_check;
+        self
+    }
+
+    /// Set whether to expose `uint256 public constant K` and `N` as `log2` of domain size and domain
+    /// size, so integrators can confirm which circuit size `Halo2Verifier` is built for. Only
+    /// available when verifying key is embedded, since `Halo2Verifier` rendered by
+    /// `render_separately` works for any `k`.
+    pub fn set_domain_constants(mut self, domain_constants: bool) -> Self {
+        self.domain_constants = domain_constants;
+        self
+    }
+
+    /// Set whether to store 32-byte constants repeated in quotient evaluation, e.g. the ones
+    /// scaling limbs in range checks, into scratch memory once and `mload` them where used,
+    /// instead of pushing them inline each time, to shrink runtime bytecode. Only as many
+    /// constants as scratch memory has room for are hoisted, most repeated first.
+    pub fn set_hoist_constants(mut self, hoist_constants: bool) -> Self {
+        self.hoist_constants = hoist_constants;
+        self
+    }
+
+    /// Set name of the verifier contract, which is `Halo2Verifier` by default, so verifiers of
+    /// different circuits don't collide by name in the same project. It's also the EIP-712 domain
+    /// name of [`SolidityGenerator::set_signed_request`].
+    ///
+    /// Rendering fails if `contract_name` is not a valid Solidity identifier.
+    pub fn set_contract_name(mut self, contract_name: impl Into<String>) -> Self {
+        self.contract_name = contract_name.into();
+        self
+    }
+
+    /// Set name of the verification function, which is `verifyProof` by default, e.g. `verify` to
+    /// conform to the interface a router expects without a wrapper. Wrappers by `render_pausable`,
+    /// `render_router` and `scaffold_project` follow the name.
+    ///
+    /// Calldata encoded by [`encode_calldata`] and its variants invokes `verifyProof`, so the
+    /// function selector should be replaced by [`SolidityGenerator::verify_fn_selector`], e.g. by
+    /// [`VerifyCall::set_verify_fn_selector`]. Additional entrypoints call the renamed function.
+    ///
+    /// [`VerifyCall::set_verify_fn_selector`]: crate::VerifyCall::set_verify_fn_selector
+    ///
+    /// Rendering fails if `verify_fn_name` is not a valid Solidity identifier.
+    pub fn set_verify_fn_name(mut self, verify_fn_name: impl Into<String>) -> Self {
+        self.verify_fn_name = verify_fn_name.into();
+        self
+    }
+
+    /// Set version pragma of `Halo2Verifier.sol`, `Halo2VerifyingKey.sol` and the other contracts
+    /// rendered along, which is `^0.8.13` by default, e.g. `^0.8.19` or `>=0.8.13 <0.9.0` to match
+    /// compiler of the project.
+    ///
+    /// Rendering fails if `pragma` is not a version range `solc` accepts, or it admits any version
+    /// below `0.8.13`, which can't compile rendered contracts.
+    pub fn set_pragma(mut self, pragma: impl Into<String>) -> Self {
+        self.pragma = pragma.into();
+        self
+    }
+
+    /// Set SPDX license identifier in the first line of `Halo2Verifier.sol`,
+    /// `Halo2VerifyingKey.sol` and the other contracts rendered along, which is `MIT` by default,
+    /// e.g. `Apache-2.0` or `MIT OR Apache-2.0`.
+    ///
+    /// Rendering fails if `license` is not a single line SPDX license expression.
+    pub fn set_license(mut self, license: impl Into<String>) -> Self {
+        self.license = license.into();
+        self
+    }
 }
 
 impl<'a> SolidityGenerator<'a> {
+    /// Return length of proof in bytes `Halo2Verifier` expects, which counts commitments,
+    /// evaluations and opening proof, to validate proof before submitting transaction.
+    pub fn proof_len(&self) -> usize {
+        self.meta.proof_len(self.scheme)
+    }
+
+    /// Encode `proof` and `instances` given per instance column, as `halo2_proofs` takes them,
+    /// into calldata to invoke `verifyProof`, after checking their shape against what
+    /// `Halo2Verifier` expects.
+    ///
+    /// For `vk_address`, see [`encode_calldata`].
+    ///
+    /// # Panics
+    /// Panics if [`SolidityGenerator::set_transcript_prefix`] is set, in which case
+    /// [`encode_calldata_with_prefix`] should be used.
+    ///
+    /// [`encode_calldata_with_prefix`]: crate::encode_calldata_with_prefix
+    pub fn encode_verify_calldata(
+        &self,
+        vk_address: Option<[u8; 20]>,
+        proof: &[u8],
+        instances: &[Vec<bn256::Fr>],
+    ) -> Result<Vec<u8>, CalldataError> {
+        assert!(
+            !self.transcript_prefix,
+            "Calldata with transcript prefix should be encoded by encode_calldata_with_prefix"
+        );
+        if instances.len() > 1 {
+            return Err(CalldataError::NumInstanceColumns(instances.len()));
+        }
+        let instances = instances.first().map(Vec::as_slice).unwrap_or_default();
+        if instances.len() != self.num_instances {
+            return Err(CalldataError::NumInstances {
+                expected: self.num_instances,
+                actual: instances.len(),
+            });
+        }
+        if proof.len() != self.proof_len() {
+            return Err(CalldataError::ProofLen {
+                expected: self.proof_len(),
+                actual: proof.len(),
+            });
+        }
+        let mut calldata = encode_calldata(vk_address, proof, instances);
+        calldata[..4].copy_from_slice(&self.verify_fn_selector(vk_address.is_some()));
+        Ok(calldata)
+    }
+
+    /// Return fields of proof in the order `Halo2Verifier` reads them.
+    pub fn proof_schema(&self) -> Vec<ProofField> {
+        self.meta.proof_schema(self.scheme)
+    }
+
+    /// Return a walkthrough of the verification steps `Halo2Verifier` performs, derived from the
+    /// same metadata used to render it.
+    pub fn explain(&self) -> String {
+        let vk = self.generate_vk();
+        let mock_vk_mptr = Ptr::memory(0x100000);
+        let mock = Data::new(
+            &self.meta,
+            &vk,
+            mock_vk_mptr,
+            Ptr::calldata(0x64),
+            self.point_order,
+        );
+        let (superset, sets) = rotation_sets(&queries(&self.meta, &mock));
+        let num_challenges = self.meta.num_challenges();
+        let num_pcs_challenges = match self.scheme {
+            Bdfg21 => 3,
+            Gwc19 => unimplemented!(),
+        };
+
+        let mut steps = vec![
+            format!(
+                "Load verifying key with {} fixed commitment(s) and {} permutation commitment(s), \
+                 and absorb its digest into transcript.",
+                vk.fixed_comms.len(),
+                vk.permutation_comms.len(),
+            ),
+            format!(
+                "Check proof has {} bytes and there are {} instance(s), each less than r, then \
+                 absorb instances into transcript.",
+                self.meta.proof_len(self.scheme),
+                self.num_instances,
+            ),
+        ];
+        steps.extend(izip!(self.meta.num_advices(), &num_challenges).map(
+            |(num_advices, num_challenges)| {
+                format!(
+                    "Read {num_advices} commitment(s), check they are on curve, and squeeze \
+                     {num_challenges} challenge(s).",
+                )
+            },
+        ));
+        steps.extend([
+            format!(
+                "Read {} evaluation(s), each less than r, and squeeze {} challenge(s) for batch \
+                 opening.",
+                self.meta.num_evals, num_pcs_challenges,
+            ),
+            format!(
+                "Compute {} Lagrange evaluation(s) and instance evaluation at x.",
+                self.meta.rotation_last.unsigned_abs() as usize + self.num_instances,
+            ),
+            format!(
+                "Evaluate quotient numerator of {} gate(s), {} permutation column(s) and {} \
+                 lookup(s) at x, and derive quotient evaluation.",
+                self.vk.cs().gates().len(),
+                self.meta.num_permutations(),
+                self.meta.num_lookups(),
+            ),
+            format!(
+                "Batch open {} commitment(s) at {} point(s) in {} rotation set(s) by {:?}.",
+                sets.iter().map(|set| set.comms().len()).sum::<usize>(),
+                superset.len(),
+                sets.len(),
+                self.scheme,
+            ),
+        ]);
+        if let Some(acc_encoding) = self.acc_encoding {
+            steps.push(format!(
+                "Decode accumulator from instances at offset {} with {} limb(s) of {} bit(s), and \
+                 batch it into pairing input by a random challenge.",
+                acc_encoding.offset, acc_encoding.num_limbs, acc_encoding.num_limb_bits,
+            ));
+        }
+        steps.push("Perform 1 pairing check e(lhs, [1]_2) * e(rhs, -[s]_2) == 1.".to_string());
+
+        let total_challenges = num_challenges.iter().sum::<usize>() + num_pcs_challenges;
+        chain![
+            [format!(
+                "Halo2Verifier squeezes {total_challenges} challenges in total and performs 1 \
+                 pairing check:"
+            )],
+            steps
+                .iter()
+                .enumerate()
+                .map(|(idx, step)| format!("{}. {step}", idx + 1)),
+        ]
+        .join("\n")
+    }
+
     /// Render `Halo2Verifier.sol` with verifying key embedded into writer.
-    pub fn render_into(&self, verifier_writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
-        self.generate_verifier(false).render(verifier_writer)
+    pub fn render_into(&self, verifier_writer: &mut impl fmt::Write) -> Result<(), GenerateError> {
+        Ok(self.generate_verifier(false)?.render(verifier_writer)?)
     }
 
     /// Render `Halo2Verifier.sol` with verifying key embedded and return it as `String`.
-    pub fn render(&self) -> Result<String, fmt::Error> {
+    pub fn render(&self) -> Result<String, GenerateError> {
+        self.render_artifact().map(|artifact| artifact.solidity)
+    }
+
+    /// Render `Halo2Verifier.sol` with verifying key embedded and return it as `VerifierArtifact`.
+    pub fn render_artifact(&self) -> Result<VerifierArtifact, GenerateError> {
         let mut verifier_output = String::new();
         self.render_into(&mut verifier_output)?;
-        Ok(verifier_output)
+        Ok(VerifierArtifact {
+            solidity: verifier_output,
+            contract_name: self.contract_name.clone(),
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
+            verify_fn_name: self.verify_fn_name.clone(),
+            transcript_prefix: self.transcript_prefix,
+            num_instances: self.num_instances,
+            proof_len: self.proof_len(),
+            proof_schema: self.proof_schema(),
+            constructor_vk: (self.vk_source == VkSource::Constructor)
+                .then(|| self.generate_vk().words()),
+        })
+    }
+
+    /// Render only the verification core of `Halo2Verifier.sol` with verifying key embedded, for
+    /// splicing into hand-written Solidity, and return it as `(constants, assembly)`.
+    ///
+    /// The `constants` are `uint256 internal constant` declarations to be placed at contract
+    /// level, and `assembly` is the `assembly { ... }` block to be placed as the body of a function
+    /// whose parameters are exactly `(bytes calldata proof, uint256[] calldata instances)`, with
+    /// any name. The block relies on the following:
+    /// - Calldata is canonically encoded, so proof length is at `PROOF_LEN_CPTR`, proof at
+    ///   `PROOF_CPTR`, and instances length and instances at `NUM_INSTANCE_CPTR` and
+    ///   `INSTANCE_CPTR`.
+    /// - Memory from `0x00` is used as scratch space without respecting free memory pointer, so
+    ///   no Solidity memory variable remains valid inside the block.
+    /// - It never falls through, but either `return`s as `verifyProof` does by [`ReturnMode`] or
+    ///   `revert`s, so any code after it in the same function is unreachable.
+    ///
+    /// It fails with [`GenerateError::IncompatibleOptions`] if any option referencing Solidity
+    /// variables outside the block is set, which are `VkSource` other than [`VkSource::Inline`],
+    /// [`DecidingKeySource::Constructor`], transcript prefix, stored accumulator and instance bit
+    /// widths.
+    pub fn render_core_assembly(&self) -> Result<(String, String), GenerateError> {
+        if self.vk_source != VkSource::Inline
+            || self.deciding_key_source == DecidingKeySource::Constructor
+            || self.transcript_prefix
+            || self.stored_accumulator_index.is_some()
+            || !self.instance_bit_widths.is_empty()
+        {
+            return Err(GenerateError::IncompatibleOptions(
+                "Core assembly and options referencing Solidity variables",
+            ));
+        }
+
+        let verifier = self.generate_verifier(false)?;
+        let mut constants = String::new();
+        Halo2VerifierConstants(&verifier).render(&mut constants)?;
+        constants.push('\n');
+        let mut assembly = String::new();
+        Halo2VerifierAssembly(&verifier).render(&mut assembly)?;
+        assembly.push('\n');
+        Ok((constants, assembly))
+    }
+
+    /// Render the verifier as a standalone Yul object without Solidity ABI dispatcher, which has
+    /// `code` deploying `runtime`, and `runtime` being the core assembly of
+    /// [`SolidityGenerator::render_core_assembly`] with constants inlined.
+    ///
+    /// The runtime still expects calldata of `verifyProof` canonically encoded as by
+    /// [`encode_calldata`], and reverts if the function selector doesn't match, then either
+    /// `return`s as `verifyProof` does by [`ReturnMode`] or `revert`s.
+    ///
+    /// It fails in the same cases as [`SolidityGenerator::render_core_assembly`].
+    pub fn render_yul(&self) -> Result<String, GenerateError> {
+        let (constants, assembly) = self.render_core_assembly()?;
+        let constants = constants
+            .lines()
+            .filter_map(|line| {
+                let (name, value) = line.split_once('=')?;
+                let name = name.split_whitespace().last()?;
+                Some((
+                    name.to_string(),
+                    value.trim().trim_end_matches(';').to_string(),
+                ))
+            })
+            .collect::<HashMap<_, _>>();
+        let lines = assembly.lines().collect_vec();
+        let body = lines[1..lines.len() - 1]
+            .iter()
+            .map(|line| map_tokens(line, |token| constants.get(token).cloned()))
+            .join("\n");
+        let selector = hex::encode(self.verify_fn_selector(false));
+
+        let mut output = String::new();
+        writeln!(output, "object \"{}\" {{", self.contract_name)?;
+        writeln!(output, "    code {{")?;
+        writeln!(
+            output,
+            "        datacopy(0, dataoffset(\"runtime\"), datasize(\"runtime\"))"
+        )?;
+        writeln!(output, "        return(0, datasize(\"runtime\"))")?;
+        writeln!(output, "    }}")?;
+        writeln!(output, "    object \"runtime\" {{")?;
+        writeln!(output, "        code {{")?;
+        writeln!(
+            output,
+            "            // Accept only calldata of `{}`",
+            self.verify_fn_name
+        )?;
+        writeln!(
+            output,
+            "            if iszero(eq(shr(224, calldataload(0)), 0x{selector})) {{ revert(0, 0) }}"
+        )?;
+        writeln!(output, "{body}")?;
+        writeln!(output, "        }}")?;
+        writeln!(output, "    }}")?;
+        writeln!(output, "}}")?;
+        Ok(output)
     }
 
     /// Render `Halo2Verifier.sol` and `Halo2VerifyingKey.sol` into writers.
@@ -149,20 +1145,347 @@ impl<'a> SolidityGenerator<'a> {
         &self,
         verifier_writer: &mut impl fmt::Write,
         vk_writer: &mut impl fmt::Write,
-    ) -> Result<(), fmt::Error> {
-        self.generate_verifier(true).render(verifier_writer)?;
+    ) -> Result<(), GenerateError> {
+        self.generate_verifier(true)?.render(verifier_writer)?;
         self.generate_vk().render(vk_writer)?;
         Ok(())
     }
 
     /// Render `Halo2Verifier.sol` and `Halo2VerifyingKey.sol` and return them as `String`.
-    pub fn render_separately(&self) -> Result<(String, String), fmt::Error> {
+    ///
+    /// This is also the way to stay under the runtime code size limit of EIP-170 when verifier
+    /// with verifying key embedded is too large, since `Halo2VerifyingKey` holds only the
+    /// constants as its runtime code, which `Halo2Verifier` copies by `extcodecopy` from the
+    /// address given in calldata.
+    pub fn render_separately(&self) -> Result<(String, String), GenerateError> {
         let mut verifier_output = String::new();
         let mut vk_output = String::new();
         self.render_separately_into(&mut verifier_output, &mut vk_output)?;
         Ok((verifier_output, vk_output))
     }
 
+    /// Render `Halo2VerifierPausable.sol`, a wrapper forwarding `verifyProof` to `Halo2Verifier`
+    /// deployed at constructor argument `address verifier`. The deployer of wrapper is admin, who
+    /// can `pause()` to make `verifyProof` revert with `Paused()`, and `unpause()` to restore it.
+    /// Like `Halo2Verifier`, it returns nothing with [`ReturnMode::Revert`].
+    ///
+    /// Set `separate` if `Halo2Verifier` is rendered by `render_separately`.
+    pub fn render_pausable(&self, separate: bool) -> Result<String, GenerateError> {
+        self.validate(separate)?;
+        let mut output = String::new();
+        Halo2VerifierPausable {
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
+            separate,
+            verify_fn_name: self.verify_fn_name.clone(),
+            transcript_prefix: self.transcript_prefix,
+            return_mode: self.return_mode,
+        }
+        .render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Render `Halo2VerifierRouter.sol`, a router calling `Halo2Verifier` rendered by
+    /// `render_separately` with its `Halo2VerifyingKey`, both looked up by circuit hash. It has an
+    /// entrypoint `verify(bytes32,uint256[],bytes)` to verify proof of any registered circuit,
+    /// whose verifier is rendered with the same verify function name, transcript prefix and
+    /// return mode as router. The deployer of router is admin, who can
+    /// `register(bytes32,address,address)` circuits with their verifier and verifying key.
+    /// Like `Halo2Verifier`, `verify` returns nothing with [`ReturnMode::Revert`].
+    pub fn render_router(&self) -> Result<String, GenerateError> {
+        self.validate(true)?;
+        let mut output = String::new();
+        Halo2VerifierRouter {
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
+            verify_fn_name: self.verify_fn_name.clone(),
+            transcript_prefix: self.transcript_prefix,
+            return_mode: self.return_mode,
+        }
+        .render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Render `IVerifier.sol`, a standalone interface `IHalo2Verifier` of `verifyProof` with the
+    /// same selector as `Halo2Verifier`, for callers to compile against without depending on the
+    /// full verifier source. Additional entrypoints are not included.
+    ///
+    /// Set `separate` if `Halo2Verifier` is rendered by `render_separately`.
+    pub fn render_interface(&self, separate: bool) -> Result<String, GenerateError> {
+        self.validate(separate)?;
+        let mut output = String::new();
+        Halo2VerifierInterface {
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
+            separate,
+            verify_fn_name: self.verify_fn_name.clone(),
+            transcript_prefix: self.transcript_prefix,
+            return_mode: self.return_mode,
+        }
+        .render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Return ABI JSON of `verifyProof` of `Halo2Verifier`, to feed `ethers::abi::Contract` or
+    /// `abigen!` instead of constructing it by hand. Additional entrypoints are not included.
+    ///
+    /// Set `separate` if `Halo2Verifier` is rendered by `render_separately`.
+    pub fn verifier_abi_json(&self, separate: bool) -> String {
+        let param = |name: &str, ty: &str| {
+            format!(r#"{{"name":"{name}","type":"{ty}","internalType":"{ty}"}}"#)
+        };
+        let inputs = chain![
+            separate.then(|| param("vk", "address")),
+            [param("proof", "bytes"), param("instances", "uint256[]")],
+            self.transcript_prefix.then(|| param("prefix", "bytes")),
+        ]
+        .join(",");
+        let outputs = match self.return_mode {
+            ReturnMode::Bool => param("", "bool"),
+            ReturnMode::Revert => String::new(),
+        };
+        format!(
+            r#"[{{"type":"function","name":"{}","inputs":[{inputs}],"outputs":[{outputs}],"stateMutability":"nonpayable"}}]"#,
+            self.verify_fn_name
+        )
+    }
+
+    /// Return function selector of `verifyProof` of `Halo2Verifier`, which differs from
+    /// [`FN_SIG_VERIFY_PROOF`] and its variants when [`SolidityGenerator::set_verify_fn_name`] is
+    /// set.
+    ///
+    /// Set `separate` if `Halo2Verifier` is rendered by `render_separately`.
+    ///
+    /// [`FN_SIG_VERIFY_PROOF`]: crate::FN_SIG_VERIFY_PROOF
+    pub fn verify_fn_selector(&self, separate: bool) -> [u8; 4] {
+        Keccak256::digest(self.verify_fn_signature(separate))[..4]
+            .try_into()
+            .unwrap()
+    }
+
+    fn verify_fn_signature(&self, separate: bool) -> String {
+        let params = chain![
+            separate.then_some("address"),
+            ["bytes", "uint256[]"],
+            self.transcript_prefix.then_some("bytes"),
+        ]
+        .join(",");
+        format!("{}({params})", self.verify_fn_name)
+    }
+
+    /// Render `Halo2VerifierEncoder.sol`, a library `VerifyEncoder` for on-chain callers to
+    /// encode calldata of `verify` from struct `PublicInputs` and proof, which matches
+    /// [`encode_nullifier_calldata`] off-chain. The overload taking `address vk` is for
+    /// `Halo2Verifier` rendered by `render_separately`.
+    ///
+    /// It fails with [`GenerateError::InvalidOption`] if
+    /// [`SolidityGenerator::set_nullifier_interface`] is not set.
+    ///
+    /// [`encode_nullifier_calldata`]: crate::encode_nullifier_calldata
+    pub fn render_encoder(&self) -> Result<String, GenerateError> {
+        self.validate(false)?;
+        if self.nullifier_interface.is_none() {
+            return Err(GenerateError::InvalidOption {
+                option: "nullifier_interface",
+                reason: "encoder library requires nullifier interface".to_string(),
+            });
+        }
+        let mut output = String::new();
+        Halo2VerifierEncoder {
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
+        }
+        .render(&mut output)?;
+        Ok(output)
+    }
+
+    /// Render `Halo2Verifier.sol` with verifying key embedded as `render` does, but return the one
+    /// cached in `cache_dir` if the verifying key, the deciding key of `params` and all options
+    /// are the same as the cached one, otherwise render and cache it as `<digest>.sol`.
+    ///
+    /// Any change of them (or of this crate's version) results in a different digest, so stale
+    /// entries are never returned but also never removed.
+    ///
+    /// The entry is written to a temporary file in `cache_dir` then renamed into place, so
+    /// concurrent callers never read a partially written one.
+    pub fn render_cached(&self, cache_dir: impl AsRef<Path>) -> io::Result<String> {
+        static NUM_TMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+        let cache_dir = cache_dir.as_ref();
+        let digest = hex::encode(self.cache_key());
+        let path = cache_dir.join(format!("{digest}.sol"));
+        match fs::read_to_string(&path) {
+            Ok(verifier) => return Ok(verifier),
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+            Err(_) => {}
+        }
+
+        let verifier = self
+            .render()
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        fs::create_dir_all(cache_dir)?;
+        let tmp_path = cache_dir.join(format!(
+            "{digest}.sol.{}.{}.tmp",
+            std::process::id(),
+            NUM_TMP_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&tmp_path, &verifier)?;
+        if let Err(err) = fs::rename(&tmp_path, path) {
+            let _ = fs::remove_file(tmp_path);
+            return Err(err);
+        }
+        Ok(verifier)
+    }
+
+    fn cache_key(&self) -> [u8; 32] {
+        // Destructure exhaustively so a new option can't be left out of the key.
+        let Self {
+            params,
+            vk,
+            scheme,
+            num_instances,
+            acc_encoding,
+            vk_source,
+            deciding_key_source,
+            return_mode,
+            point_order,
+            calldata_bounds_check,
+            result_index,
+            allowlist_index,
+            transcript_prefix,
+            nullifier_interface,
+            stored_accumulator_index,
+            verbose_gas,
+            batch_verify,
+            batch_aggregate,
+            non_canonical_point_check,
+            pairing_failure_context,
+            debug_errors,
+            section_comments,
+            challenge_reduction,
+            instance_permutation,
+            signed_request,
+            nonce_index,
+            instance_bit_widths,
+            hex_proof,
+            memory_proof,
+            dynamic_instances,
+            inputs_root_index,
+            poseidon_preimage,
+            upgradeable,
+            deployment_check,
+            domain_constants,
+            hoist_constants,
+            contract_name,
+            verify_fn_name,
+            pragma,
+            license,
+            meta: _,
+        } = self;
+        let options: &[&dyn Debug] = &[
+            scheme,
+            num_instances,
+            acc_encoding,
+            vk_source,
+            deciding_key_source,
+            return_mode,
+            point_order,
+            calldata_bounds_check,
+            result_index,
+            allowlist_index,
+            transcript_prefix,
+            nullifier_interface,
+            stored_accumulator_index,
+            verbose_gas,
+            batch_verify,
+            batch_aggregate,
+            non_canonical_point_check,
+            pairing_failure_context,
+            debug_errors,
+            section_comments,
+            challenge_reduction,
+            instance_permutation,
+            signed_request,
+            nonce_index,
+            instance_bit_widths,
+            hex_proof,
+            memory_proof,
+            dynamic_instances,
+            inputs_root_index,
+            poseidon_preimage,
+            upgradeable,
+            deployment_check,
+            domain_constants,
+            hoist_constants,
+            contract_name,
+            verify_fn_name,
+            pragma,
+            license,
+        ];
+        let mut hasher = Keccak256::new();
+        hasher.update(env!("CARGO_PKG_VERSION"));
+        hasher.update(vk_transcript_repr(vk).to_repr());
+        hasher.update(format!(
+            "{:?}{:?}{:?}",
+            params.get_g()[0],
+            params.g2(),
+            params.s_g2()
+        ));
+        hasher.update(format!("{options:?}"));
+        hasher.finalize().into()
+    }
+
+    /// Write a minimal Foundry project into `out_dir` to build and test the verifier, which
+    /// contains:
+    /// - `foundry.toml` pinning solc version to [`SOLC_VERSION`].
+    /// - `src/Verifier.sol` rendered by `render`.
+    /// - `src/IVerifier.sol` with interface `IHalo2Verifier` of `verifyProof`.
+    /// - `test/Verifier.t.sol` rendered by [`foundry_test_sol`] with given `proof` and `instances`
+    ///   as fixture.
+    ///
+    /// It fails with [`GenerateError::IncompatibleOptions`] wrapped in [`io::Error`] if the
+    /// verifier takes constructor arguments, which the fixture doesn't provide.
+    ///
+    /// # Panics
+    /// Panics if `proof` or `instances` doesn't match the verifier.
+    pub fn scaffold_project(
+        &self,
+        out_dir: impl AsRef<Path>,
+        proof: &[u8],
+        instances: &[bn256::Fr],
+    ) -> io::Result<()> {
+        let to_io_error = |err: GenerateError| io::Error::new(io::ErrorKind::Other, err);
+        if self.vk_source == VkSource::Constructor
+            || self.deciding_key_source == DecidingKeySource::Constructor
+            || self.allowlist_index.is_some()
+        {
+            return Err(to_io_error(GenerateError::IncompatibleOptions(
+                "Scaffold and verifier taking constructor arguments",
+            )));
+        }
+
+        let artifact = self.render_artifact().map_err(to_io_error)?;
+        let interface = self.render_interface(false).map_err(to_io_error)?;
+        let test =
+            foundry_test_sol(&artifact, instances, proof).map_err(|err| to_io_error(err.into()))?;
+        let foundry_toml = format!(
+            "[profile.default]\nsrc = \"src\"\ntest = \"test\"\nout = \"out\"\nsolc_version = \"{SOLC_VERSION}\"\n"
+        );
+
+        let out_dir = out_dir.as_ref();
+        for (path, content) in [
+            ("foundry.toml", foundry_toml),
+            ("src/Verifier.sol", artifact.solidity),
+            ("src/IVerifier.sol", interface),
+            ("test/Verifier.t.sol", test),
+        ] {
+            let path = out_dir.join(path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
     fn generate_vk(&self) -> Halo2VerifyingKey {
         let constants = {
             let domain = self.vk.get_domain();
@@ -226,48 +1549,245 @@ impl<'a> SolidityGenerator<'a> {
             .tuples()
             .collect();
         Halo2VerifyingKey {
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
             constants,
             fixed_comms,
             permutation_comms,
         }
     }
 
-    fn generate_verifier(&self, separate: bool) -> Halo2Verifier {
-        let proof_cptr = Ptr::calldata(if separate { 0x84 } else { 0x64 });
+    // Whether any entrypoint calling `verifyProof` with canonical calldata encoding is enabled.
+    fn has_additional_entrypoints(&self) -> bool {
+        self.result_index.is_some()
+            || self.allowlist_index.is_some()
+            || self.nullifier_interface.is_some()
+            || self.verbose_gas
+            || self.batch_verify
+            || self.batch_aggregate
+            || self.instance_permutation
+            || self.signed_request
+            || self.nonce_index.is_some()
+            || self.hex_proof
+            || self.memory_proof
+            || self.dynamic_instances
+            || self.inputs_root_index.is_some()
+            || self.poseidon_preimage.is_some()
+    }
+
+    // Check options are valid for the circuit and can be rendered together, with verifying key
+    // embedded or separate.
+    fn validate(&self, separate: bool) -> Result<(), GenerateError> {
+        let invalid_option = |option, reason: &str| {
+            Err(GenerateError::InvalidOption {
+                option,
+                reason: reason.to_string(),
+            })
+        };
+        let check_index = |option, index: Option<usize>| match index {
+            Some(index) if index >= self.num_instances => {
+                invalid_option(option, "index should be less than number of instances")
+            }
+            _ => Ok(()),
+        };
+
+        if !is_solidity_identifier(&self.contract_name) {
+            return Err(GenerateError::InvalidIdentifier(self.contract_name.clone()));
+        }
+        if !is_solidity_identifier(&self.verify_fn_name) {
+            return Err(GenerateError::InvalidIdentifier(
+                self.verify_fn_name.clone(),
+            ));
+        }
+        if !is_version_pragma(&self.pragma) || pragma_min_version(&self.pragma) < MIN_SOLC_VERSION {
+            return Err(GenerateError::InvalidPragma(self.pragma.clone()));
+        }
+        if self.license.trim().is_empty()
+            || !self.license.chars().all(|char| {
+                char.is_ascii_alphanumeric()
+                    || matches!(char, '-' | '.' | '+' | ':' | ' ' | '(' | ')')
+            })
+        {
+            return Err(GenerateError::InvalidLicense(self.license.clone()));
+        }
+
+        if let Some(acc_encoding) = self.acc_encoding {
+            if acc_encoding.num_limbs * acc_encoding.num_limb_bits < bn256::Fq::NUM_BITS as usize {
+                return invalid_option("acc_encoding", "limbs should hold a base field element");
+            }
+        }
+        check_index("result_index", self.result_index)?;
+        check_index("allowlist_index", self.allowlist_index)?;
+        check_index("stored_accumulator_index", self.stored_accumulator_index)?;
+        check_index("nonce_index", self.nonce_index)?;
+        check_index("inputs_root_index", self.inputs_root_index)?;
+        if let Some(NullifierInterface {
+            signal,
+            root,
+            nullifier_hash,
+        }) = self.nullifier_interface
+        {
+            if self.num_instances != 3 {
+                return invalid_option("nullifier_interface", "requires exactly 3 instances");
+            }
+            let mut indices = [signal, root, nullifier_hash];
+            indices.sort();
+            if indices != [0, 1, 2] {
+                return invalid_option("nullifier_interface", "should map to distinct instances");
+            }
+        }
+        for (index, bit_width) in self.instance_bit_widths.iter().copied() {
+            check_index("instance_bit_widths", Some(index))?;
+            if !(1..254).contains(&bit_width) {
+                return invalid_option(
+                    "instance_bit_widths",
+                    &format!("bit width should be in range 1..254 but got {bit_width}"),
+                );
+            }
+        }
+        if let Some(poseidon_preimage) = self.poseidon_preimage {
+            check_index("poseidon_preimage", Some(poseidon_preimage.instance_index))?;
+            if poseidon_preimage.preimage_len == 0 {
+                return invalid_option("poseidon_preimage", "preimage should not be empty");
+            }
+        }
+
+        let incompatible = |is_incompatible, options| {
+            if is_incompatible {
+                Err(GenerateError::IncompatibleOptions(options))
+            } else {
+                Ok(())
+            }
+        };
+        incompatible(
+            self.transcript_prefix && self.has_additional_entrypoints(),
+            "Transcript prefix and additional entrypoints",
+        )?;
+        incompatible(
+            separate && self.domain_constants,
+            "Domain constants and separate verifying key",
+        )?;
+        incompatible(
+            separate && self.deployment_check,
+            "Deployment check and separate verifying key",
+        )?;
+        incompatible(
+            !separate
+                && self.vk_source == VkSource::Constructor
+                && (self.deployment_check || self.domain_constants || self.upgradeable),
+            "VkSource::Constructor and deployment check, domain constants or upgradeable",
+        )
+    }
+
+    fn generate_verifier(&self, separate: bool) -> Result<Halo2Verifier, GenerateError> {
+        self.validate(separate)?;
+
+        let proof_cptr = Ptr::calldata(
+            if separate { 0x84 } else { 0x64 } + if self.transcript_prefix { 0x20 } else { 0 },
+        );
 
         let vk = self.generate_vk();
         let vk_len = vk.len();
         let vk_mptr = Ptr::memory(self.estimate_static_working_memory_size(&vk, proof_cptr));
-        let data = Data::new(&self.meta, &vk, vk_mptr, proof_cptr);
-
-        let evaluator = Evaluator::new(self.vk.cs(), &self.meta, &data);
-        let quotient_eval_numer_computations = chain![
-            evaluator.gate_computations(),
-            evaluator.permutation_computations(),
-            evaluator.lookup_computations()
-        ]
-        .enumerate()
-        .map(|(idx, (mut lines, var))| {
-            let line = if idx == 0 {
-                format!("quotient_eval_numer := {var}")
+        let data = Data::new(&self.meta, &vk, vk_mptr, proof_cptr, self.point_order);
+        let computations = |data: &Data| {
+            let evaluator = Evaluator::new(self.vk.cs(), &self.meta, data);
+            let mut quotient_eval_numer_computations = chain![
+                evaluator.gate_computations(),
+                evaluator.permutation_computations(),
+                evaluator.lookup_computations()
+            ]
+            .enumerate()
+            .map(|(idx, (mut lines, var))| {
+                let line = if idx == 0 {
+                    format!("quotient_eval_numer := {var}")
+                } else {
+                    format!(
+                        "quotient_eval_numer := addmod(mulmod(quotient_eval_numer, y, r), {var}, r)"
+                    )
+                };
+                lines.push(line);
+                lines
+            })
+            .collect_vec();
+            let hoisted_constants = if self.hoist_constants {
+                // Scratch memory below verifying key except `0x00`, which is used by permutation
+                let num_slots = vk_mptr.value().as_usize() / 0x20 - 1;
+                hoist_constants(&mut quotient_eval_numer_computations, num_slots)
             } else {
-                format!(
-                    "quotient_eval_numer := addmod(mulmod(quotient_eval_numer, y, r), {var}, r)"
-                )
+                Vec::new()
             };
-            lines.push(line);
-            lines
-        })
-        .collect();
 
-        let pcs_computations = match self.scheme {
-            Bdfg21 => bdfg21_computations(&self.meta, &data),
-            Gwc19 => unimplemented!(),
+            let pcs_computations = match self.scheme {
+                Bdfg21 => bdfg21_computations(&self.meta, data),
+                Gwc19 => unimplemented!(),
+            };
+            (
+                hoisted_constants,
+                quotient_eval_numer_computations,
+                pcs_computations,
+            )
         };
+        let (hoisted_constants, quotient_eval_numer_computations, pcs_computations) =
+            computations(&data);
+
+        // Proof and instances of `verifyMemory` are copied in the layout of calldata to above all
+        // working memory, which ends at either the last slot after `theta_mptr` or the batch
+        // inversion of Lagrange evaluations, which takes twice as many words as evaluations
+        let num_lagranges = self.num_instances + self.meta.rotation_last.unsigned_abs() as usize;
+        let memory_proof_mptr = data.theta_mptr + 26 + 2 * num_lagranges + 1;
+        let memory_data = self.memory_proof.then(|| {
+            Data::new(
+                &self.meta,
+                &vk,
+                vk_mptr,
+                memory_proof_mptr,
+                self.point_order,
+            )
+        });
 
-        Halo2Verifier {
+        let mut verifier = Halo2Verifier {
+            contract_name: self.contract_name.clone(),
+            verify_fn_name: self.verify_fn_name.clone(),
+            verify_fn_signature: self.verify_fn_signature(separate),
+            license: self.license.clone(),
+            pragma: self.pragma.clone(),
             scheme: self.scheme,
             vk: (!separate).then_some(vk),
+            vk_source: self.vk_source,
+            deciding_key_source: self.deciding_key_source,
+            return_mode: self.return_mode,
+            point_order: self.point_order,
+            calldata_bounds_check: self.calldata_bounds_check,
+            result_index: self.result_index,
+            allowlist_index: self.allowlist_index,
+            transcript_prefix: self.transcript_prefix,
+            nullifier_interface: self.nullifier_interface,
+            stored_accumulator_index: self.stored_accumulator_index,
+            verbose_gas: self.verbose_gas,
+            batch_verify: self.batch_verify,
+            batch_aggregate: self.batch_aggregate,
+            non_canonical_point_check: self.non_canonical_point_check,
+            pairing_failure_context: self.pairing_failure_context || self.debug_errors,
+            debug_errors: self.debug_errors,
+            section_comments: self.section_comments,
+            challenge_reduction: self.challenge_reduction,
+            instance_permutation: self.instance_permutation,
+            signed_request: self.signed_request,
+            nonce_index: self.nonce_index,
+            instance_bit_widths: self.instance_bit_widths.clone(),
+            hex_proof: self.hex_proof,
+            memory_proof: self.memory_proof,
+            dynamic_instances: self.dynamic_instances.then_some(self.num_instances),
+            inputs_root_index: self.inputs_root_index,
+            poseidon_preimage: self.poseidon_preimage,
+            upgradeable: self.upgradeable,
+            deployment_check: self.deployment_check,
+            has_additional_entrypoints: self.has_additional_entrypoints(),
+            domain: self.domain_constants.then(|| {
+                let k = self.vk.get_domain().k();
+                (k, 1 << k)
+            }),
             vk_len,
             vk_mptr,
             num_neg_lagranges: self.meta.rotation_last.unsigned_abs() as usize,
@@ -280,9 +1800,28 @@ impl<'a> SolidityGenerator<'a> {
             proof_len: self.meta.proof_len(self.scheme),
             challenge_mptr: data.challenge_mptr,
             theta_mptr: data.theta_mptr,
+            hoisted_constants,
             quotient_eval_numer_computations,
             pcs_computations,
+            num_instances: self.num_instances,
+            memory_core: None,
+        };
+        if let Some(memory_data) = memory_data {
+            let (hoisted_constants, quotient_eval_numer_computations, pcs_computations) =
+                computations(&memory_data);
+            let memory_verifier = Halo2Verifier {
+                proof_cptr: memory_proof_mptr,
+                quotient_comm_cptr: memory_data.quotient_comm_cptr,
+                hoisted_constants,
+                quotient_eval_numer_computations,
+                pcs_computations,
+                ..verifier.clone()
+            };
+            let mut memory_core = String::new();
+            Halo2VerifierAssembly(&memory_verifier).render(&mut memory_core)?;
+            verifier.memory_core = Some(memory_core);
         }
+        Ok(verifier)
     }
 
     fn estimate_static_working_memory_size(
@@ -293,7 +1832,7 @@ impl<'a> SolidityGenerator<'a> {
         let pcs_computation = match self.scheme {
             Bdfg21 => {
                 let mock_vk_mptr = Ptr::memory(0x100000);
-                let mock = Data::new(&self.meta, vk, mock_vk_mptr, proof_cptr);
+                let mock = Data::new(&self.meta, vk, mock_vk_mptr, proof_cptr, self.point_order);
                 let (superset, sets) = rotation_sets(&queries(&self.meta, &mock));
                 let num_coeffs = sets.iter().map(|set| set.rots().len()).sum::<usize>();
                 2 * (1 + num_coeffs) + 6 + 2 * superset.len() + 1 + 3 * sets.len()
@@ -303,7 +1842,8 @@ impl<'a> SolidityGenerator<'a> {
 
         itertools::max(chain![
             // Hashing advice commitments
-            chain![self.meta.num_advices().into_iter()].map(|n| n * 2 + 1),
+            chain![self.meta.num_advices().into_iter()]
+                .map(|n| n * 2 + 1 + self.transcript_prefix as usize),
             // Hashing evaluations
             [self.meta.num_evals + 1],
             // PCS computation
@@ -316,6 +1856,299 @@ impl<'a> SolidityGenerator<'a> {
     }
 }
 
+// Version range as `||` separated sets of comparators or hyphen ranges, where each comparator is an
+// optional operator followed by version with up to 3 parts, e.g. `^0.8.13`, `>=0.8.13 <0.9.0` or
+// `0.8.x`.
+fn is_version_pragma(pragma: &str) -> bool {
+    let is_version = |version: &str| {
+        let parts = version.split('.').collect::<Vec<_>>();
+        parts.len() <= 3
+            && parts.iter().all(|part| {
+                !part.is_empty()
+                    && (part.chars().all(|char| char.is_ascii_digit())
+                        || matches!(*part, "x" | "X" | "*"))
+            })
+    };
+    let is_comparator = |comparator: &str| {
+        let version = ["^", "~", ">=", "<=", ">", "<", "="]
+            .iter()
+            .find_map(|operator| comparator.strip_prefix(operator))
+            .unwrap_or(comparator);
+        is_version(version)
+    };
+    pragma.split("||").all(|set| {
+        let tokens = set.split_whitespace().collect::<Vec<_>>();
+        match tokens.as_slice() {
+            [] => false,
+            [lhs, "-", rhs] => is_version(lhs) && is_version(rhs),
+            tokens => tokens.iter().all(|token| is_comparator(token)),
+        }
+    })
+}
+
+// Lowest version admitted by version range accepted by `is_version_pragma`, where a missing or
+// wildcard part counts as 0, and comparator without lower bound admits `0.0.0`.
+fn pragma_min_version(pragma: &str) -> [u64; 3] {
+    let min_version = |comparator: &str| {
+        let (version, exclusive) = if let Some(version) = comparator.strip_prefix(">=") {
+            (version, false)
+        } else if let Some(version) = comparator.strip_prefix('>') {
+            (version, true)
+        } else if comparator.starts_with('<') {
+            return [0; 3];
+        } else {
+            (comparator.trim_start_matches(&['^', '~', '='][..]), false)
+        };
+        let parts = version
+            .split('.')
+            .map_while(|part| part.parse().ok())
+            .collect::<Vec<u64>>();
+        let mut min = [0; 3];
+        min[..parts.len()].copy_from_slice(&parts);
+        // `>0.8.12` admits from `0.8.13`, and `>0.8` from `0.9.0`
+        if exclusive && !parts.is_empty() {
+            min[parts.len() - 1] += 1;
+        }
+        min
+    };
+    pragma
+        .split("||")
+        .map(|set| {
+            let tokens = set.split_whitespace().collect::<Vec<_>>();
+            match tokens.as_slice() {
+                [lhs, "-", _] => min_version(lhs),
+                tokens => tokens
+                    .iter()
+                    .map(|token| min_version(token))
+                    .max()
+                    .unwrap_or_default(),
+            }
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn is_solidity_identifier(name: &str) -> bool {
+    // Keywords, reserved keywords, units and `this`/`super`, besides sized elementary types below
+    const KEYWORDS: &[&str] = &[
+        "abstract",
+        "address",
+        "after",
+        "alias",
+        "anonymous",
+        "apply",
+        "as",
+        "assembly",
+        "auto",
+        "bool",
+        "break",
+        "byte",
+        "bytes",
+        "calldata",
+        "case",
+        "catch",
+        "constant",
+        "constructor",
+        "continue",
+        "contract",
+        "copyof",
+        "days",
+        "default",
+        "define",
+        "delete",
+        "do",
+        "else",
+        "emit",
+        "enum",
+        "ether",
+        "event",
+        "external",
+        "fallback",
+        "false",
+        "final",
+        "fixed",
+        "for",
+        "function",
+        "gwei",
+        "hex",
+        "hours",
+        "if",
+        "immutable",
+        "implements",
+        "import",
+        "in",
+        "indexed",
+        "inline",
+        "int",
+        "interface",
+        "internal",
+        "is",
+        "let",
+        "library",
+        "macro",
+        "mapping",
+        "match",
+        "memory",
+        "minutes",
+        "modifier",
+        "mutable",
+        "new",
+        "null",
+        "of",
+        "override",
+        "partial",
+        "payable",
+        "pragma",
+        "private",
+        "promise",
+        "public",
+        "pure",
+        "receive",
+        "reference",
+        "relocatable",
+        "return",
+        "returns",
+        "sealed",
+        "seconds",
+        "sizeof",
+        "static",
+        "storage",
+        "string",
+        "struct",
+        "super",
+        "supports",
+        "switch",
+        "this",
+        "true",
+        "try",
+        "type",
+        "typedef",
+        "typeof",
+        "ufixed",
+        "uint",
+        "unchecked",
+        "unicode",
+        "using",
+        "var",
+        "view",
+        "virtual",
+        "weeks",
+        "wei",
+        "while",
+        "years",
+    ];
+    // `intN`/`uintN` of 8 to 256 bits by step of 8, `bytesN` of 1 to 32 bytes, and
+    // `fixedMxN`/`ufixedMxN` of `M` as `intN` and `N` of 0 to 80 decimals
+    let is_sized = |size: &str, range: RangeInclusive<usize>, step: usize| {
+        !size.starts_with('0')
+            && size
+                .parse::<usize>()
+                .map_or(false, |size| range.contains(&size) && size % step == 0)
+    };
+    let is_int_type = |name: &str| {
+        let size = name.strip_prefix('u').unwrap_or(name).strip_prefix("int");
+        size.map_or(false, |size| is_sized(size, 8..=256, 8))
+    };
+    let is_bytes_type = |name: &str| {
+        let size = name.strip_prefix("bytes");
+        size.map_or(false, |size| is_sized(size, 1..=32, 1))
+    };
+    let is_fixed_type = |name: &str| {
+        let size = name.strip_prefix('u').unwrap_or(name).strip_prefix("fixed");
+        size.and_then(|size| size.split_once('x'))
+            .map_or(false, |(bits, decimals)| {
+                is_sized(bits, 8..=256, 8) && (decimals == "0" || is_sized(decimals, 0..=80, 1))
+            })
+    };
+    let mut chars = name.chars();
+    chars.next().map_or(false, |char| {
+        char.is_ascii_alphabetic() || char == '_' || char == '$'
+    }) && chars.all(|char| char.is_ascii_alphanumeric() || char == '_' || char == '$')
+        && !KEYWORDS.contains(&name)
+        && !is_int_type(name)
+        && !is_bytes_type(name)
+        && !is_fixed_type(name)
+}
+
+/// Assert `Halo2Verifier` can be deployed to `target`, since the rendered verifier is the same
+/// for every supported `Target`.
+///
+/// # Panics
+/// Panics if `target` lacks any precompile `Halo2Verifier` relies on, see [`Target`].
+pub fn assert_target_supported(target: Target) {
+    assert_ne!(
+        target,
+        Target::ZkSyncEra,
+        "Target zkSync Era is not supported due to lack of precompiles ecAdd, ecMul and ecPairing"
+    );
+}
+
+/// Render a Foundry test `.t.sol` for `Halo2Verifier` of `artifact`, which deploys it from
+/// `../src/Verifier.sol` (with verifying key as constructor argument if it's
+/// [`VkSource::Constructor`]), then asserts that the verification function accepts given `proof`
+/// and `instances` baked as literals, and rejects them with the last proof byte flipped or the
+/// first instance flipped.
+///
+/// It works with both [`ReturnMode`] and follows the verification function name and transcript
+/// prefix of `artifact` (with empty prefix), and the verifier is expected to take no other
+/// constructor argument than verifying key.
+///
+/// # Panics
+/// Panics if `instances` or `proof` doesn't match `artifact`.
+pub fn foundry_test_sol(
+    artifact: &VerifierArtifact,
+    instances: &[bn256::Fr],
+    proof: &[u8],
+) -> Result<String, fmt::Error> {
+    assert_eq!(
+        instances.len(),
+        artifact.num_instances,
+        "Invalid number of instances"
+    );
+    assert_eq!(proof.len(), artifact.proof_len, "Invalid proof length");
+
+    let mut output = String::new();
+    Halo2VerifierTest {
+        license: artifact.license.clone(),
+        pragma: artifact.pragma.clone(),
+        contract_name: artifact.contract_name.clone(),
+        verify_fn_name: artifact.verify_fn_name.clone(),
+        transcript_prefix: artifact.transcript_prefix,
+        vk: artifact.constructor_vk.clone(),
+        proof: hex::encode(proof),
+        instances: instances.iter().map(fr_to_u256).collect(),
+    }
+    .render(&mut output)?;
+    Ok(output)
+}
+
+/// Render a Hardhat deployment script `deploy.ts` with ethers v6, which deploys contract of
+/// `artifact`, then calls its verification function by `staticCall` with proof read from
+/// `proof.json` and instances read from `instances.json` (overridable by environment variables
+/// `PROOF_PATH` and `INSTANCES_PATH`), as a smoke test of the deployment. If the verification
+/// function takes transcript prefix, it's read from environment variable `PREFIX` and empty by
+/// default.
+///
+/// The verifier is expected to take no constructor argument.
+///
+/// # Panics
+/// Panics if `artifact` takes verifying key as constructor argument.
+pub fn hardhat_deploy_script(artifact: &VerifierArtifact) -> Result<String, fmt::Error> {
+    assert!(
+        artifact.constructor_vk.is_none(),
+        "Deploy script with verifier taking constructor arguments is not yet implemented"
+    );
+
+    let mut output = String::new();
+    Halo2VerifierDeploy {
+        contract_name: artifact.contract_name.clone(),
+        verify_fn_name: artifact.verify_fn_name.clone(),
+        transcript_prefix: artifact.transcript_prefix,
+    }
+    .render(&mut output)?;
+    Ok(output)
+}
+
 // Remove when `vk.transcript_repr()` is ready for usage.
 fn vk_transcript_repr(vk: &VerifyingKey<bn256::G1Affine>) -> bn256::Fr {
     use blake2b_simd::Params;
@@ -331,3 +2164,91 @@ fn vk_transcript_repr(vk: &VerifyingKey<bn256::G1Affine>) -> bn256::Fr {
         .update(fmtted_pinned_vk.as_bytes());
     FromUniformBytes::from_uniform_bytes(hasher.finalize().as_array())
 }
+
+/// Return whether `deployed_runtime` bytecode embeds verifying key digest, deciding key (`g2` and
+/// `-s_g2`) of `params`, and all fixed and permutation commitments of `expected_vk`, each at its
+/// slot in verifying key relative to the others, which applies to `Halo2VerifyingKey` and
+/// `Halo2Verifier` with verifying key embedded by [`VkSource::Inline`] or
+/// [`VkSource::BytesConstant`] and [`DecidingKeySource::Embedded`].
+///
+/// The words are matched either as contiguous bytes (`Halo2VerifyingKey` and `bytes` constant),
+/// or as `mstore(mptr, word)` with `PUSH` immediates and `mptr` advancing by `0x20` per slot
+/// (inlined), so a word can't match at an arbitrary place in bytecode. Other constants depending
+/// on generator options (e.g. `num_instances`) are not checked.
+pub fn verify_deployment(
+    deployed_runtime: &[u8],
+    params: &ParamsKZG<bn256::Bn256>,
+    expected_vk: &VerifyingKey<bn256::G1Affine>,
+) -> bool {
+    // Slots of `g2_x_1` and the first fixed commitment in verifying key rendered by `generate_vk`
+    const G2_SLOT: usize = 13;
+    const FIXED_COMMS_SLOT: usize = 21;
+
+    let words = chain![
+        [(0, fr_to_u256(vk_transcript_repr(expected_vk)))],
+        izip!(
+            G2_SLOT..,
+            chain![g2_to_u256s(params.g2()), g2_to_u256s(-params.s_g2())]
+        ),
+        izip!(
+            FIXED_COMMS_SLOT..,
+            chain![
+                expected_vk.fixed_commitments(),
+                expected_vk.permutation().commitments()
+            ]
+            .flat_map(g1_to_u256s)
+        ),
+    ]
+    .collect_vec();
+
+    let contiguous = {
+        let digest = words[0].1.to_be_bytes::<0x20>();
+        (0..deployed_runtime.len().saturating_sub(0x1f))
+            .filter(|base| deployed_runtime[*base..*base + 0x20] == digest)
+            .any(|base| {
+                words.iter().all(|(slot, word)| {
+                    let start = base + 0x20 * slot;
+                    deployed_runtime.get(start..start + 0x20)
+                        == Some(&word.to_be_bytes::<0x20>()[..])
+                })
+            })
+    };
+    let inlined = {
+        let mstores = constant_mstores(deployed_runtime);
+        mstores
+            .iter()
+            .filter(|(_, value)| *value == words[0].1)
+            .any(|(base, _)| {
+                words
+                    .iter()
+                    .all(|(slot, word)| mstores.contains(&(base + U256::from(0x20 * slot), *word)))
+            })
+    };
+    contiguous || inlined
+}
+
+// Return `(mptr, value)` of every `PUSH value PUSH mptr MSTORE` in `code` disassembled linearly.
+fn constant_mstores(code: &[u8]) -> HashSet<(U256, U256)> {
+    const PUSH0: u8 = 0x5f;
+    const PUSH32: u8 = 0x7f;
+    const MSTORE: u8 = 0x52;
+
+    let mut mstores = HashSet::new();
+    let mut pushes = Vec::<Option<U256>>::new();
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        let push = (PUSH0..=PUSH32).contains(&opcode).then(|| {
+            let len = (opcode - PUSH0) as usize;
+            let immediate = &code[(pc + 1).min(code.len())..(pc + 1 + len).min(code.len())];
+            pc += len;
+            U256::try_from_be_slice(immediate).unwrap()
+        });
+        if let ([.., Some(value), Some(mptr)], MSTORE) = (pushes.as_slice(), opcode) {
+            mstores.insert((*mptr, *value));
+        }
+        pushes.push(push);
+        pc += 1;
+    }
+    mstores
+}
@@ -1,6 +1,6 @@
 #![allow(clippy::useless_format)]
 
-use crate::codegen::util::{code_block, fe_to_u256, ConstraintSystemMeta, Data};
+use crate::codegen::util::{code_block, fe_to_u256, ConstraintSystemMeta, Data, Ptr};
 use halo2_proofs::{
     halo2curves::ff::PrimeField,
     plonk::{
@@ -310,6 +310,69 @@ where
     }
 }
 
+// Replace 32-byte constants repeated in `computations` by `mload` from `0x20`, up to `num_slots`
+// most repeated ones, and return them with where they should be stored before `computations`.
+pub(crate) fn hoist_constants(
+    computations: &mut [Vec<String>],
+    num_slots: usize,
+) -> Vec<(Ptr, String)> {
+    let mut counts = HashMap::<String, usize>::new();
+    for line in computations.iter().flatten() {
+        map_tokens(line, |token| {
+            if is_large_constant(token) {
+                *counts.entry(token.to_string()).or_default() += 1;
+            }
+            None
+        });
+    }
+    let hoisted = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .sorted_by(|(lhs, lhs_count), (rhs, rhs_count)| {
+            rhs_count.cmp(lhs_count).then_with(|| lhs.cmp(rhs))
+        })
+        .take(num_slots)
+        .enumerate()
+        .map(|(idx, (constant, _))| (Ptr::memory(0x20) + idx, constant))
+        .collect_vec();
+
+    let ptrs = hoisted
+        .iter()
+        .map(|(ptr, constant)| (constant.as_str(), *ptr))
+        .collect::<HashMap<_, _>>();
+    for line in computations.iter_mut().flatten() {
+        *line = map_tokens(line, |token| {
+            ptrs.get(token).map(|ptr| format!("mload({ptr})"))
+        });
+    }
+    hoisted
+}
+
+// Map each identifier or literal token of `line` by `f`, and keep the ones mapped to `None`.
+pub(crate) fn map_tokens(line: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    let is_token_char = |char: char| char.is_ascii_alphanumeric() || char == '_';
+    let mut mapped = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(is_token_char) {
+        mapped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|char| !is_token_char(char)).unwrap_or(rest.len());
+        let token = &rest[..end];
+        mapped.push_str(&f(token).unwrap_or_else(|| token.to_string()));
+        rest = &rest[end..];
+    }
+    mapped.push_str(rest);
+    mapped
+}
+
+// Constant not less than `2^64`, which is rendered by `u256_string` in full and pushed by `PUSH32`
+// in the worst case.
+fn is_large_constant(token: &str) -> bool {
+    token.strip_prefix("0x").map_or(false, |hex| {
+        hex.len() > 16 && hex.chars().all(|char| char.is_ascii_hexdigit())
+    })
+}
+
 fn u256_string(value: U256) -> String {
     if value.bit_len() < 64 {
         format!("0x{:x}", value.as_limbs()[0])
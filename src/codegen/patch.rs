@@ -0,0 +1,354 @@
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    iter::Peekable,
+    ops::Range,
+    str::CharIndices,
+};
+
+/// Error returned by [`patch_vk`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatchVkError {
+    /// Source doesn't contain verifying key constants rendered by `SolidityGenerator`.
+    MissingVk,
+    /// Verifying key constants have different layout, e.g. different number of fixed or
+    /// permutation commitments.
+    VkShapeMismatch,
+    /// Verification logic other than verifying key constants differs.
+    LogicMismatch,
+    /// JSON is malformed, or doesn't have exactly the verifying key constants of the source.
+    InvalidJson,
+    /// Number of instances in verifying key constants differs from the expected one.
+    InvalidInstanceCount {
+        /// Number of instances in verifying key constants.
+        expected: usize,
+        /// Number of instances given by caller.
+        got: usize,
+    },
+}
+
+impl Display for PatchVkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingVk => write!(
+                f,
+                "source doesn't contain verifying key constants rendered by SolidityGenerator"
+            ),
+            Self::VkShapeMismatch => write!(
+                f,
+                "verifying key constants have different layout, regenerate the verifier"
+            ),
+            Self::LogicMismatch => write!(
+                f,
+                "verification logic differs, regenerate the verifier with the same options"
+            ),
+            Self::InvalidJson => write!(
+                f,
+                "JSON is malformed or doesn't have exactly the verifying key constants of the source"
+            ),
+            Self::InvalidInstanceCount { expected, got } => write!(
+                f,
+                "verifier is rendered for {expected} instances but {got} instances are given"
+            ),
+        }
+    }
+}
+
+impl Error for PatchVkError {}
+
+/// Replace verifying key constants in `existing` solidity source by the ones in `generated`, and
+/// preserve everything else in `existing`, including manual edits to surrounding wrapper code.
+///
+/// Both sources should be rendered by `SolidityGenerator` with the same options, either both by
+/// `render` or both as verifying key contract by `render_separately`. The verifying key constants
+/// should have the same layout, and the memory layout constants and `assembly` blocks should be
+/// identical except for verifying key constants, otherwise it returns an error since the circuit
+/// shape has changed and the verifier should be regenerated.
+pub fn patch_vk(existing: &str, generated: &str) -> Result<String, PatchVkError> {
+    let existing_lines = existing.split_inclusive('\n').collect::<Vec<_>>();
+    let generated_lines = generated.split_inclusive('\n').collect::<Vec<_>>();
+
+    let existing_vk = vk_range(&existing_lines).ok_or(PatchVkError::MissingVk)?;
+    let generated_vk = vk_range(&generated_lines).ok_or(PatchVkError::MissingVk)?;
+
+    if labels(&existing_lines[existing_vk.clone()])
+        != labels(&generated_lines[generated_vk.clone()])
+    {
+        return Err(PatchVkError::VkShapeMismatch);
+    }
+    if logic(&existing_lines, &existing_vk) != logic(&generated_lines, &generated_vk) {
+        return Err(PatchVkError::LogicMismatch);
+    }
+
+    Ok(existing_lines[..existing_vk.start]
+        .iter()
+        .chain(&generated_lines[generated_vk])
+        .chain(&existing_lines[existing_vk.end..])
+        .copied()
+        .collect())
+}
+
+/// Export verifying key constants in `solidity` rendered by `SolidityGenerator` as JSON, for
+/// tooling in other languages to consume without depending on Rust types. It works for both
+/// sources by `render` and verifying key contract by `render_separately`.
+///
+/// The JSON is an object with scalar constants like `vk_digest` and `omega` as `0x` prefixed hex
+/// strings of 32 bytes, and commitments `fixed_comms` and `permutation_comms` as arrays of
+/// objects with `x` and `y`, all in the order `Halo2Verifier` loads them into memory.
+pub fn export_vk_json(solidity: &str) -> Result<String, PatchVkError> {
+    let lines = solidity.split_inclusive('\n').collect::<Vec<_>>();
+    let vk = vk_range(&lines).ok_or(PatchVkError::MissingVk)?;
+
+    let words = lines[vk]
+        .iter()
+        .map(|line| Ok((vk_label(line).unwrap(), vk_value(line)?)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let groups = words
+        .iter()
+        .group_by(|(label, _)| label.split_once('[').map(|(name, _)| name));
+    let entries = groups
+        .into_iter()
+        .flat_map(|(name, words)| match name {
+            Some(name) => {
+                let points = words
+                    .tuples()
+                    .map(|((_, x), (_, y))| format!("    {{ \"x\": \"0x{x}\", \"y\": \"0x{y}\" }}"))
+                    .join(",\n");
+                vec![format!("  \"{name}\": [\n{points}\n  ]")]
+            }
+            None => words
+                .map(|(label, value)| format!("  \"{label}\": \"0x{value}\""))
+                .collect(),
+        })
+        .collect_vec();
+    Ok(format!("{{\n{}\n}}\n", entries.join(",\n")))
+}
+
+/// Replace verifying key constants in `solidity` rendered by `SolidityGenerator` by the ones in
+/// `json` in the format of [`export_vk_json`], and preserve everything else in `solidity`.
+///
+/// The `json` should have exactly the constants of `solidity`, in any order of object keys,
+/// otherwise it returns an error since the circuit shape has changed.
+pub fn import_vk_json(solidity: &str, json: &str) -> Result<String, PatchVkError> {
+    let lines = solidity.split_inclusive('\n').collect::<Vec<_>>();
+    let vk = vk_range(&lines).ok_or(PatchVkError::MissingVk)?;
+
+    let words = JsonParser::new(json)
+        .parse()
+        .ok_or(PatchVkError::InvalidJson)?;
+    let mut values = words.iter().cloned().collect::<HashMap<_, _>>();
+    if values.len() != words.len() || values.len() != vk.len() {
+        return Err(PatchVkError::InvalidJson);
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            if !vk.contains(&idx) {
+                return Ok(line.to_string());
+            }
+            let value = values
+                .remove(vk_label(line).unwrap())
+                .ok_or(PatchVkError::InvalidJson)?;
+            let range = vk_value_range(line).ok_or(PatchVkError::MissingVk)?;
+            Ok(format!(
+                "{}{value}{}",
+                &line[..range.start],
+                &line[range.end..]
+            ))
+        })
+        .collect()
+}
+
+/// Check that `solidity` rendered by `SolidityGenerator` verifies proofs with `num_instances`
+/// instances, by reading the `num_instances` verifying key constant. It works for both sources by
+/// `render` and verifying key contract by `render_separately`.
+///
+/// Calldata with different number of instances is rejected by `Halo2Verifier`, so it's cheaper to
+/// catch the mismatch before deployment.
+pub fn check_num_instances(solidity: &str, num_instances: usize) -> Result<(), PatchVkError> {
+    let lines = solidity.split_inclusive('\n').collect::<Vec<_>>();
+    let vk = vk_range(&lines).ok_or(PatchVkError::MissingVk)?;
+
+    let line = lines[vk]
+        .iter()
+        .find(|line| vk_label(line) == Some("num_instances"))
+        .ok_or(PatchVkError::MissingVk)?;
+    let expected =
+        usize::from_str_radix(vk_value(line)?.trim_start_matches('0'), 16).unwrap_or_default();
+    if expected != num_instances {
+        return Err(PatchVkError::InvalidInstanceCount {
+            expected,
+            got: num_instances,
+        });
+    }
+    Ok(())
+}
+
+// Each verifying key constant is rendered in a single line as either `hex"..." // name` in data
+// section or `mstore(ptr, 0x...) // name` in assembly, starting with `vk_digest`.
+fn vk_label(line: &str) -> Option<&str> {
+    let line = line.trim();
+    if !line.starts_with("hex\"") && !line.starts_with("mstore(") {
+        return None;
+    }
+    line.split_once(" // ").map(|(_, label)| label)
+}
+
+fn vk_range(lines: &[&str]) -> Option<Range<usize>> {
+    let start = lines
+        .iter()
+        .position(|line| vk_label(line) == Some("vk_digest"))?;
+    let len = lines[start..]
+        .iter()
+        .take_while(|line| vk_label(line).is_some())
+        .count();
+    Some(start..start + len)
+}
+
+// Range of 64 hex digits of verifying key constant in either `hex"..."` or `mstore(ptr, 0x...)`.
+fn vk_value_range(line: &str) -> Option<Range<usize>> {
+    let start = match line.find("hex\"") {
+        Some(start) => start + 4,
+        None => line.find(", 0x")? + 4,
+    };
+    line.get(start..start + 64)
+        .filter(|value| value.chars().all(|char| char.is_ascii_hexdigit()))
+        .map(|_| start..start + 64)
+}
+
+fn vk_value(line: &str) -> Result<&str, PatchVkError> {
+    vk_value_range(line)
+        .map(|range| &line[range])
+        .ok_or(PatchVkError::MissingVk)
+}
+
+// Parser of the subset of JSON written by `export_vk_json`, which has only objects, arrays and
+// strings without escape, and flattens them into labels of verifying key constants with values
+// as 64 hex digits.
+struct JsonParser<'a> {
+    json: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    words: Vec<(String, String)>,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(json: &'a str) -> Self {
+        Self {
+            json,
+            chars: json.char_indices().peekable(),
+            words: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> Option<Vec<(String, String)>> {
+        self.value("")?;
+        self.skip_whitespace();
+        self.chars.next().is_none().then_some(self.words)
+    }
+
+    fn value(&mut self, label: &str) -> Option<()> {
+        self.skip_whitespace();
+        match self.chars.peek()?.1 {
+            '{' => self.object(label),
+            '[' => self.array(label),
+            '"' if !label.is_empty() => {
+                let value = self.string()?.strip_prefix("0x")?;
+                if value.is_empty()
+                    || value.len() > 64
+                    || !value.chars().all(|char| char.is_ascii_hexdigit())
+                {
+                    return None;
+                }
+                let value = format!("{value:0>64}").to_ascii_lowercase();
+                self.words.push((label.to_string(), value));
+                Some(())
+            }
+            _ => None,
+        }
+    }
+
+    fn object(&mut self, label: &str) -> Option<()> {
+        self.expect('{')?;
+        if self.try_expect('}') {
+            return Some(());
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.expect(':')?;
+            let label = if label.is_empty() {
+                key.to_string()
+            } else {
+                format!("{label}.{key}")
+            };
+            self.value(&label)?;
+            if !self.try_expect(',') {
+                return self.expect('}');
+            }
+        }
+    }
+
+    fn array(&mut self, label: &str) -> Option<()> {
+        self.expect('[')?;
+        if self.try_expect(']') {
+            return Some(());
+        }
+        for idx in 0.. {
+            self.value(&format!("{label}[{idx}]"))?;
+            if !self.try_expect(',') {
+                break;
+            }
+        }
+        self.expect(']')
+    }
+
+    fn string(&mut self) -> Option<&'a str> {
+        let (start, _) = self.chars.next().filter(|(_, char)| *char == '"')?;
+        let (end, _) = self.chars.find(|(_, char)| matches!(char, '"' | '\\'))?;
+        (self.json.as_bytes()[end] == b'"').then(|| &self.json[start + 1..end])
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .chars
+            .next_if(|(_, char)| char.is_whitespace())
+            .is_some()
+        {}
+    }
+
+    fn try_expect(&mut self, expected: char) -> bool {
+        self.skip_whitespace();
+        self.chars.next_if(|(_, char)| *char == expected).is_some()
+    }
+
+    fn expect(&mut self, expected: char) -> Option<()> {
+        self.try_expect(expected).then_some(())
+    }
+}
+
+fn labels<'a>(lines: &[&'a str]) -> Vec<&'a str> {
+    lines.iter().filter_map(|line| vk_label(line)).collect()
+}
+
+// Memory layout constants and `assembly` blocks without verifying key constants.
+fn logic<'a>(lines: &[&'a str], vk: &Range<usize>) -> Vec<&'a str> {
+    let mut depth = 0usize;
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(idx, line)| {
+            let is_assembly = depth > 0 || line.trim_end().ends_with("assembly {");
+            if is_assembly {
+                depth += line.matches('{').count();
+                depth -= line.matches('}').count().min(depth);
+            }
+            !vk.contains(idx)
+                && (is_assembly || line.trim_start().starts_with("uint256 internal constant"))
+        })
+        .map(|(_, line)| line.trim_end())
+        .collect()
+}
@@ -5,6 +5,9 @@ use itertools::{chain, izip, Itertools};
 use std::collections::{BTreeMap, BTreeSet};
 
 /// KZG batch open schemes in `halo2`.
+///
+/// The scheme determines how the batch opening and the final pairing inputs are assembled, so it
+/// must match the prover, otherwise the verifier rejects every proof.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BatchOpenScheme {
     /// Batch open scheme in [Plonk] paper.
@@ -164,8 +167,7 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
     let max_rot = *superset.last().unwrap();
     let num_coeffs = sets.iter().map(|set| set.rots().len()).sum::<usize>();
 
-    let w = EcPoint::from(data.w_cptr);
-    let w_prime = EcPoint::from(data.w_cptr + 2);
+    let (w, w_prime) = (data.w, data.w_prime);
 
     let diff_0 = Word::from(Ptr::memory(0x00));
     let coeffs = sets
@@ -382,7 +384,8 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
                                     })
                                     .collect_vec()
                             } else {
-                                let item = "mulmod(coeff, calldataload(mptr), r)";
+                                let eval = Word::from(Ptr::new(evals[0].ptr().loc(), "mptr"));
+                                let item = format!("mulmod(coeff, {eval}, r)");
                                 for_loop(
                                     [
                                         format!("let mptr := {}", evals[0].ptr()),
@@ -525,10 +528,18 @@ pub(crate) fn bdfg21_computations(meta: &ConstraintSystemMeta, data: &Data) -> V
                             })
                             .collect_vec()
                     } else {
-                        let mptr = comms.first().unwrap().x().ptr();
+                        let first = comms.first().unwrap();
+                        let mptr = first.x().ptr();
                         let mptr_end = mptr - 2 * comms.len();
                         let x = Word::from(Ptr::new(loc, "mptr"));
-                        let y = Word::from(Ptr::new(loc, "add(mptr, 0x20)"));
+                        let y = Word::from(Ptr::new(
+                            loc,
+                            if first.y().ptr() == mptr + 1 {
+                                "add(mptr, 0x20)"
+                            } else {
+                                "sub(mptr, 0x20)"
+                            },
+                        ));
                         for_loop(
                             [
                                 format!("let mptr := {mptr}"),
@@ -1,31 +1,184 @@
 use crate::codegen::{
     pcs::BatchOpenScheme::{self, Bdfg21, Gwc19},
-    util::Ptr,
+    util::{Location, Ptr},
+    DecidingKeySource, NullifierInterface, PoseidonPreimage, ReturnMode,
+    VkSource::{self, BytesConstant, Inline},
 };
+use crate::proof::PointOrder;
+use crate::transcript::{modulus, ChallengeReduction};
 use askama::{Error, Template};
+use halo2_proofs::halo2curves::bn256;
+use itertools::chain;
 use ruint::aliases::U256;
-use std::fmt;
+use std::{fmt, ops::Deref};
 
-#[derive(Template)]
+#[derive(Clone, Template)]
 #[template(path = "Halo2VerifyingKey.sol")]
 pub(crate) struct Halo2VerifyingKey {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
     pub(crate) constants: Vec<(&'static str, U256)>,
     pub(crate) fixed_comms: Vec<(U256, U256)>,
     pub(crate) permutation_comms: Vec<(U256, U256)>,
 }
 
+#[derive(Template)]
+#[template(path = "Halo2VerifierRouter.sol")]
+pub(crate) struct Halo2VerifierRouter {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
+    pub(crate) verify_fn_name: String,
+    pub(crate) transcript_prefix: bool,
+    pub(crate) return_mode: ReturnMode,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierInterface.sol")]
+pub(crate) struct Halo2VerifierInterface {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
+    pub(crate) separate: bool,
+    pub(crate) verify_fn_name: String,
+    pub(crate) transcript_prefix: bool,
+    pub(crate) return_mode: ReturnMode,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierEncoder.sol")]
+pub(crate) struct Halo2VerifierEncoder {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierTest.sol")]
+pub(crate) struct Halo2VerifierTest {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
+    pub(crate) contract_name: String,
+    pub(crate) verify_fn_name: String,
+    pub(crate) transcript_prefix: bool,
+    pub(crate) vk: Option<Vec<(String, U256)>>,
+    pub(crate) proof: String,
+    pub(crate) instances: Vec<U256>,
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierDeploy.ts")]
+pub(crate) struct Halo2VerifierDeploy {
+    pub(crate) contract_name: String,
+    pub(crate) verify_fn_name: String,
+    pub(crate) transcript_prefix: bool,
+}
+
 impl Halo2VerifyingKey {
     pub(crate) fn len(&self) -> usize {
         (self.constants.len() * 0x20)
             + (self.fixed_comms.len() + self.permutation_comms.len()) * 0x40
     }
+
+    pub(crate) fn words(&self) -> Vec<(String, U256)> {
+        let comms = |name: &str, comms: &[(U256, U256)]| {
+            comms
+                .iter()
+                .enumerate()
+                .flat_map(|(idx, (x, y))| {
+                    [
+                        (format!("{name}[{idx}].x"), *x),
+                        (format!("{name}[{idx}].y"), *y),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        };
+        chain![
+            self.constants
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value)),
+            comms("fixed_comms", &self.fixed_comms),
+            comms("permutation_comms", &self.permutation_comms),
+        ]
+        .collect()
+    }
+
+    pub(crate) fn g1_points(&self) -> Vec<(U256, U256)> {
+        let g1 = (self.constant("g1_x"), self.constant("g1_y"));
+        chain![
+            [g1],
+            self.fixed_comms.clone(),
+            self.permutation_comms.clone()
+        ]
+        .collect()
+    }
+
+    // Pairing input of e(g1, g2) * e(-g1, g2) * e(g1, -s_g2) * e(-g1, -s_g2), which equals 1 if
+    // both G2 points are on curve and in subgroup, otherwise pairing precompile fails.
+    pub(crate) fn g2_check_pairing_input(&self) -> Vec<U256> {
+        let g1 = [self.constant("g1_x"), self.constant("g1_y")];
+        let neg_g1 = [
+            g1[0],
+            (modulus::<bn256::Fq>() - g1[1]) % modulus::<bn256::Fq>(),
+        ];
+        let g2 = ["g2_x_1", "g2_x_2", "g2_y_1", "g2_y_2"].map(|name| self.constant(name));
+        let neg_s_g2 = [
+            "neg_s_g2_x_1",
+            "neg_s_g2_x_2",
+            "neg_s_g2_y_1",
+            "neg_s_g2_y_2",
+        ]
+        .map(|name| self.constant(name));
+        chain![g1, g2, neg_g1, g2, g1, neg_s_g2, neg_g1, neg_s_g2].collect()
+    }
+
+    fn constant(&self, name: &str) -> U256 {
+        self.constants
+            .iter()
+            .find(|(constant_name, _)| *constant_name == name)
+            .unwrap()
+            .1
+    }
 }
 
-#[derive(Template)]
+#[derive(Clone, Template)]
 #[template(path = "Halo2Verifier.sol")]
 pub(crate) struct Halo2Verifier {
+    pub(crate) contract_name: String,
+    pub(crate) verify_fn_name: String,
+    pub(crate) verify_fn_signature: String,
+    pub(crate) license: String,
+    pub(crate) pragma: String,
     pub(crate) scheme: BatchOpenScheme,
     pub(crate) vk: Option<Halo2VerifyingKey>,
+    pub(crate) vk_source: VkSource,
+    pub(crate) deciding_key_source: DecidingKeySource,
+    pub(crate) return_mode: ReturnMode,
+    pub(crate) point_order: PointOrder,
+    pub(crate) calldata_bounds_check: bool,
+    pub(crate) non_canonical_point_check: bool,
+    pub(crate) pairing_failure_context: bool,
+    pub(crate) debug_errors: bool,
+    pub(crate) section_comments: bool,
+    pub(crate) challenge_reduction: ChallengeReduction,
+    pub(crate) result_index: Option<usize>,
+    pub(crate) allowlist_index: Option<usize>,
+    pub(crate) transcript_prefix: bool,
+    pub(crate) nullifier_interface: Option<NullifierInterface>,
+    pub(crate) stored_accumulator_index: Option<usize>,
+    pub(crate) verbose_gas: bool,
+    pub(crate) batch_verify: bool,
+    pub(crate) batch_aggregate: bool,
+    pub(crate) instance_permutation: bool,
+    pub(crate) signed_request: bool,
+    pub(crate) nonce_index: Option<usize>,
+    pub(crate) instance_bit_widths: Vec<(usize, usize)>,
+    pub(crate) hex_proof: bool,
+    pub(crate) memory_proof: bool,
+    pub(crate) dynamic_instances: Option<usize>,
+    pub(crate) inputs_root_index: Option<usize>,
+    pub(crate) poseidon_preimage: Option<PoseidonPreimage>,
+    pub(crate) upgradeable: bool,
+    pub(crate) deployment_check: bool,
+    pub(crate) has_additional_entrypoints: bool,
+    pub(crate) domain: Option<(u32, u64)>,
     pub(crate) vk_len: usize,
     pub(crate) proof_len: usize,
     pub(crate) vk_mptr: Ptr,
@@ -38,8 +191,82 @@ pub(crate) struct Halo2Verifier {
     pub(crate) num_challenges: Vec<usize>,
     pub(crate) num_evals: usize,
     pub(crate) num_quotients: usize,
+    pub(crate) hoisted_constants: Vec<(Ptr, String)>,
     pub(crate) quotient_eval_numer_computations: Vec<Vec<String>>,
     pub(crate) pcs_computations: Vec<Vec<String>>,
+    pub(crate) num_instances: usize,
+    pub(crate) memory_core: Option<String>,
+}
+
+impl Halo2Verifier {
+    // Whether proof and instances are read from memory, where `verifyMemory` copies them to in
+    // the layout of calldata.
+    pub(crate) fn proof_in_memory(&self) -> bool {
+        self.proof_cptr.loc() == Location::Memory
+    }
+
+    pub(crate) fn proof_load(&self) -> &'static str {
+        self.proof_cptr.loc().opcode()
+    }
+
+    // Pointer in calldata by its constant name, or its value in memory if proof is in memory.
+    pub(crate) fn proof_ptr(&self, name: &str) -> String {
+        if !self.proof_in_memory() {
+            return name.to_string();
+        }
+        let ptr = match name {
+            "PROOF_LEN_CPTR" => self.proof_cptr - 1,
+            "PROOF_CPTR" => self.proof_cptr,
+            "NUM_INSTANCE_CPTR" => self.proof_cptr + self.proof_len / 32,
+            "INSTANCE_CPTR" => self.proof_cptr + self.proof_len / 32 + 1,
+            "FIRST_QUOTIENT_X_CPTR" => self.quotient_comm_cptr,
+            "LAST_QUOTIENT_X_CPTR" => self.quotient_comm_cptr + 2 * (self.num_quotients - 1),
+            _ => unreachable!(),
+        };
+        ptr.to_string()
+    }
+
+    // End of memory overwritten when proof is in memory, which is the end of instances.
+    pub(crate) fn memory_end(&self) -> Ptr {
+        self.proof_cptr + self.proof_len / 32 + 1 + self.num_instances
+    }
+}
+
+// Constants of `Halo2Verifier.sol`, which it includes at contract level.
+#[derive(Template)]
+#[template(path = "Halo2VerifierConstants.sol")]
+pub(crate) struct Halo2VerifierConstants<'a>(pub(crate) &'a Halo2Verifier);
+
+// Assembly block of `verifyProof` of `Halo2Verifier.sol`, which it includes as function body.
+#[derive(Template)]
+#[template(path = "Halo2VerifierAssembly.sol")]
+pub(crate) struct Halo2VerifierAssembly<'a>(pub(crate) &'a Halo2Verifier);
+
+impl Deref for Halo2VerifierConstants<'_> {
+    type Target = Halo2Verifier;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl Deref for Halo2VerifierAssembly<'_> {
+    type Target = Halo2Verifier;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+#[derive(Template)]
+#[template(path = "Halo2VerifierPausable.sol")]
+pub(crate) struct Halo2VerifierPausable {
+    pub(crate) license: String,
+    pub(crate) pragma: String,
+    pub(crate) separate: bool,
+    pub(crate) verify_fn_name: String,
+    pub(crate) transcript_prefix: bool,
+    pub(crate) return_mode: ReturnMode,
 }
 
 impl Halo2VerifyingKey {
@@ -60,7 +287,80 @@ impl Halo2Verifier {
     }
 }
 
+impl Halo2VerifierConstants<'_> {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierAssembly<'_> {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierPausable {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierRouter {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierInterface {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierEncoder {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierTest {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
+impl Halo2VerifierDeploy {
+    pub(crate) fn render(&self, writer: &mut impl fmt::Write) -> Result<(), fmt::Error> {
+        self.render_into(writer).map_err(|err| match err {
+            Error::Fmt(err) => err,
+            _ => unreachable!(),
+        })
+    }
+}
+
 mod filters {
+    use sha3::{Digest, Keccak256};
     use std::fmt::LowerHex;
 
     pub fn hex(value: impl LowerHex) -> ::askama::Result<String> {
@@ -80,4 +380,27 @@ mod filters {
             Ok(string)
         }
     }
+
+    pub fn hex_word(value: impl LowerHex) -> ::askama::Result<String> {
+        Ok(hex_padded(value, 64)?[2..].to_string())
+    }
+
+    // Mixed-case checksum encoding by EIP-55, which `solc` requires for address literal.
+    pub fn checksum_address(address: impl AsRef<[u8]>) -> ::askama::Result<String> {
+        let address = ::hex::encode(address);
+        let hash = Keccak256::digest(address.as_bytes());
+        let address = address
+            .chars()
+            .enumerate()
+            .map(|(idx, char)| {
+                let nibble = (hash[idx / 2] >> if idx % 2 == 0 { 4 } else { 0 }) & 0xf;
+                if nibble >= 8 {
+                    char.to_ascii_uppercase()
+                } else {
+                    char
+                }
+            })
+            .collect::<String>();
+        Ok(format!("0x{address}"))
+    }
 }
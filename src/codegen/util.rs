@@ -1,6 +1,12 @@
-use crate::codegen::{
-    template::Halo2VerifyingKey,
-    BatchOpenScheme::{self, Bdfg21, Gwc19},
+use crate::{
+    codegen::{
+        template::Halo2VerifyingKey,
+        BatchOpenScheme::{self, Bdfg21, Gwc19},
+    },
+    proof::{
+        PointOrder, ProofField,
+        ProofFieldKind::{Point, Scalar},
+    },
 };
 use halo2_proofs::{
     halo2curves::{bn256, ff::PrimeField, CurveAffine},
@@ -166,6 +172,82 @@ impl ConstraintSystemMeta {
             }
         }
     }
+
+    pub(crate) fn proof_schema(&self, scheme: BatchOpenScheme) -> Vec<ProofField> {
+        let advice_comms = (0..self.advice_indices.len()).map(|idx| {
+            let column = self.advice_indices.iter().position(|index| *index == idx);
+            (format!("advice_comms[{}]", column.unwrap()), Point)
+        });
+        let lookup_permuted_comms = (0..self.num_lookup_permuteds / 2).flat_map(|idx| {
+            [
+                (format!("lookup_permuted_comms[{idx}].input"), Point),
+                (format!("lookup_permuted_comms[{idx}].table"), Point),
+            ]
+        });
+        let permutation_z_comms =
+            (0..self.num_permutation_zs).map(|idx| (format!("permutation_z_comms[{idx}]"), Point));
+        let lookup_z_comms =
+            (0..self.num_lookup_zs).map(|idx| (format!("lookup_z_comms[{idx}]"), Point));
+        let quotient_comms =
+            (0..self.num_quotients).map(|idx| (format!("quotient_comms[{idx}]"), Point));
+        let advice_evals = self
+            .advice_queries
+            .iter()
+            .map(|(column, rotation)| (format!("advice_evals[{column}][{rotation}]"), Scalar));
+        let fixed_evals = self
+            .fixed_queries
+            .iter()
+            .map(|(column, rotation)| (format!("fixed_evals[{column}][{rotation}]"), Scalar));
+        let permutation_evals =
+            (0..self.num_permutations()).map(|idx| (format!("permutation_evals[{idx}]"), Scalar));
+        let permutation_z_evals = (0..self.num_permutation_zs).flat_map(|idx| {
+            chain![
+                [
+                    format!("permutation_z_evals[{idx}].z"),
+                    format!("permutation_z_evals[{idx}].z_next"),
+                ],
+                (idx != self.num_permutation_zs - 1)
+                    .then(|| format!("permutation_z_evals[{idx}].z_last")),
+            ]
+            .map(|name| (name, Scalar))
+        });
+        let lookup_evals = (0..self.num_lookup_zs).flat_map(|idx| {
+            [
+                "z",
+                "z_next",
+                "permuted_input",
+                "permuted_input_prev",
+                "permuted_table",
+            ]
+            .map(|name| (format!("lookup_evals[{idx}].{name}"), Scalar))
+        });
+        let batch_open_proof = match scheme {
+            Bdfg21 => ["w", "w_prime"],
+            Gwc19 => unimplemented!(),
+        }
+        .map(|name| (name.to_string(), Point));
+        chain![
+            advice_comms,
+            lookup_permuted_comms,
+            permutation_z_comms,
+            lookup_z_comms,
+            [("random_comm".to_string(), Point)],
+            quotient_comms,
+            advice_evals,
+            fixed_evals,
+            [("random_eval".to_string(), Scalar)],
+            permutation_evals,
+            permutation_z_evals,
+            lookup_evals,
+            batch_open_proof,
+        ]
+        .scan(0, |offset, (name, kind)| {
+            let field = ProofField::new(name, kind, *offset);
+            *offset += kind.size();
+            Some(field)
+        })
+        .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -174,7 +256,8 @@ pub(crate) struct Data {
     pub(crate) theta_mptr: Ptr,
 
     pub(crate) quotient_comm_cptr: Ptr,
-    pub(crate) w_cptr: Ptr,
+    pub(crate) w: EcPoint,
+    pub(crate) w_prime: EcPoint,
 
     pub(crate) fixed_comms: Vec<EcPoint>,
     pub(crate) permutation_comms: HashMap<Column<Any>, EcPoint>,
@@ -204,6 +287,7 @@ impl Data {
         vk: &Halo2VerifyingKey,
         vk_mptr: Ptr,
         proof_cptr: Ptr,
+        point_order: PointOrder,
     ) -> Self {
         let fixed_comm_mptr = vk_mptr + vk.constants.len();
         let permutation_comm_mptr = fixed_comm_mptr + 2 * vk.fixed_comms.len();
@@ -234,23 +318,24 @@ impl Data {
             EcPoint::range(permutation_comm_mptr)
         )
         .collect();
+        let proof_comms = |start: Ptr, len: usize| {
+            (0..len).map(move |idx| EcPoint::from_proof(start + 2 * idx, point_order))
+        };
         let advice_comms = meta
             .advice_indices
             .iter()
-            .map(|idx| advice_comm_start + 2 * idx)
-            .map_into()
+            .map(|idx| EcPoint::from_proof(advice_comm_start + 2 * idx, point_order))
             .collect();
-        let lookup_permuted_comms = EcPoint::range(lookup_permuted_comm_start)
-            .take(meta.num_lookup_permuteds)
-            .tuples()
-            .collect();
-        let permutation_z_comms = EcPoint::range(permutation_z_comm_start)
-            .take(meta.num_permutation_zs)
-            .collect();
-        let lookup_z_comms = EcPoint::range(lookup_z_comm_start)
-            .take(meta.num_lookup_zs)
-            .collect();
-        let random_comm = random_comm_start.into();
+        let lookup_permuted_comms =
+            proof_comms(lookup_permuted_comm_start, meta.num_lookup_permuteds)
+                .tuples()
+                .collect();
+        let permutation_z_comms =
+            proof_comms(permutation_z_comm_start, meta.num_permutation_zs).collect();
+        let lookup_z_comms = proof_comms(lookup_z_comm_start, meta.num_lookup_zs).collect();
+        let random_comm = EcPoint::from_proof(random_comm_start, point_order);
+        let w = EcPoint::from_proof(w_cptr, point_order);
+        let w_prime = EcPoint::from_proof(w_cptr + 2, point_order);
         let computed_quotient_comm = EcPoint::new(
             Ptr::memory("QUOTIENT_X_MPTR"),
             Ptr::memory("QUOTIENT_Y_MPTR"),
@@ -293,7 +378,8 @@ impl Data {
             challenge_mptr,
             theta_mptr,
             quotient_comm_cptr: quotient_comm_start,
-            w_cptr,
+            w,
+            w_prime,
 
             fixed_comms,
             permutation_comms,
@@ -325,7 +411,7 @@ pub(crate) enum Location {
 }
 
 impl Location {
-    fn opcode(&self) -> &'static str {
+    pub(crate) fn opcode(&self) -> &'static str {
         match self {
             Location::Calldata => "calldataload",
             Location::Memory => "mload",
@@ -505,6 +591,14 @@ impl EcPoint {
         }
     }
 
+    // EC point in proof at `ptr` with coordinates in `point_order`.
+    pub(crate) fn from_proof(ptr: Ptr, point_order: PointOrder) -> Self {
+        match point_order {
+            PointOrder::Xy => Self::new(ptr, ptr + 1),
+            PointOrder::Yx => Self::new(ptr + 1, ptr),
+        }
+    }
+
     pub(crate) fn range(ec_point: impl Into<EcPoint>) -> impl Iterator<Item = EcPoint> {
         let ptr = ec_point.into().x.ptr();
         (0..).map(move |idx| ptr + 2 * idx).map_into()
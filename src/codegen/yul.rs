@@ -0,0 +1,166 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// Error returned by [`check_yul_instructions`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum YulError {
+    /// Instruction is neither a supported builtin nor a function defined in the same source.
+    UnsupportedInstruction {
+        /// Name of the instruction.
+        name: String,
+        /// Line number (1-based) where the instruction is first used.
+        line: usize,
+    },
+}
+
+impl Display for YulError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedInstruction { name, line } => {
+                write!(f, "unsupported instruction `{name}` at line {line}")
+            }
+        }
+    }
+}
+
+impl Error for YulError {}
+
+// Builtins whose semantics are identical in inline assembly of Solidity and in standalone Yul
+// object, and which have no side effect other than memory, return data and storage of itself.
+const SUPPORTED_INSTRUCTIONS: &[&str] = &[
+    "add",
+    "addmod",
+    "address",
+    "and",
+    "byte",
+    "calldatacopy",
+    "calldataload",
+    "calldatasize",
+    "caller",
+    "callvalue",
+    "codecopy",
+    "codesize",
+    "datacopy",
+    "dataoffset",
+    "datasize",
+    "div",
+    "eq",
+    "exp",
+    "extcodecopy",
+    "extcodesize",
+    "gas",
+    "gt",
+    "invalid",
+    "iszero",
+    "keccak256",
+    "lt",
+    "mload",
+    "mod",
+    "mstore",
+    "mstore8",
+    "mul",
+    "mulmod",
+    "not",
+    "or",
+    "pop",
+    "return",
+    "returndatacopy",
+    "returndatasize",
+    "revert",
+    "sar",
+    "sdiv",
+    "sgt",
+    "shl",
+    "shr",
+    "signextend",
+    "sload",
+    "slt",
+    "smod",
+    "sstore",
+    "staticcall",
+    "stop",
+    "sub",
+    "xor",
+];
+
+/// Enumerate instructions used by `yul`, which is either a standalone Yul object or an `assembly`
+/// block, e.g. rendered by `SolidityGenerator::render_yul` or `render_core_assembly` and then
+/// edited by hand, and return them in lexical order.
+///
+/// It fails fast with the first instruction that is neither in a fixed set of builtins known to be
+/// translated faithfully (e.g. no `call`, `delegatecall`, `create` or `verbatim_*`) nor a
+/// function defined in `yul`, so a verifier relying on it is not shipped silently broken.
+///
+/// Comments and string literals are skipped, and everything else is assumed to be valid Yul.
+pub fn check_yul_instructions(yul: &str) -> Result<BTreeSet<String>, YulError> {
+    let lines = yul
+        .lines()
+        .map(strip_comment_and_strings)
+        .collect::<Vec<_>>();
+    let defined = lines
+        .iter()
+        .flat_map(|line| {
+            let mut tokens = tokens(line).map(|(token, _)| token).peekable();
+            let mut defined = Vec::new();
+            while let Some(token) = tokens.next() {
+                if token == "function" {
+                    defined.extend(tokens.peek().copied());
+                }
+            }
+            defined
+        })
+        .collect::<BTreeSet<_>>();
+
+    let mut instructions = BTreeSet::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let mut prev = None;
+        for (token, is_call) in tokens(line) {
+            if is_call && prev != Some("function") && !defined.contains(token) {
+                if !SUPPORTED_INSTRUCTIONS.contains(&token) {
+                    return Err(YulError::UnsupportedInstruction {
+                        name: token.to_string(),
+                        line: idx + 1,
+                    });
+                }
+                instructions.insert(token.to_string());
+            }
+            prev = Some(token);
+        }
+    }
+    Ok(instructions)
+}
+
+fn strip_comment_and_strings(line: &str) -> String {
+    let mut stripped = String::with_capacity(line.len());
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+    while let Some(char) = chars.next() {
+        match char {
+            '"' => in_string = !in_string,
+            '/' if !in_string && chars.peek() == Some(&'/') => break,
+            _ if !in_string => stripped.push(char),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+// Return identifiers in `line` along with whether each is called, i.e. followed by `(`.
+fn tokens(line: &str) -> impl Iterator<Item = (&str, bool)> {
+    let is_token_char = |char: char| char.is_ascii_alphanumeric() || char == '_' || char == '.';
+    let mut rest = line;
+    std::iter::from_fn(move || loop {
+        let start = rest.find(is_token_char)?;
+        rest = &rest[start..];
+        let end = rest.find(|char| !is_token_char(char)).unwrap_or(rest.len());
+        let token = &rest[..end];
+        rest = &rest[end..];
+        if token.starts_with(|char: char| char.is_ascii_digit()) {
+            continue;
+        }
+        return Some((token, rest.trim_start().starts_with('(')));
+    })
+}
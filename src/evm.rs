@@ -1,7 +1,12 @@
-use crate::codegen::util::{fr_to_u256, to_u256_be_bytes};
-use halo2_proofs::halo2curves::bn256;
+use crate::{
+    codegen::util::{fr_to_u256, g2_to_u256s, to_u256_be_bytes},
+    transcript::modulus,
+};
+use halo2_proofs::halo2curves::{bn256, ff::PrimeField};
 use itertools::chain;
 use ruint::aliases::U256;
+use sha3::{Digest, Keccak256};
+use std::iter;
 
 /// Function signature of `verifyProof(bytes,uint256[])`.
 pub const FN_SIG_VERIFY_PROOF: [u8; 4] = [0x1e, 0x8e, 0x1e, 0x13];
@@ -9,6 +14,118 @@ pub const FN_SIG_VERIFY_PROOF: [u8; 4] = [0x1e, 0x8e, 0x1e, 0x13];
 /// Function signature of `verifyProof(address,bytes,uint256[])`.
 pub const FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS: [u8; 4] = [0xaf, 0x83, 0xa1, 0x8d];
 
+/// Function signature of `verifyProof(bytes,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PROOF_WITH_PREFIX: [u8; 4] = [0xe8, 0xdc, 0x04, 0x52];
+
+/// Function signature of `verifyProof(address,bytes,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PREFIX: [u8; 4] = [0x1c, 0xcb, 0x84, 0x03];
+
+/// Function signature of `verify(uint256,uint256,uint256,bytes)`.
+pub const FN_SIG_VERIFY_NULLIFIER: [u8; 4] = [0x2e, 0x8f, 0xac, 0xba];
+
+/// Function signature of `verify(address,uint256,uint256,uint256,bytes)`.
+pub const FN_SIG_VERIFY_NULLIFIER_WITH_VK_ADDRESS: [u8; 4] = [0x26, 0xb2, 0x8e, 0xdf];
+
+/// Function signature of `verifyAndGet(uint256[],bytes)`.
+pub const FN_SIG_VERIFY_AND_GET: [u8; 4] = [0x00, 0x67, 0xc7, 0x09];
+
+/// Function signature of `verifyAndGet(address,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_AND_GET_WITH_VK_ADDRESS: [u8; 4] = [0x51, 0x47, 0x40, 0x15];
+
+/// Function signature of `verifyVerboseGas(uint256[],bytes)`.
+pub const FN_SIG_VERIFY_VERBOSE_GAS: [u8; 4] = [0x02, 0x03, 0x9d, 0x34];
+
+/// Function signature of `verifyVerboseGas(address,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_VERBOSE_GAS_WITH_VK_ADDRESS: [u8; 4] = [0xe4, 0xf1, 0xda, 0x88];
+
+/// Function signature of `verify(bytes32,uint256[],bytes)` of `Halo2VerifierRouter`.
+pub const FN_SIG_VERIFY_ROUTED: [u8; 4] = [0x83, 0x76, 0x90, 0x6b];
+
+/// Function signature of `verifyPermuted(uint256[],uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PERMUTED: [u8; 4] = [0x44, 0x5f, 0x9f, 0xd4];
+
+/// Function signature of `verifyPermuted(address,uint256[],uint256[],bytes)`.
+pub const FN_SIG_VERIFY_PERMUTED_WITH_VK_ADDRESS: [u8; 4] = [0x14, 0x72, 0xbe, 0xf4];
+
+/// Function signature of `verifySigned(address,uint256[],bytes,uint8,bytes32,bytes32)`.
+pub const FN_SIG_VERIFY_SIGNED: [u8; 4] = [0x32, 0xec, 0xd3, 0x05];
+
+/// Function signature of `verifySigned(address,address,uint256[],bytes,uint8,bytes32,bytes32)`.
+pub const FN_SIG_VERIFY_SIGNED_WITH_VK_ADDRESS: [u8; 4] = [0x79, 0xa1, 0x2b, 0x55];
+
+/// Function signature of `verifyWithNonce(uint256[],bytes)`.
+pub const FN_SIG_VERIFY_WITH_NONCE: [u8; 4] = [0x14, 0x64, 0xcf, 0x6b];
+
+/// Function signature of `verifyWithNonce(address,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_WITH_NONCE_WITH_VK_ADDRESS: [u8; 4] = [0x08, 0xd8, 0x35, 0xec];
+
+/// Function signature of `verifyHex(uint256[],string)`.
+pub const FN_SIG_VERIFY_HEX: [u8; 4] = [0x15, 0x25, 0x93, 0x42];
+
+/// Function signature of `verifyHex(address,uint256[],string)`.
+pub const FN_SIG_VERIFY_HEX_WITH_VK_ADDRESS: [u8; 4] = [0x29, 0x6d, 0xdc, 0x1d];
+
+/// Function signature of `verifyMemory(uint256[],bytes)`.
+pub const FN_SIG_VERIFY_MEMORY: [u8; 4] = [0x12, 0xce, 0x60, 0x4c];
+
+/// Function signature of `verifyMemory(address,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_MEMORY_WITH_VK_ADDRESS: [u8; 4] = [0x52, 0x2d, 0xb1, 0x1d];
+
+/// Function signature of `verify(uint256[],bytes)`.
+pub const FN_SIG_VERIFY_DYNAMIC: [u8; 4] = [0xbd, 0x20, 0x5a, 0x90];
+
+/// Function signature of `verify(address,uint256[],bytes)`.
+pub const FN_SIG_VERIFY_DYNAMIC_WITH_VK_ADDRESS: [u8; 4] = [0xd6, 0x13, 0xb9, 0xdc];
+
+/// Function signature of `verifyWithInputsRoot(uint256[],bytes,bytes32[])`.
+pub const FN_SIG_VERIFY_WITH_INPUTS_ROOT: [u8; 4] = [0xdc, 0x1a, 0x03, 0xf6];
+
+/// Function signature of `verifyWithInputsRoot(address,uint256[],bytes,bytes32[])`.
+pub const FN_SIG_VERIFY_WITH_INPUTS_ROOT_WITH_VK_ADDRESS: [u8; 4] = [0xc9, 0x31, 0x2d, 0x15];
+
+/// Function signature of `verifyManyVerbose(uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_MANY_VERBOSE: [u8; 4] = [0x0e, 0x50, 0x1f, 0xf6];
+
+/// Function signature of `verifyManyVerbose(address,uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_MANY_VERBOSE_WITH_VK_ADDRESS: [u8; 4] = [0x8d, 0xf6, 0x5d, 0x48];
+
+/// Function signature of `verifyBatch(uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_BATCH: [u8; 4] = [0x29, 0x9c, 0xeb, 0xf9];
+
+/// Function signature of `verifyBatch(address,uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_BATCH_WITH_VK_ADDRESS: [u8; 4] = [0xe3, 0x52, 0xf8, 0x5f];
+
+/// Function signature of `verifyBatchAggregated(uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_BATCH_AGGREGATED: [u8; 4] = [0x64, 0x47, 0x6f, 0xff];
+
+/// Function signature of `verifyBatchAggregated(address,uint256[][],bytes[])`.
+pub const FN_SIG_VERIFY_BATCH_AGGREGATED_WITH_VK_ADDRESS: [u8; 4] = [0x5a, 0xf1, 0x37, 0x58];
+
+/// Function signature of `verifyWithPoseidonPreimage(uint256[],bytes,uint256[])`.
+pub const FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE: [u8; 4] = [0x77, 0xd1, 0x9e, 0x60];
+
+/// Function signature of `verifyWithPoseidonPreimage(address,uint256[],bytes,uint256[])`.
+pub const FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE_WITH_VK_ADDRESS: [u8; 4] = [0x0b, 0xd7, 0x61, 0x1f];
+
+/// Function signature of `verifyInAllowlist(uint256[],bytes,bytes32[])`.
+pub const FN_SIG_VERIFY_IN_ALLOWLIST: [u8; 4] = [0x28, 0x73, 0xda, 0xf6];
+
+/// Function signature of `verifyInAllowlist(address,uint256[],bytes,bytes32[])`.
+pub const FN_SIG_VERIFY_IN_ALLOWLIST_WITH_VK_ADDRESS: [u8; 4] = [0x33, 0x41, 0x37, 0x68];
+
+/// Encode deciding key `g2` and `s_g2`, which are `params.g2()` and `params.s_g2()` of SRS, into
+/// constructor arguments `(uint256[4] g2, uint256[4] s_g2)` of `Halo2Verifier` rendered with
+/// `DecidingKeySource::Constructor`, to be appended to its creation bytecode.
+///
+/// Each point takes 4 words as `(x_1, x_2, y_1, y_2)`, where each coordinate in `Fq2` is
+/// `c_1 * u + c_2` with `u^2 = -1`, and each of `c_1` and `c_2` is 32 bytes big-endian. So the
+/// coefficient of `u` comes first, which is the order the pairing precompile of EIP-197 expects.
+pub fn encode_deciding_key(g2: &bn256::G2Affine, s_g2: &bn256::G2Affine) -> Vec<u8> {
+    chain![g2_to_u256s(g2), g2_to_u256s(s_g2)]
+        .flat_map(to_u256_be_bytes)
+        .collect()
+}
+
 /// Encode proof into calldata to invoke `Halo2Verifier.verifyProof`.
 ///
 /// For `vk_address`:
@@ -24,40 +141,721 @@ pub fn encode_calldata(
     } else {
         (FN_SIG_VERIFY_PROOF, 0x40)
     };
-    let vk_address = if let Some(vk_address) = vk_address {
+    let vk_address = vk_address_word(vk_address);
+    let num_instances = instances.len();
+    chain![
+        fn_sig,                                                      // function signature
+        vk_address.into_iter().flatten(),                            // verifying key address
+        to_u256_be_bytes(offset),                                    // offset of proof
+        to_u256_be_bytes(offset + 0x20 + proof.len()),               // offset of instances
+        to_u256_be_bytes(proof.len()),                               // length of proof
+        proof.iter().cloned(),                                       // proof
+        to_u256_be_bytes(num_instances),                             // length of instances
+        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
+    ]
+    .collect()
+}
+
+/// Encode proof and transcript prefix into calldata to invoke `Halo2Verifier.verifyProof`, which
+/// takes prefix only when `SolidityGenerator::set_transcript_prefix` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_calldata_with_prefix(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    prefix: &[u8],
+) -> Vec<u8> {
+    let (fn_sig, offset) = if vk_address.is_some() {
+        (FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PREFIX, 0x80)
+    } else {
+        (FN_SIG_VERIFY_PROOF_WITH_PREFIX, 0x60)
+    };
+    let vk_address = vk_address_word(vk_address);
+    let num_instances = instances.len();
+    let instances_offset = offset + 0x20 + proof.len();
+    let padded_prefix_len = (prefix.len() + 0x1f) / 0x20 * 0x20;
+    chain![
+        fn_sig,                                                          // function signature
+        vk_address.into_iter().flatten(),                                // verifying key address
+        to_u256_be_bytes(offset),                                        // offset of proof
+        to_u256_be_bytes(instances_offset),                              // offset of instances
+        to_u256_be_bytes(instances_offset + 0x20 * (1 + num_instances)), // offset of prefix
+        to_u256_be_bytes(proof.len()),                                   // length of proof
+        proof.iter().cloned(),                                           // proof
+        to_u256_be_bytes(num_instances),                                 // length of instances
+        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes),     // instances
+        to_u256_be_bytes(prefix.len()),                                  // length of prefix
+        prefix.iter().cloned(),                                          // prefix
+        vec![0; padded_prefix_len - prefix.len()],                       // padding of prefix
+    ]
+    .collect()
+}
+
+/// Encode fields of external nullifier interface and proof into calldata to invoke
+/// `Halo2Verifier.verify`, which is only available when `SolidityGenerator::set_nullifier_interface`
+/// is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_nullifier_calldata(
+    vk_address: Option<[u8; 20]>,
+    signal: bn256::Fr,
+    root: bn256::Fr,
+    nullifier_hash: bn256::Fr,
+    proof: &[u8],
+) -> Vec<u8> {
+    let (fn_sig, offset) = if vk_address.is_some() {
+        (FN_SIG_VERIFY_NULLIFIER_WITH_VK_ADDRESS, 0xa0)
+    } else {
+        (FN_SIG_VERIFY_NULLIFIER, 0x80)
+    };
+    let vk_address = vk_address_word(vk_address);
+    chain![
+        fn_sig,                                       // function signature
+        vk_address.into_iter().flatten(),             // verifying key address
+        to_u256_be_bytes(fr_to_u256(signal)),         // signal
+        to_u256_be_bytes(fr_to_u256(root)),           // root
+        to_u256_be_bytes(fr_to_u256(nullifier_hash)), // nullifier hash
+        to_u256_be_bytes(offset),                     // offset of proof
+        to_u256_be_bytes(proof.len()),                // length of proof
+        proof.iter().cloned(),                        // proof
+    ]
+    .collect()
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verifyAndGet`, which is only available
+/// when `SolidityGenerator::set_result_index` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_and_get_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_AND_GET_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_AND_GET
+    };
+    encode_instances_then_proof(fn_sig, vk_address, proof, instances)
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verifyVerboseGas`, which is only available
+/// when `SolidityGenerator::set_verbose_gas` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_verbose_gas_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_VERBOSE_GAS_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_VERBOSE_GAS
+    };
+    encode_instances_then_proof(fn_sig, vk_address, proof, instances)
+}
+
+/// Encode proofs into calldata to invoke `Halo2Verifier.verifyManyVerbose`, which is only
+/// available when `SolidityGenerator::set_verbose_gas` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+///
+/// # Panics
+/// Panics if `proofs` and `instances` have different lengths.
+pub fn encode_verify_many_verbose_calldata(
+    vk_address: Option<[u8; 20]>,
+    proofs: &[impl AsRef<[u8]>],
+    instances: &[impl AsRef<[bn256::Fr]>],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_MANY_VERBOSE_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_MANY_VERBOSE
+    };
+    encode_instances_list_then_proofs(fn_sig, vk_address, proofs, instances)
+}
+
+/// Encode proofs into calldata to invoke `Halo2Verifier.verifyBatch`, which is only available
+/// when `SolidityGenerator::set_batch_verify` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+///
+/// # Panics
+/// Panics if `proofs` and `instances` have different lengths.
+pub fn encode_verify_batch_calldata(
+    vk_address: Option<[u8; 20]>,
+    proofs: &[impl AsRef<[u8]>],
+    instances: &[impl AsRef<[bn256::Fr]>],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_BATCH_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_BATCH
+    };
+    encode_instances_list_then_proofs(fn_sig, vk_address, proofs, instances)
+}
+
+/// Encode proofs into calldata to invoke `Halo2Verifier.verifyBatchAggregated`, which is only
+/// available when `SolidityGenerator::set_batch_aggregate` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+///
+/// # Panics
+/// Panics if `proofs` and `instances` have different lengths.
+pub fn encode_verify_batch_aggregated_calldata(
+    vk_address: Option<[u8; 20]>,
+    proofs: &[impl AsRef<[u8]>],
+    instances: &[impl AsRef<[bn256::Fr]>],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_BATCH_AGGREGATED_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_BATCH_AGGREGATED
+    };
+    encode_instances_list_then_proofs(fn_sig, vk_address, proofs, instances)
+}
+
+fn encode_instances_list_then_proofs(
+    fn_sig: [u8; 4],
+    vk_address: Option<[u8; 20]>,
+    proofs: &[impl AsRef<[u8]>],
+    instances: &[impl AsRef<[bn256::Fr]>],
+) -> Vec<u8> {
+    assert_eq!(proofs.len(), instances.len());
+
+    let vk_address = vk_address_word(vk_address);
+    // Each element is encoded as length followed by content padded to multiple of 32 bytes, with
+    // offsets relative to the start of the element offsets.
+    let encode_array = |elements: Vec<Vec<u8>>| -> Vec<u8> {
+        let offsets = elements
+            .iter()
+            .scan(0x20 * elements.len(), |offset, element| {
+                let current = *offset;
+                *offset += element.len();
+                Some(current)
+            });
+        chain![
+            to_u256_be_bytes(elements.len()),
+            offsets.flat_map(to_u256_be_bytes).collect::<Vec<_>>(),
+            elements.concat(),
+        ]
+        .collect()
+    };
+    let instances = encode_array(
+        instances
+            .iter()
+            .map(|instances| {
+                let instances = instances.as_ref();
+                chain![
+                    to_u256_be_bytes(instances.len()),
+                    instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes),
+                ]
+                .collect()
+            })
+            .collect(),
+    );
+    let proofs = encode_array(
+        proofs
+            .iter()
+            .map(|proof| {
+                let proof = proof.as_ref();
+                let padding = (0x20 - proof.len() % 0x20) % 0x20;
+                chain![
+                    to_u256_be_bytes(proof.len()),
+                    proof.iter().cloned(),
+                    iter::repeat(0).take(padding),
+                ]
+                .collect()
+            })
+            .collect(),
+    );
+    let offset = if vk_address.is_some() { 0x60 } else { 0x40 };
+    chain![
+        fn_sig,                                     // function signature
+        vk_address.into_iter().flatten(),           // verifying key address
+        to_u256_be_bytes(offset),                   // offset of instances
+        to_u256_be_bytes(offset + instances.len()), // offset of proofs
+        instances,                                  // instances
+        proofs,                                     // proofs
+    ]
+    .collect()
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verifyWithNonce`, which is only available
+/// when `SolidityGenerator::set_nonce_index` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_with_nonce_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_WITH_NONCE_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_WITH_NONCE
+    };
+    encode_instances_then_proof(fn_sig, vk_address, proof, instances)
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verifyMemory(uint256[],bytes)`, which is
+/// only available when `SolidityGenerator::set_memory_proof` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_memory_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_MEMORY_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_MEMORY
+    };
+    encode_instances_then_proof(fn_sig, vk_address, proof, instances)
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verify(uint256[],bytes)`, which is only
+/// available when `SolidityGenerator::set_dynamic_instances` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_dynamic_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_DYNAMIC_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_DYNAMIC
+    };
+    encode_instances_then_proof(fn_sig, vk_address, proof, instances)
+}
+
+fn encode_instances_then_proof(
+    fn_sig: [u8; 4],
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    encode_word_then_instances_then_proof(fn_sig, vk_address_word(vk_address), proof, instances)
+}
+
+// Encode `vk_address` as an ABI `address` word, or nothing if verifying key is embedded.
+fn vk_address_word(vk_address: Option<[u8; 20]>) -> Option<[u8; 0x20]> {
+    vk_address.map(|vk_address| {
         U256::try_from_be_slice(&vk_address)
             .unwrap()
             .to_be_bytes::<0x20>()
-            .to_vec()
+    })
+}
+
+fn encode_word_then_instances_then_proof(
+    fn_sig: [u8; 4],
+    word: Option<[u8; 0x20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let offset = if word.is_some() { 0x60 } else { 0x40 };
+    let num_instances = instances.len();
+    chain![
+        fn_sig,                                                      // function signature
+        word.into_iter().flatten(),                                  // leading word
+        to_u256_be_bytes(offset),                                    // offset of instances
+        to_u256_be_bytes(offset + 0x20 * (1 + num_instances)),       // offset of proof
+        to_u256_be_bytes(num_instances),                             // length of instances
+        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
+        to_u256_be_bytes(proof.len()),                               // length of proof
+        proof.iter().cloned(),                                       // proof
+    ]
+    .collect()
+}
+
+/// Encode proof into calldata to invoke `Halo2VerifierRouter.verify`, which routes to verifying
+/// key registered under `circuit_hash`.
+pub fn encode_router_calldata(
+    circuit_hash: [u8; 32],
+    proof: &[u8],
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    encode_word_then_instances_then_proof(
+        FN_SIG_VERIFY_ROUTED,
+        Some(circuit_hash),
+        proof,
+        instances,
+    )
+}
+
+/// Encode proof into calldata to invoke `Halo2Verifier.verifyPermuted`, which is only available
+/// when `SolidityGenerator::set_instance_permutation` is set. The `instances[i]` is moved to index
+/// `permutation[i]` before verifying.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_permuted_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    permutation: &[usize],
+) -> Vec<u8> {
+    let (fn_sig, offset) = if vk_address.is_some() {
+        (FN_SIG_VERIFY_PERMUTED_WITH_VK_ADDRESS, 0x80)
+    } else {
+        (FN_SIG_VERIFY_PERMUTED, 0x60)
+    };
+    let vk_address = vk_address_word(vk_address);
+    let permutation_offset = offset + 0x20 * (1 + instances.len());
+    let proof_offset = permutation_offset + 0x20 * (1 + permutation.len());
+    chain![
+        fn_sig,                                                      // function signature
+        vk_address.into_iter().flatten(),                            // verifying key address
+        to_u256_be_bytes(offset),                                    // offset of instances
+        to_u256_be_bytes(permutation_offset),                        // offset of permutation
+        to_u256_be_bytes(proof_offset),                              // offset of proof
+        to_u256_be_bytes(instances.len()),                           // length of instances
+        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
+        to_u256_be_bytes(permutation.len()),                         // length of permutation
+        permutation.iter().flat_map(|idx| to_u256_be_bytes(*idx)),   // permutation
+        to_u256_be_bytes(proof.len()),                               // length of proof
+        proof.iter().cloned(),                                       // proof
+    ]
+    .collect()
+}
+
+/// Return EIP-712 digest of `ProofRequest(uint256[] instances,bytes32 proofHash,uint256 nonce)` to
+/// be signed for `verifySigned` of verifier named `contract_name` deployed at `verifier` on chain
+/// `chain_id`, which is only available when `SolidityGenerator::set_signed_request` is set. The
+/// `nonce` should be `signerNonces(signer)` of the verifier.
+pub fn eip712_proof_request_digest(
+    chain_id: u64,
+    contract_name: &str,
+    verifier: [u8; 20],
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    nonce: u64,
+) -> [u8; 32] {
+    let keccak256 = |data: &[u8]| -> [u8; 32] { Keccak256::digest(data).into() };
+    let domain_separator = keccak256(
+        &chain![
+            keccak256(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)"),
+            keccak256(contract_name.as_bytes()),
+            keccak256(b"1"),
+            to_u256_be_bytes(chain_id),
+            [0; 12],
+            verifier,
+        ]
+        .collect::<Vec<_>>(),
+    );
+    let instances = instances
+        .iter()
+        .map(fr_to_u256)
+        .flat_map(to_u256_be_bytes)
+        .collect::<Vec<_>>();
+    let struct_hash = keccak256(
+        &chain![
+            keccak256(b"ProofRequest(uint256[] instances,bytes32 proofHash,uint256 nonce)"),
+            keccak256(&instances),
+            keccak256(proof),
+            to_u256_be_bytes(nonce),
+        ]
+        .collect::<Vec<_>>(),
+    );
+    keccak256(&chain![[0x19, 0x01], domain_separator, struct_hash].collect::<Vec<_>>())
+}
+
+/// Encode signed request into calldata to invoke `Halo2Verifier.verifySigned`, where
+/// `signature` is `(v, r, s)` of digest returned by [`eip712_proof_request_digest`].
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_signed_calldata(
+    vk_address: Option<[u8; 20]>,
+    signer: [u8; 20],
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    (v, r, s): (u8, [u8; 32], [u8; 32]),
+) -> Vec<u8> {
+    let (fn_sig, offset) = if vk_address.is_some() {
+        (FN_SIG_VERIFY_SIGNED_WITH_VK_ADDRESS, 0xe0)
     } else {
-        Vec::new()
+        (FN_SIG_VERIFY_SIGNED, 0xc0)
     };
+    let vk_address = vk_address_word(vk_address);
     let num_instances = instances.len();
     chain![
         fn_sig,                                                      // function signature
-        vk_address,                                                  // verifying key address
-        to_u256_be_bytes(offset),                                    // offset of proof
-        to_u256_be_bytes(offset + 0x20 + proof.len()),               // offset of instances
+        vk_address.into_iter().flatten(),                            // verifying key address
+        [0; 12],                                                     // padding of signer
+        signer,                                                      // signer
+        to_u256_be_bytes(offset),                                    // offset of instances
+        to_u256_be_bytes(offset + 0x20 * (1 + num_instances)),       // offset of proof
+        to_u256_be_bytes(v),                                         // v
+        r,                                                           // r
+        s,                                                           // s
+        to_u256_be_bytes(num_instances),                             // length of instances
+        instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
         to_u256_be_bytes(proof.len()),                               // length of proof
         proof.iter().cloned(),                                       // proof
+    ]
+    .collect()
+}
+
+/// Decode proof given as hex string with optional `0x` prefix, e.g. from JSON APIs, into bytes to
+/// be encoded by [`encode_calldata`].
+///
+/// Prefer this over `Halo2Verifier.verifyHex`, since decoding on-chain costs considerably more gas.
+pub fn decode_hex_proof(proof: &str) -> Result<Vec<u8>, hex::FromHexError> {
+    let proof = proof.trim();
+    hex::decode(proof.strip_prefix("0x").unwrap_or(proof))
+}
+
+/// Encode proof given as hex string into calldata to invoke `Halo2Verifier.verifyHex`, which is
+/// only available when `SolidityGenerator::set_hex_proof` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_hex_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &str,
+    instances: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_HEX_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_HEX
+    };
+    let padded_proof_len = (proof.len() + 0x1f) / 0x20 * 0x20;
+    chain![
+        encode_instances_then_proof(fn_sig, vk_address, proof.as_bytes(), instances),
+        vec![0; padded_proof_len - proof.len()], // padding of proof
+    ]
+    .collect()
+}
+
+/// Encode proof and merkle proof into calldata to invoke `Halo2Verifier.verifyInAllowlist`, which
+/// is only available when `SolidityGenerator::set_allowlist_index` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_in_allowlist_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    merkle_proof: &[[u8; 32]],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_IN_ALLOWLIST_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_IN_ALLOWLIST
+    };
+    encode_instances_then_proof_then_words(fn_sig, vk_address, proof, instances, merkle_proof)
+}
+
+/// Encode proof and merkle proof into calldata to invoke `Halo2Verifier.verifyWithInputsRoot`,
+/// which is only available when `SolidityGenerator::set_inputs_root_index` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_with_inputs_root_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    merkle_proof: &[[u8; 32]],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_WITH_INPUTS_ROOT_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_WITH_INPUTS_ROOT
+    };
+    encode_instances_then_proof_then_words(fn_sig, vk_address, proof, instances, merkle_proof)
+}
+
+/// Encode proof and Poseidon preimage into calldata to invoke
+/// `Halo2Verifier.verifyWithPoseidonPreimage`, which is only available when
+/// `SolidityGenerator::set_poseidon_preimage` is set.
+///
+/// For `vk_address`, see [`encode_calldata`].
+pub fn encode_verify_with_poseidon_preimage_calldata(
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    preimage: &[bn256::Fr],
+) -> Vec<u8> {
+    let fn_sig = if vk_address.is_some() {
+        FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE_WITH_VK_ADDRESS
+    } else {
+        FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE
+    };
+    let preimage = preimage
+        .iter()
+        .map(fr_to_u256)
+        .map(to_u256_be_bytes)
+        .collect::<Vec<_>>();
+    encode_instances_then_proof_then_words(fn_sig, vk_address, proof, instances, &preimage)
+}
+
+fn encode_instances_then_proof_then_words(
+    fn_sig: [u8; 4],
+    vk_address: Option<[u8; 20]>,
+    proof: &[u8],
+    instances: &[bn256::Fr],
+    words: &[[u8; 32]],
+) -> Vec<u8> {
+    let offset = if vk_address.is_some() { 0x80 } else { 0x60 };
+    let vk_address = vk_address_word(vk_address);
+    let num_instances = instances.len();
+    let proof_offset = offset + 0x20 * (1 + num_instances);
+    chain![
+        fn_sig,                                                      // function signature
+        vk_address.into_iter().flatten(),                            // verifying key address
+        to_u256_be_bytes(offset),                                    // offset of instances
+        to_u256_be_bytes(proof_offset),                              // offset of proof
+        to_u256_be_bytes(proof_offset + 0x20 + proof.len()),         // offset of words
         to_u256_be_bytes(num_instances),                             // length of instances
         instances.iter().map(fr_to_u256).flat_map(to_u256_be_bytes), // instances
+        to_u256_be_bytes(proof.len()),                               // length of proof
+        proof.iter().cloned(),                                       // proof
+        to_u256_be_bytes(words.len()),                               // length of words
+        words.iter().flatten().cloned(),                             // words
     ]
     .collect()
 }
 
+/// Byte order of a 32 bytes encoded instance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Endianness {
+    /// Most significant byte first, which is how `Halo2Verifier` reads `uint256` instances from
+    /// calldata, and how [`encode_calldata`] encodes them.
+    #[default]
+    Big,
+    /// Least significant byte first, which is how `PrimeField::to_repr` of `bn256::Fr` encodes.
+    Little,
+}
+
+/// Encode instance into 32 bytes in given `endianness`.
+pub fn encode_instance(instance: &bn256::Fr, endianness: Endianness) -> [u8; 32] {
+    let mut bytes = to_u256_be_bytes(fr_to_u256(instance));
+    if endianness == Endianness::Little {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Decode instance from 32 bytes in given `endianness`, or return `None` if it's not canonical.
+pub fn decode_instance(bytes: &[u8; 32], endianness: Endianness) -> Option<bn256::Fr> {
+    let mut repr = *bytes;
+    if endianness == Endianness::Big {
+        repr.reverse();
+    }
+    bn256::Fr::from_repr(repr).into()
+}
+
+/// Convert instances given as 32 bytes in `endianness` into `uint256` tokens as most EVM libraries
+/// represent them, or return `None` if any of them is not canonical.
+///
+/// `Halo2Verifier` reads every instance as a big-endian `uint256`, so feeding bytes produced by
+/// `PrimeField::to_repr` (little-endian) directly fails verification silently, which this function
+/// avoids by decoding them in the given `endianness` first.
+pub fn instances_to_tokens(instances: &[[u8; 32]], endianness: Endianness) -> Option<Vec<U256>> {
+    instances
+        .iter()
+        .map(|bytes| decode_instance(bytes, endianness).map(|instance| fr_to_u256(&instance)))
+        .collect()
+}
+
+/// Reduce instance by scalar field modulus `r`.
+///
+/// `Halo2Verifier` requires every instance to be canonical (less than `r`) and rejects the proof
+/// otherwise, instead of reducing it implicitly, so an instance given in form of `r + x` should be
+/// reduced by this function before being encoded into calldata.
+pub fn reduce_instance(instance: U256) -> U256 {
+    instance % modulus::<bn256::Fr>()
+}
+
+/// Call of `Halo2Verifier.verifyProof` bundling optional verifying key address, proof and
+/// instances, which produces calldata by [`encode_calldata`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifyCall {
+    vk_address: Option<[u8; 20]>,
+    verify_fn_selector: Option<[u8; 4]>,
+    proof: Vec<u8>,
+    instances: Vec<bn256::Fr>,
+}
+
+impl VerifyCall {
+    /// Return a new `VerifyCall` to `Halo2Verifier` with verifying key embedded.
+    pub fn new(proof: Vec<u8>, instances: Vec<bn256::Fr>) -> Self {
+        Self {
+            vk_address: None,
+            verify_fn_selector: None,
+            proof,
+            instances,
+        }
+    }
+
+    /// Return a new `VerifyCall` with instances given as `U256` as most EVM libraries represent
+    /// `uint256`, or `None` if any of them is not less than scalar field modulus.
+    pub fn from_u256s(proof: Vec<u8>, instances: &[U256]) -> Option<Self> {
+        let instances = instances
+            .iter()
+            .map(|instance| decode_instance(&instance.to_be_bytes(), Endianness::Big))
+            .collect::<Option<_>>()?;
+        Some(Self::new(proof, instances))
+    }
+
+    /// Set address of `Halo2VerifyingKey` for `Halo2Verifier` rendered separately.
+    pub fn set_vk_address(mut self, vk_address: Option<[u8; 20]>) -> Self {
+        self.vk_address = vk_address;
+        self
+    }
+
+    /// Set function selector of `Halo2Verifier` rendered with
+    /// [`SolidityGenerator::set_verify_fn_name`], which is given by
+    /// [`SolidityGenerator::verify_fn_selector`]. Calldata invokes `verifyProof` if not set.
+    ///
+    /// [`SolidityGenerator::set_verify_fn_name`]: crate::SolidityGenerator::set_verify_fn_name
+    /// [`SolidityGenerator::verify_fn_selector`]: crate::SolidityGenerator::verify_fn_selector
+    pub fn set_verify_fn_selector(mut self, verify_fn_selector: Option<[u8; 4]>) -> Self {
+        self.verify_fn_selector = verify_fn_selector;
+        self
+    }
+
+    /// Return instances as `U256`.
+    pub fn instances_as_u256s(&self) -> Vec<U256> {
+        self.instances.iter().map(fr_to_u256).collect()
+    }
+
+    /// Return encoded calldata with function signature included.
+    pub fn calldata(&self) -> Vec<u8> {
+        let mut calldata = encode_calldata(self.vk_address, &self.proof, &self.instances);
+        if let Some(verify_fn_selector) = self.verify_fn_selector {
+            calldata[..4].copy_from_slice(&verify_fn_selector);
+        }
+        calldata
+    }
+}
+
 #[cfg(any(test, feature = "evm"))]
 pub(crate) mod test {
+    use crate::{
+        codegen::{util::to_u256_be_bytes, VerifierArtifact},
+        evm::encode_calldata,
+    };
+    use halo2_proofs::halo2curves::bn256;
+    use itertools::chain;
     pub use revm;
     use revm::{
-        primitives::{Address, CreateScheme, ExecutionResult, Output, TransactTo, TxEnv},
-        InMemoryDB, EVM,
+        interpreter::{CallInputs, Gas, InstructionResult},
+        primitives::{Address, Bytes, CreateScheme, ExecutionResult, Output, TransactTo, TxEnv},
+        Database, EVMData, InMemoryDB, Inspector, EVM,
     };
+    use ruint::aliases::U256;
     use std::{
-        fmt::{self, Debug, Formatter},
+        error::Error,
+        fmt::{self, Debug, Display, Formatter},
+        fs,
         io::{self, Write},
+        path::Path,
         process::{Command, Stdio},
         str,
+        time::{Duration, Instant},
     };
 
     /// Compile solidity with `--via-ir` flag, then return creation bytecode.
@@ -65,12 +863,61 @@ pub(crate) mod test {
     /// # Panics
     /// Panics if executable `solc` can not be found, or compilation fails.
     pub fn compile_solidity(solidity: impl AsRef<[u8]>) -> Vec<u8> {
+        let (stdout, stderr) = solc(&["--bin", "--optimize"], solidity.as_ref());
+        if let Some(binary) = find_binary(&stdout) {
+            binary
+        } else {
+            panic!("Compilation fails:\n{stderr}")
+        }
+    }
+
+    /// Compile solidity without optimizer for step-debugging, then return creation bytecode.
+    ///
+    /// Note that runtime bytecode of `Halo2Verifier` compiled this way usually exceeds the limit of
+    /// EIP-170, so it can only be deployed to [`Evm`] with [`Evm::set_code_size_limit`] relaxed.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn compile_solidity_unoptimized(solidity: impl AsRef<[u8]>) -> Vec<u8> {
+        let (stdout, stderr) = solc(&["--bin"], solidity.as_ref());
+        if let Some(binary) = find_binary(&stdout) {
+            binary
+        } else {
+            panic!("Compilation fails:\n{stderr}")
+        }
+    }
+
+    /// Compile standalone Yul object, e.g. rendered by [`SolidityGenerator::render_yul`], then
+    /// return creation bytecode.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    ///
+    /// [`SolidityGenerator::render_yul`]: crate::SolidityGenerator::render_yul
+    pub fn compile_yul(yul: impl AsRef<[u8]>) -> Vec<u8> {
+        let (stdout, stderr) = solc(&["--strict-assembly", "--bin", "--optimize"], yul.as_ref());
+        let binary = stdout
+            .find("Binary representation:")
+            .and_then(|start| stdout[start..].lines().nth(1))
+            .map(|binary| hex::decode(binary.trim()).unwrap());
+        if let Some(binary) = binary {
+            binary
+        } else {
+            panic!("Compilation fails:\n{stderr}")
+        }
+    }
+
+    fn find_binary(stdout: &str) -> Option<Vec<u8>> {
+        let start = stdout.find("Binary:")? + 8;
+        Some(hex::decode(&stdout[start..stdout.len() - 1]).unwrap())
+    }
+
+    fn solc(args: &[&str], solidity: &[u8]) -> (String, String) {
         let mut process = match Command::new("solc")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .arg("--bin")
-            .arg("--optimize")
+            .args(args)
             .arg("-")
             .spawn()
         {
@@ -82,27 +929,353 @@ pub(crate) mod test {
                 panic!("Failed to spwan process with command 'solc':\n{err}");
             }
         };
-        process
-            .stdin
-            .take()
-            .unwrap()
-            .write_all(solidity.as_ref())
-            .unwrap();
+        process.stdin.take().unwrap().write_all(solidity).unwrap();
         let output = process.wait_with_output().unwrap();
-        let stdout = str::from_utf8(&output.stdout).unwrap();
-        if let Some(binary) = find_binary(stdout) {
-            binary
-        } else {
-            panic!(
-                "Compilation fails:\n{}",
-                str::from_utf8(&output.stderr).unwrap()
-            )
+        let stdout = str::from_utf8(&output.stdout).unwrap().to_string();
+        let stderr = str::from_utf8(&output.stderr).unwrap().to_string();
+        (stdout, stderr)
+    }
+
+    /// Error returned by [`Evm::compile_and_deploy`] and [`estimate_verify_gas`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum DeployError {
+        /// Compilation fails with the output of `solc`.
+        Compilation(String),
+        /// Runtime bytecode exceeds the limit, which is 0x6000 by EIP-170 by default.
+        CodeSizeLimit {
+            /// Size of runtime bytecode.
+            size: usize,
+            /// Limit of runtime bytecode size.
+            limit: usize,
+        },
+        /// Deployment reverts with the revert data.
+        Revert(Vec<u8>),
+    }
+
+    impl Display for DeployError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::Compilation(stderr) => write!(f, "compilation fails:\n{stderr}"),
+                Self::CodeSizeLimit { size, limit } => write!(
+                    f,
+                    "runtime bytecode size {size} exceeds limit {limit}, consider render_separately"
+                ),
+                Self::Revert(output) => {
+                    write!(f, "deployment reverts with 0x{}", hex::encode(output))
+                }
+            }
         }
     }
 
-    fn find_binary(stdout: &str) -> Option<Vec<u8>> {
-        let start = stdout.find("Binary:")? + 8;
-        Some(hex::decode(&stdout[start..stdout.len() - 1]).unwrap())
+    impl Error for DeployError {}
+
+    /// Runtime bytecode size of `Halo2Verifier` in bytes, broken down by section.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct SizeReport {
+        /// Loading verifying key into memory, including the `bytes` constant if any.
+        pub vk: usize,
+        /// Reading proof and instances from calldata and squeezing challenges.
+        pub transcript: usize,
+        /// Computing Lagrange evaluations, instance evaluation and quotient evaluation.
+        pub quotient: usize,
+        /// Computing quotient commitment and batch opening MSM, and combining accumulator.
+        pub msm: usize,
+        /// Pairing check.
+        pub pairing: usize,
+        /// Function dispatcher, ABI decoding and anything else not in the other sections.
+        pub dispatcher: usize,
+        /// Metadata appended by `solc`.
+        pub metadata: usize,
+    }
+
+    impl SizeReport {
+        /// Return total runtime bytecode size.
+        pub fn total(&self) -> usize {
+            self.vk
+                + self.transcript
+                + self.quotient
+                + self.msm
+                + self.pairing
+                + self.dispatcher
+                + self.metadata
+        }
+
+        fn section_mut(&mut self, section: Section) -> &mut usize {
+            match section {
+                Section::Vk => &mut self.vk,
+                Section::Transcript => &mut self.transcript,
+                Section::Quotient => &mut self.quotient,
+                Section::Msm => &mut self.msm,
+                Section::Pairing => &mut self.pairing,
+                Section::Dispatcher => &mut self.dispatcher,
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    enum Section {
+        Vk,
+        Transcript,
+        Quotient,
+        Msm,
+        Pairing,
+        Dispatcher,
+    }
+
+    /// Compile rendered `Halo2Verifier` the same way as [`compile_solidity`], then attribute each
+    /// instruction of runtime bytecode to a section by source mapping.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn size_breakdown(solidity: &str) -> SizeReport {
+        // Start of each section in rendered source
+        let markers = [
+            ("bytes internal constant VK_DATA", Section::Vk),
+            ("function verifyProof(", Section::Dispatcher),
+            ("function read_ec_point(", Section::Transcript),
+            ("function batch_invert(", Section::Quotient),
+            ("function ec_add_acc(", Section::Msm),
+            ("function ec_pairing(", Section::Pairing),
+            ("// Modulus", Section::Dispatcher),
+            ("// Load vk into memory", Section::Vk),
+            ("// Copy vk from `bytes` constant", Section::Vk),
+            ("// Copy vk into memory", Section::Vk),
+            ("// Check valid length of proof", Section::Transcript),
+            ("// Revert earlier if anything", Section::Dispatcher),
+            ("// Compute lagrange evaluations", Section::Quotient),
+            ("// Compute quotient commitment", Section::Msm),
+            ("// Perform pairing\n", Section::Pairing),
+            ("// Revert if anything fails", Section::Dispatcher),
+        ];
+        let mut markers = markers
+            .into_iter()
+            .filter_map(|(marker, section)| Some((solidity.find(marker)?, section)))
+            .collect::<Vec<_>>();
+        markers.sort();
+        let section_of = |offset: i64| {
+            let idx = markers.partition_point(|(start, _)| (*start as i64) <= offset);
+            idx.checked_sub(1)
+                .map(|idx| markers[idx].1)
+                .unwrap_or(Section::Dispatcher)
+        };
+
+        let (stdout, stderr) = solc(
+            &[
+                "--combined-json",
+                "bin-runtime,srcmap-runtime",
+                "--optimize",
+            ],
+            solidity.as_bytes(),
+        );
+        let find_field = |name: &str| {
+            let contract = stdout.find("<stdin>:Halo2Verifier\"")?;
+            let start =
+                contract + stdout[contract..].find(&format!("\"{name}\":\""))? + name.len() + 4;
+            let end = start + stdout[start..].find('"')?;
+            Some(stdout[start..end].to_string())
+        };
+        let (Some(code), Some(srcmap)) = (find_field("bin-runtime"), find_field("srcmap-runtime"))
+        else {
+            panic!("Compilation fails:\n{stderr}")
+        };
+        let code = hex::decode(code).unwrap();
+
+        let mut report = SizeReport::default();
+        let mut pc = 0;
+        let (mut s, mut f) = (-1, -1);
+        for entry in srcmap.split(';') {
+            let mut fields = entry.split(':');
+            if let Some(value) = fields.next().filter(|value| !value.is_empty()) {
+                s = value.parse().unwrap();
+            }
+            fields.next();
+            if let Some(value) = fields.next().filter(|value| !value.is_empty()) {
+                f = value.parse().unwrap();
+            }
+            let size = match code[pc] {
+                opcode @ 0x60..=0x7f => 1 + (opcode - 0x5f) as usize,
+                _ => 1,
+            };
+            let section = if f == 0 {
+                section_of(s)
+            } else {
+                Section::Dispatcher
+            };
+            *report.section_mut(section) += size;
+            pc += size;
+        }
+        report.metadata = code.len() - pc;
+        report
+    }
+
+    /// Compile solidity with `--optimize-runs` set to each of `runs`, then return
+    /// `(runs, runtime_code_size, compile_time)` for each setting, to pick a trade-off between gas
+    /// cost and code size under limit of EIP-170.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation fails.
+    pub fn sweep_optimizer(solidity: &str, runs: &[usize]) -> Vec<(usize, usize, Duration)> {
+        runs.iter()
+            .map(|runs| {
+                let start = Instant::now();
+                let (stdout, stderr) = solc(
+                    &[
+                        "--bin-runtime",
+                        "--optimize",
+                        "--optimize-runs",
+                        &runs.to_string(),
+                    ],
+                    solidity.as_bytes(),
+                );
+                let compile_time = start.elapsed();
+                let runtime_code_size = stdout
+                    .find("Binary of the runtime part:")
+                    .map(|start| stdout[start..].lines().nth(1).unwrap().len() / 2)
+                    .unwrap_or_else(|| panic!("Compilation fails:\n{stderr}"));
+                (*runs, runtime_code_size, compile_time)
+            })
+            .collect()
+    }
+
+    /// Compile and deploy each case of `(name, solidity, proof, instances)` as `Halo2Verifier`
+    /// with verifying key embedded, measure gas cost of `verifyProof`, then write `name (gas: N)`
+    /// per line sorted by name into `path`, in the format of Foundry's `.gas-snapshot`, so gas
+    /// deltas show up in diff of pull requests.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, compilation or deployment fails, or any proof
+    /// is rejected.
+    pub fn write_gas_snapshot(
+        path: impl AsRef<Path>,
+        cases: &[(&str, &str, &[u8], &[bn256::Fr])],
+    ) -> io::Result<()> {
+        let mut evm = Evm::default();
+        let mut lines = cases
+            .iter()
+            .map(|(name, solidity, proof, instances)| {
+                let verifier_address = evm.create(compile_solidity(solidity));
+                let calldata = encode_calldata(None, proof, instances);
+                let (gas_cost, _) = evm.call(verifier_address, calldata);
+                format!("{name} (gas: {gas_cost})\n")
+            })
+            .collect::<Vec<_>>();
+        lines.sort();
+        fs::write(path, lines.concat())
+    }
+
+    /// Gas cost of `verifyProof`, with the part spent by elliptic curve precompiles counted
+    /// explicitly.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct GasReport {
+        /// Gas used by the transaction, including intrinsic gas and calldata.
+        pub total: u64,
+        /// Gas spent by `ecAdd` precompile.
+        pub ec_add: u64,
+        /// Gas spent by `ecMul` precompile.
+        pub ec_mul: u64,
+        /// Gas spent by `ecPairing` precompile.
+        pub ec_pairing: u64,
+    }
+
+    // Precompile gas costs by EIP-1108.
+    impl<DB: Database> Inspector<DB> for &mut GasReport {
+        fn call(
+            &mut self,
+            _: &mut EVMData<'_, DB>,
+            inputs: &mut CallInputs,
+        ) -> (InstructionResult, Gas, Bytes) {
+            let address: &[u8] = inputs.contract.as_ref();
+            if address[..19] == [0; 19] {
+                match address[19] {
+                    0x06 => self.ec_add += 150,
+                    0x07 => self.ec_mul += 6000,
+                    0x08 => self.ec_pairing += 45000 + 34000 * (inputs.input.len() as u64 / 192),
+                    _ => {}
+                }
+            }
+            (InstructionResult::Continue, Gas::new(0), Bytes::new())
+        }
+    }
+
+    /// Compile and deploy `Halo2Verifier` with verifying key embedded by
+    /// [`Evm::compile_and_deploy`], then call `verifyProof` with given `proof` and `instances` in
+    /// a local EVM, to budget verification cost without a node.
+    /// Returns gas cost with calls to `ecAdd`, `ecMul` and `ecPairing` precompiles counted.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or the proof is rejected.
+    pub fn estimate_verify_gas(
+        solidity: &str,
+        proof: &[u8],
+        instances: &[bn256::Fr],
+    ) -> Result<GasReport, DeployError> {
+        let mut evm = Evm::default();
+        let verifier_address = evm.compile_and_deploy(solidity)?;
+
+        let mut report = GasReport::default();
+        evm.evm.env.tx = TxEnv {
+            gas_limit: u64::MAX,
+            transact_to: TransactTo::Call(verifier_address),
+            data: encode_calldata(None, proof, instances).into(),
+            ..Default::default()
+        };
+        let result = evm.evm.inspect_commit(&mut report).unwrap();
+        match result {
+            ExecutionResult::Success {
+                gas_used,
+                output: Output::Call(output),
+                ..
+            } if output[..] == [vec![0; 31], vec![1]].concat() => report.total = gas_used,
+            result => panic!("Proof is rejected with {result:?}"),
+        }
+        Ok(report)
+    }
+
+    /// Deploy `Halo2Verifier` of `artifact` in a local EVM, then call `verifyProof` with given
+    /// `instances` per instance column and `proof`, to check them before paying gas with exactly
+    /// the bytecode deployed on-chain instead of a verifier reimplemented in Rust.
+    /// Returns whether the proof is accepted.
+    ///
+    /// It returns `false` without execution if `instances` or `proof` doesn't have the shape
+    /// `Halo2Verifier` expects.
+    ///
+    /// # Panics
+    /// Panics if executable `solc` can not be found, or compilation or deployment fails, e.g. the
+    /// verifier takes constructor arguments other than verifying key.
+    pub fn verify_in_rust(
+        artifact: &VerifierArtifact,
+        instances: &[Vec<bn256::Fr>],
+        proof: &[u8],
+    ) -> bool {
+        let instances = match instances {
+            [] => &[][..],
+            [instances] => instances.as_slice(),
+            _ => return false,
+        };
+        if instances.len() != artifact.num_instances || proof.len() != artifact.proof_len {
+            return false;
+        }
+
+        let constructor_args = artifact.constructor_vk.iter().flat_map(|vk| {
+            chain![
+                [0x20, vk.len()].map(U256::from),
+                vk.iter().map(|(_, word)| *word),
+            ]
+        });
+        let verifier_creation_code = chain![
+            compile_solidity(&artifact.solidity),
+            constructor_args.flat_map(to_u256_be_bytes),
+        ]
+        .collect();
+
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(verifier_creation_code);
+        let calldata = encode_calldata(None, proof, instances);
+        match evm.try_call(verifier_address, calldata) {
+            // `ReturnMode::Revert` returns nothing if proof is accepted
+            Ok((_, output)) => output.is_empty() || output == [vec![0; 31], vec![1]].concat(),
+            Err(_) => false,
+        }
     }
 
     /// Evm runner.
@@ -132,6 +1305,75 @@ pub(crate) mod test {
     }
 
     impl Evm {
+        /// Set limit of runtime bytecode size of created contract, which is 0x6000 by EIP-170 by
+        /// default.
+        pub fn set_code_size_limit(&mut self, limit: usize) {
+            self.evm.env.cfg.limit_contract_code_size = Some(limit);
+        }
+
+        /// Compile `solidity` the same way as [`compile_solidity`], check its runtime bytecode size
+        /// against the limit, then deploy it without constructor arguments.
+        /// Returns created `address`.
+        ///
+        /// # Panics
+        /// Panics if executable `solc` can not be found, or execution halts unexpectedly.
+        pub fn compile_and_deploy(
+            &mut self,
+            solidity: impl AsRef<[u8]>,
+        ) -> Result<Address, DeployError> {
+            let (stdout, stderr) =
+                solc(&["--bin", "--bin-runtime", "--optimize"], solidity.as_ref());
+            let binary = |header: &str| {
+                let start = stdout.find(header)?;
+                hex::decode(stdout[start..].lines().nth(1)?).ok()
+            };
+            let (bytecode, runtime_bytecode) =
+                match (binary("Binary:"), binary("Binary of the runtime part:")) {
+                    (Some(bytecode), Some(runtime_bytecode)) => (bytecode, runtime_bytecode),
+                    _ => return Err(DeployError::Compilation(stderr)),
+                };
+
+            let limit = self.evm.env.cfg.limit_contract_code_size.unwrap_or(0x6000);
+            if runtime_bytecode.len() > limit {
+                return Err(DeployError::CodeSizeLimit {
+                    size: runtime_bytecode.len(),
+                    limit,
+                });
+            }
+
+            let result = self.transact(TxEnv {
+                gas_limit: u64::MAX,
+                transact_to: TransactTo::Create(CreateScheme::Create),
+                data: bytecode.into(),
+                ..Default::default()
+            });
+            match result {
+                Ok((_, Output::Create(_, Some(address)))) => Ok(address),
+                Ok(_) => unreachable!(),
+                Err((_, output)) => Err(DeployError::Revert(output.into())),
+            }
+        }
+
+        /// Compile and deploy `Halo2Verifier` with verifying key embedded, then call `verifyProof`
+        /// with given `proof` and `instances`.
+        /// Returns whether the proof is accepted.
+        ///
+        /// # Panics
+        /// Panics if compilation or deployment fails.
+        pub fn deploy_and_verify(
+            &mut self,
+            solidity: impl AsRef<[u8]>,
+            proof: &[u8],
+            instances: &[bn256::Fr],
+        ) -> bool {
+            let verifier_address = self.create(compile_solidity(solidity));
+            let calldata = encode_calldata(None, proof, instances);
+            match self.try_call(verifier_address, calldata) {
+                Ok((_, output)) => output == [vec![0; 31], vec![1]].concat(),
+                Err(_) => false,
+            }
+        }
+
         /// Return code_size of given address.
         ///
         /// # Panics
@@ -145,13 +1387,36 @@ pub(crate) mod test {
                 .len()
         }
 
+        /// Return runtime bytecode of given address.
+        ///
+        /// # Panics
+        /// Panics if given address doesn't have bytecode.
+        pub fn code(&mut self, address: Address) -> Vec<u8> {
+            self.evm.db.as_ref().unwrap().accounts[&address]
+                .info
+                .code
+                .as_ref()
+                .unwrap()
+                .original_bytes()
+                .to_vec()
+        }
+
         /// Apply create transaction with given `bytecode` as creation bytecode.
         /// Return created `address`.
         ///
         /// # Panics
         /// Panics if execution reverts or halts unexpectedly.
         pub fn create(&mut self, bytecode: Vec<u8>) -> Address {
+            self.create_from(Address::default(), bytecode)
+        }
+
+        /// Same as [`Evm::create`], but with given `caller` as `msg.sender`.
+        ///
+        /// # Panics
+        /// Panics if execution reverts or halts unexpectedly.
+        pub fn create_from(&mut self, caller: Address, bytecode: Vec<u8>) -> Address {
             let (_, output) = self.transact_success_or_panic(TxEnv {
+                caller,
                 gas_limit: u64::MAX,
                 transact_to: TransactTo::Create(CreateScheme::Create),
                 data: bytecode.into(),
@@ -181,7 +1446,51 @@ pub(crate) mod test {
             }
         }
 
+        /// Apply call transaction to given `address` with `calldata`.
+        /// Returns `gas_used` and `return_data` if execution succeeds, otherwise returns
+        /// `gas_used` and `revert_data` as error.
+        ///
+        /// # Panics
+        /// Panics if execution halts unexpectedly.
+        pub fn try_call(
+            &mut self,
+            address: Address,
+            calldata: Vec<u8>,
+        ) -> Result<(u64, Vec<u8>), (u64, Vec<u8>)> {
+            self.try_call_from(Address::default(), address, calldata)
+        }
+
+        /// Same as [`Evm::try_call`], but with given `caller` as `msg.sender`.
+        ///
+        /// # Panics
+        /// Panics if execution halts unexpectedly.
+        pub fn try_call_from(
+            &mut self,
+            caller: Address,
+            address: Address,
+            calldata: Vec<u8>,
+        ) -> Result<(u64, Vec<u8>), (u64, Vec<u8>)> {
+            let result = self.transact(TxEnv {
+                caller,
+                gas_limit: u64::MAX,
+                transact_to: TransactTo::Call(address),
+                data: calldata.into(),
+                ..Default::default()
+            });
+            match result {
+                Ok((gas_used, Output::Call(output))) => Ok((gas_used, output.into())),
+                Ok(_) => unreachable!(),
+                Err((gas_used, output)) => Err((gas_used, output.into())),
+            }
+        }
+
         fn transact_success_or_panic(&mut self, tx: TxEnv) -> (u64, Output) {
+            self.transact(tx).unwrap_or_else(|(gas_used, output)| {
+                panic!("Transaction reverts with gas_used {gas_used} and output {output:#x}")
+            })
+        }
+
+        fn transact(&mut self, tx: TxEnv) -> Result<(u64, Output), (u64, Bytes)> {
             self.evm.env.tx = tx;
             let result = self.evm.transact_commit().unwrap();
             self.evm.env.tx = Default::default();
@@ -202,11 +1511,9 @@ pub(crate) mod test {
                         }
                         println!("--- end ---");
                     }
-                    (gas_used, output)
-                }
-                ExecutionResult::Revert { gas_used, output } => {
-                    panic!("Transaction reverts with gas_used {gas_used} and output {output:#x}")
+                    Ok((gas_used, output))
                 }
+                ExecutionResult::Revert { gas_used, output } => Err((gas_used, output)),
                 ExecutionResult::Halt { reason, gas_used } => panic!(
                     "Transaction halts unexpectedly with gas_used {gas_used} and reason {reason:?}"
                 ),
@@ -8,14 +8,55 @@
 
 mod codegen;
 mod evm;
+mod proof;
 mod transcript;
 
 #[cfg(test)]
 mod test;
 
-pub use codegen::{AccumulatorEncoding, BatchOpenScheme, SolidityGenerator};
-pub use evm::{encode_calldata, FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS};
-pub use transcript::Keccak256Transcript;
+pub use codegen::{
+    assert_target_supported, check_num_instances, check_yul_instructions, export_vk_json,
+    foundry_test_sol, hardhat_deploy_script, import_vk_json, patch_vk, verify_deployment,
+    AccumulatorEncoding, BatchOpenScheme, CalldataError, DecidingKeySource, GenerateError,
+    NullifierInterface, PatchVkError, PoseidonPreimage, ReturnMode, SolidityGenerator, Target,
+    VerifierArtifact, VkSource, YulError, SOLC_VERSION,
+};
+pub use evm::{
+    decode_hex_proof, decode_instance, eip712_proof_request_digest, encode_calldata,
+    encode_calldata_with_prefix, encode_deciding_key, encode_instance, encode_nullifier_calldata,
+    encode_router_calldata, encode_verify_and_get_calldata,
+    encode_verify_batch_aggregated_calldata, encode_verify_batch_calldata,
+    encode_verify_dynamic_calldata, encode_verify_hex_calldata,
+    encode_verify_in_allowlist_calldata, encode_verify_many_verbose_calldata,
+    encode_verify_memory_calldata, encode_verify_permuted_calldata, encode_verify_signed_calldata,
+    encode_verify_verbose_gas_calldata, encode_verify_with_inputs_root_calldata,
+    encode_verify_with_nonce_calldata, encode_verify_with_poseidon_preimage_calldata,
+    instances_to_tokens, reduce_instance, Endianness, VerifyCall, FN_SIG_VERIFY_AND_GET,
+    FN_SIG_VERIFY_AND_GET_WITH_VK_ADDRESS, FN_SIG_VERIFY_BATCH, FN_SIG_VERIFY_BATCH_AGGREGATED,
+    FN_SIG_VERIFY_BATCH_AGGREGATED_WITH_VK_ADDRESS, FN_SIG_VERIFY_BATCH_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_DYNAMIC, FN_SIG_VERIFY_DYNAMIC_WITH_VK_ADDRESS, FN_SIG_VERIFY_HEX,
+    FN_SIG_VERIFY_HEX_WITH_VK_ADDRESS, FN_SIG_VERIFY_IN_ALLOWLIST,
+    FN_SIG_VERIFY_IN_ALLOWLIST_WITH_VK_ADDRESS, FN_SIG_VERIFY_MANY_VERBOSE,
+    FN_SIG_VERIFY_MANY_VERBOSE_WITH_VK_ADDRESS, FN_SIG_VERIFY_MEMORY,
+    FN_SIG_VERIFY_MEMORY_WITH_VK_ADDRESS, FN_SIG_VERIFY_NULLIFIER,
+    FN_SIG_VERIFY_NULLIFIER_WITH_VK_ADDRESS, FN_SIG_VERIFY_PERMUTED,
+    FN_SIG_VERIFY_PERMUTED_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_PREFIX,
+    FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS_AND_PREFIX,
+    FN_SIG_VERIFY_ROUTED, FN_SIG_VERIFY_SIGNED, FN_SIG_VERIFY_SIGNED_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_VERBOSE_GAS, FN_SIG_VERIFY_VERBOSE_GAS_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_WITH_INPUTS_ROOT, FN_SIG_VERIFY_WITH_INPUTS_ROOT_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_WITH_NONCE, FN_SIG_VERIFY_WITH_NONCE_WITH_VK_ADDRESS,
+    FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE, FN_SIG_VERIFY_WITH_POSEIDON_PREIMAGE_WITH_VK_ADDRESS,
+};
+pub use proof::{
+    annotate_proof, diff_proof_transcripts, reorder_proof_points, validate_proof_against_schema,
+    FieldDiff, PointOrder, ProofError, ProofField, ProofFieldKind,
+};
+pub use transcript::{ChallengeReduction, Keccak256Transcript};
 
 #[cfg(feature = "evm")]
-pub use evm::test::{compile_solidity, revm, Evm};
+pub use evm::test::{
+    compile_solidity, compile_solidity_unoptimized, compile_yul, estimate_verify_gas, revm,
+    size_breakdown, sweep_optimizer, verify_in_rust, write_gas_snapshot, DeployError, Evm,
+    GasReport, SizeReport,
+};
@@ -0,0 +1,246 @@
+use halo2_proofs::halo2curves::{
+    bn256::{Fq, Fr},
+    ff::PrimeField,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
+/// Kind of field in proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofFieldKind {
+    /// EC point on BN254 encoded as coordinates `x` and `y`, each in 32 bytes big-endian.
+    Point,
+    /// Scalar of BN254 encoded in 32 bytes big-endian.
+    Scalar,
+}
+
+impl ProofFieldKind {
+    /// Return byte size of field.
+    pub fn size(&self) -> usize {
+        match self {
+            ProofFieldKind::Point => 0x40,
+            ProofFieldKind::Scalar => 0x20,
+        }
+    }
+}
+
+/// Order of coordinates of EC point in proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointOrder {
+    /// `x` followed by `y`, which is how [`Keccak256Transcript`] writes EC point.
+    ///
+    /// [`Keccak256Transcript`]: crate::Keccak256Transcript
+    #[default]
+    Xy,
+    /// `y` followed by `x`.
+    Yx,
+}
+
+/// Field in proof read by `Halo2Verifier`, see [`SolidityGenerator::proof_schema`].
+///
+/// [`SolidityGenerator::proof_schema`]: crate::SolidityGenerator::proof_schema
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofField {
+    /// Name of field.
+    pub name: String,
+    /// Kind of field.
+    pub kind: ProofFieldKind,
+    /// Byte offset of field in proof.
+    pub offset: usize,
+}
+
+impl ProofField {
+    pub(crate) fn new(name: impl Into<String>, kind: ProofFieldKind, offset: usize) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            offset,
+        }
+    }
+
+    /// Return byte range of field in proof.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.kind.size()
+    }
+}
+
+/// Field which differs between two proofs, see [`diff_proof_transcripts`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDiff {
+    /// Field which differs.
+    pub field: ProofField,
+    /// Bytes of field in the first proof, or `None` if it's too short to contain the field.
+    pub lhs: Option<Vec<u8>>,
+    /// Bytes of field in the second proof, or `None` if it's too short to contain the field.
+    pub rhs: Option<Vec<u8>>,
+}
+
+/// Align two proofs by `schema` and return fields which differ, in the order of `schema`.
+///
+/// Bytes beyond the last field of `schema` are ignored.
+pub fn diff_proof_transcripts(lhs: &[u8], rhs: &[u8], schema: &[ProofField]) -> Vec<FieldDiff> {
+    schema
+        .iter()
+        .filter_map(|field| {
+            let [lhs, rhs] = [lhs, rhs].map(|proof| proof.get(field.range()));
+            (lhs != rhs).then(|| FieldDiff {
+                field: field.clone(),
+                lhs: lhs.map(<[u8]>::to_vec),
+                rhs: rhs.map(<[u8]>::to_vec),
+            })
+        })
+        .collect()
+}
+
+/// Error found by [`validate_proof_against_schema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofError {
+    /// Proof is too short to contain the field.
+    Truncated(ProofField),
+    /// Coordinate of point field is not less than base field modulus.
+    NonCanonicalCoordinate(ProofField),
+    /// Point field is not on curve `y^2 = x^3 + 3`.
+    NotOnCurve(ProofField),
+    /// Scalar field is not less than scalar field modulus.
+    NonCanonicalScalar(ProofField),
+    /// Proof has bytes beyond the last field of schema.
+    TrailingBytes {
+        /// Expected length of proof.
+        expected: usize,
+        /// Actual length of proof.
+        actual: usize,
+    },
+}
+
+impl Display for ProofError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(field) => write!(
+                f,
+                "proof is truncated before field {} at offset {:#x}",
+                field.name, field.offset
+            ),
+            Self::NonCanonicalCoordinate(field) => write!(
+                f,
+                "coordinate of point {} at offset {:#x} is not less than base field modulus",
+                field.name, field.offset
+            ),
+            Self::NotOnCurve(field) => write!(
+                f,
+                "point {} at offset {:#x} is not on curve",
+                field.name, field.offset
+            ),
+            Self::NonCanonicalScalar(field) => write!(
+                f,
+                "scalar {} at offset {:#x} is not less than scalar field modulus",
+                field.name, field.offset
+            ),
+            Self::TrailingBytes { expected, actual } => write!(
+                f,
+                "proof has {actual} bytes but {expected} bytes are expected by schema"
+            ),
+        }
+    }
+}
+
+impl Error for ProofError {}
+
+/// Validate `proof` field by field in the order of `schema`, and return the first error found.
+///
+/// It performs the same structural checks as `Halo2Verifier` does on calldata, which is useful to
+/// reject malformed proof before submitting it on-chain.
+pub fn validate_proof_against_schema(
+    proof: &[u8],
+    schema: &[ProofField],
+) -> Result<(), ProofError> {
+    for field in schema {
+        let bytes = proof
+            .get(field.range())
+            .ok_or_else(|| ProofError::Truncated(field.clone()))?;
+        match field.kind {
+            ProofFieldKind::Point => {
+                let [x, y] = [&bytes[..0x20], &bytes[0x20..]].map(fe_from_be_bytes::<Fq>);
+                let (Some(x), Some(y)) = (x, y) else {
+                    return Err(ProofError::NonCanonicalCoordinate(field.clone()));
+                };
+                if y.square() != x.square() * x + Fq::from(3) {
+                    return Err(ProofError::NotOnCurve(field.clone()));
+                }
+            }
+            ProofFieldKind::Scalar => {
+                if fe_from_be_bytes::<Fr>(bytes).is_none() {
+                    return Err(ProofError::NonCanonicalScalar(field.clone()));
+                }
+            }
+        }
+    }
+    let expected = schema.last().map(|field| field.range().end).unwrap_or(0);
+    if proof.len() != expected {
+        return Err(ProofError::TrailingBytes {
+            expected,
+            actual: proof.len(),
+        });
+    }
+    Ok(())
+}
+
+fn fe_from_be_bytes<F: PrimeField<Repr = [u8; 0x20]>>(bytes: &[u8]) -> Option<F> {
+    let mut repr = [0; 0x20];
+    repr.copy_from_slice(bytes);
+    repr.reverse();
+    F::from_repr(repr).into()
+}
+
+/// Re-encode `proof` with coordinates of each point field of `schema` swapped if `point_order` is
+/// [`PointOrder::Yx`], to convert proof written by [`Keccak256Transcript`] for `Halo2Verifier`
+/// reading points in that order. Swapping is its own inverse, so it also converts such proof back.
+///
+/// Points are absorbed into transcript as `(x, y)` regardless of the order, so the proof doesn't
+/// need to be created again.
+///
+/// [`Keccak256Transcript`]: crate::Keccak256Transcript
+pub fn reorder_proof_points(
+    proof: &[u8],
+    schema: &[ProofField],
+    point_order: PointOrder,
+) -> Vec<u8> {
+    let mut proof = proof.to_vec();
+    if point_order == PointOrder::Yx {
+        for field in schema {
+            if field.kind == ProofFieldKind::Point {
+                if let Some(bytes) = proof.get_mut(field.range()) {
+                    bytes.rotate_left(0x20);
+                }
+            }
+        }
+    }
+    proof
+}
+
+/// Render `proof` as a hex dump labeled by `schema`, one field per line with its offset and
+/// decoded coordinates or scalar, followed by a line of total byte count.
+pub fn annotate_proof(proof: &[u8], schema: &[ProofField]) -> String {
+    let words = |bytes: &[u8]| {
+        bytes
+            .chunks(0x20)
+            .map(|word| format!("0x{}", hex::encode(word)))
+            .collect::<Vec<_>>()
+    };
+    let lines = schema.iter().map(|field| {
+        let value = match (field.kind, proof.get(field.range())) {
+            (_, None) => "<truncated>".to_string(),
+            (ProofFieldKind::Point, Some(bytes)) => {
+                let [x, y] = <[String; 2]>::try_from(words(bytes)).unwrap();
+                format!("(x: {x}, y: {y})")
+            }
+            (ProofFieldKind::Scalar, Some(bytes)) => words(bytes).remove(0),
+        };
+        format!("{:#06x} {}: {value}", field.offset, field.name)
+    });
+    let expected = schema.last().map(|field| field.range().end).unwrap_or(0);
+    let total = format!("{} bytes, {expected} bytes expected by schema", proof.len());
+    lines.chain([total]).collect::<Vec<_>>().join("\n")
+}
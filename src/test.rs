@@ -1,11 +1,49 @@
 use crate::{
-    codegen::{AccumulatorEncoding, BatchOpenScheme::Bdfg21, SolidityGenerator},
-    encode_calldata,
-    evm::test::{compile_solidity, Evm},
-    FN_SIG_VERIFY_PROOF, FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS,
+    annotate_proof, assert_target_supported, check_num_instances, check_yul_instructions,
+    codegen::{
+        util::{fq_to_u256, fr_to_u256, g1_to_u256s, to_u256_be_bytes},
+        AccumulatorEncoding,
+        BatchOpenScheme::Bdfg21,
+        CalldataError, DecidingKeySource, GenerateError, NullifierInterface, PoseidonPreimage,
+        ReturnMode, SolidityGenerator, Target, VkSource,
+    },
+    decode_hex_proof, decode_instance, diff_proof_transcripts, eip712_proof_request_digest,
+    encode_calldata, encode_calldata_with_prefix, encode_deciding_key, encode_instance,
+    encode_nullifier_calldata, encode_router_calldata, encode_verify_and_get_calldata,
+    encode_verify_batch_aggregated_calldata, encode_verify_batch_calldata,
+    encode_verify_dynamic_calldata, encode_verify_hex_calldata,
+    encode_verify_in_allowlist_calldata, encode_verify_many_verbose_calldata,
+    encode_verify_memory_calldata, encode_verify_permuted_calldata, encode_verify_signed_calldata,
+    encode_verify_verbose_gas_calldata, encode_verify_with_inputs_root_calldata,
+    encode_verify_with_nonce_calldata, encode_verify_with_poseidon_preimage_calldata,
+    evm::test::{
+        compile_solidity, compile_solidity_unoptimized, compile_yul, estimate_verify_gas,
+        size_breakdown, sweep_optimizer, verify_in_rust, write_gas_snapshot, DeployError, Evm,
+    },
+    export_vk_json, foundry_test_sol, hardhat_deploy_script, import_vk_json, instances_to_tokens,
+    patch_vk, reduce_instance, reorder_proof_points, validate_proof_against_schema,
+    verify_deployment, ChallengeReduction, Endianness, Keccak256Transcript, PatchVkError,
+    PointOrder, ProofError, ProofField, ProofFieldKind, VerifyCall, FN_SIG_VERIFY_AND_GET,
+    FN_SIG_VERIFY_AND_GET_WITH_VK_ADDRESS, FN_SIG_VERIFY_IN_ALLOWLIST,
+    FN_SIG_VERIFY_IN_ALLOWLIST_WITH_VK_ADDRESS, FN_SIG_VERIFY_NULLIFIER,
+    FN_SIG_VERIFY_NULLIFIER_WITH_VK_ADDRESS, FN_SIG_VERIFY_PERMUTED,
+    FN_SIG_VERIFY_PERMUTED_WITH_VK_ADDRESS, FN_SIG_VERIFY_PROOF,
+    FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS, FN_SIG_VERIFY_ROUTED, FN_SIG_VERIFY_SIGNED,
+    FN_SIG_VERIFY_SIGNED_WITH_VK_ADDRESS, FN_SIG_VERIFY_VERBOSE_GAS,
+    FN_SIG_VERIFY_VERBOSE_GAS_WITH_VK_ADDRESS, FN_SIG_VERIFY_WITH_NONCE,
+    FN_SIG_VERIFY_WITH_NONCE_WITH_VK_ADDRESS,
 };
-use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_proofs::{
+    halo2curves::{
+        bn256::{Bn256, Fq, Fr, G1Affine},
+        ff::Field,
+    },
+    poly::{commitment::Params, kzg::commitment::ParamsKZG},
+    transcript::{EncodedChallenge, Transcript, TranscriptRead},
+};
+use itertools::{chain, Itertools};
 use rand::{rngs::StdRng, RngCore, SeedableRng};
+use ruint::aliases::U256;
 use sha3::Digest;
 use std::{fs::File, io::Write};
 
@@ -17,6 +55,57 @@ fn function_signature() {
             "verifyProof(address,bytes,uint256[])",
             FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS,
         ),
+        (
+            "verify(uint256,uint256,uint256,bytes)",
+            FN_SIG_VERIFY_NULLIFIER,
+        ),
+        (
+            "verify(address,uint256,uint256,uint256,bytes)",
+            FN_SIG_VERIFY_NULLIFIER_WITH_VK_ADDRESS,
+        ),
+        ("verifyAndGet(uint256[],bytes)", FN_SIG_VERIFY_AND_GET),
+        (
+            "verifyAndGet(address,uint256[],bytes)",
+            FN_SIG_VERIFY_AND_GET_WITH_VK_ADDRESS,
+        ),
+        (
+            "verifyVerboseGas(uint256[],bytes)",
+            FN_SIG_VERIFY_VERBOSE_GAS,
+        ),
+        (
+            "verifyVerboseGas(address,uint256[],bytes)",
+            FN_SIG_VERIFY_VERBOSE_GAS_WITH_VK_ADDRESS,
+        ),
+        ("verify(bytes32,uint256[],bytes)", FN_SIG_VERIFY_ROUTED),
+        (
+            "verifyPermuted(uint256[],uint256[],bytes)",
+            FN_SIG_VERIFY_PERMUTED,
+        ),
+        (
+            "verifyPermuted(address,uint256[],uint256[],bytes)",
+            FN_SIG_VERIFY_PERMUTED_WITH_VK_ADDRESS,
+        ),
+        (
+            "verifySigned(address,uint256[],bytes,uint8,bytes32,bytes32)",
+            FN_SIG_VERIFY_SIGNED,
+        ),
+        (
+            "verifySigned(address,address,uint256[],bytes,uint8,bytes32,bytes32)",
+            FN_SIG_VERIFY_SIGNED_WITH_VK_ADDRESS,
+        ),
+        ("verifyWithNonce(uint256[],bytes)", FN_SIG_VERIFY_WITH_NONCE),
+        (
+            "verifyWithNonce(address,uint256[],bytes)",
+            FN_SIG_VERIFY_WITH_NONCE_WITH_VK_ADDRESS,
+        ),
+        (
+            "verifyInAllowlist(uint256[],bytes,bytes32[])",
+            FN_SIG_VERIFY_IN_ALLOWLIST,
+        ),
+        (
+            "verifyInAllowlist(address,uint256[],bytes,bytes32[])",
+            FN_SIG_VERIFY_IN_ALLOWLIST_WITH_VK_ADDRESS,
+        ),
     ] {
         assert_eq!(
             <[u8; 32]>::from(sha3::Keccak256::digest(fn_name))[..4],
@@ -35,78 +124,3173 @@ fn render_maingate() {
     run_render::<halo2::maingate::MainGateWithRange<Bn256>>()
 }
 
+#[test]
+fn render_multi_point() {
+    run_render::<halo2::multi_point::MultiPointCircuit<Bn256>>()
+}
+
+#[test]
+fn render_selector_compressed() {
+    run_render::<halo2::selector::SelectorCircuit<Bn256, true>>()
+}
+
+#[test]
+fn render_selector_uncompressed() {
+    run_render::<halo2::selector::SelectorCircuit<Bn256, false>>()
+}
+
+#[test]
+fn selector_compression_fixed_comms() {
+    let (_, compressed_vk, _, _) = halo2::create_testdata_bdfg21::<
+        halo2::selector::SelectorCircuit<Bn256, true>,
+    >(6, None, std_rng());
+    let (_, uncompressed_vk, _, _) = halo2::create_testdata_bdfg21::<
+        halo2::selector::SelectorCircuit<Bn256, false>,
+    >(6, None, std_rng());
+    assert!(compressed_vk.fixed_commitments().len() < uncompressed_vk.fixed_commitments().len());
+}
+
 #[test]
 fn render_separately_huge() {
     run_render_separately::<halo2::huge::HugeCircuit<Bn256>>()
 }
 
 #[test]
-fn render_separately_maingate() {
-    run_render_separately::<halo2::maingate::MainGateWithRange<Bn256>>()
-}
-
-fn run_render<C: halo2::TestCircuit<Fr>>() {
-    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+fn render_separately_maingate() {
+    run_render_separately::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render<C: halo2::TestCircuit<Fr>>() {
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_acc_encoding(acc_encoding);
+    let verifier_solidity = generator.render().unwrap();
+    let verifier_creation_code = compile_solidity(verifier_solidity);
+    let verifier_creation_code_size = verifier_creation_code.len();
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+    let verifier_runtime_code_size = evm.code_size(verifier_address);
+
+    println!("Verifier creation code size: {verifier_creation_code_size}");
+    println!("Verifier runtime code size: {verifier_runtime_code_size}");
+
+    let (gas_cost, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost: {gas_cost}");
+}
+
+fn run_render_separately<C: halo2::TestCircuit<Fr>>() {
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_acc_encoding(acc_encoding);
+    let (verifier_solidity, _vk_solidity) = generator.render_separately().unwrap();
+    let verifier_creation_code = compile_solidity(&verifier_solidity);
+    let verifier_creation_code_size = verifier_creation_code.len();
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+    let verifier_runtime_code_size = evm.code_size(verifier_address);
+
+    println!("Verifier creation code size: {verifier_creation_code_size}");
+    println!("Verifier runtime code size: {verifier_runtime_code_size}");
+
+    let deployed_verifier_solidity = verifier_solidity;
+
+    for k in C::min_k()..C::min_k() + 4 {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata_bdfg21::<C>(k, acc_encoding, std_rng());
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_acc_encoding(acc_encoding);
+
+        let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+        assert_eq!(deployed_verifier_solidity, verifier_solidity);
+
+        let vk_creation_code = compile_solidity(&vk_solidity);
+        let vk_address = evm.create(vk_creation_code);
+
+        let (gas_cost, output) = evm.call(
+            verifier_address,
+            encode_calldata(Some(vk_address.into()), &proof, &instances),
+        );
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+        println!("Gas cost: {gas_cost}");
+    }
+}
+
+#[test]
+fn render_vk_bytes_constant_huge() {
+    run_render_vk_bytes_constant::<halo2::huge::HugeCircuit<Bn256>>()
+}
+
+fn run_render_vk_bytes_constant<C: halo2::TestCircuit<Fr>>() {
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+
+    let mut evm = Evm::default();
+    let [inline_runtime_code_size, bytes_constant_runtime_code_size] =
+        [VkSource::Inline, VkSource::BytesConstant].map(|vk_source| {
+            let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+                .set_acc_encoding(acc_encoding)
+                .set_vk_source(vk_source);
+            let verifier_creation_code = compile_solidity(generator.render().unwrap());
+            let verifier_address = evm.create(verifier_creation_code);
+
+            let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+            evm.code_size(verifier_address)
+        });
+
+    println!("Verifier runtime code size with inline vk: {inline_runtime_code_size}");
+    println!(
+        "Verifier runtime code size with vk in bytes constant: {bytes_constant_runtime_code_size}"
+    );
+    assert!(bytes_constant_runtime_code_size < inline_runtime_code_size);
+}
+
+#[test]
+fn acc_encoding_limbs_huge() {
+    run_acc_encoding_limbs::<halo2::huge::HugeCircuit<Bn256>>()
+}
+
+fn run_acc_encoding_limbs<C: halo2::TestCircuit<Fr>>() {
+    let mut evm = Evm::default();
+    // Layouts of non-native base field element in aggregation circuits, e.g. 3 limbs of 88 bits
+    // is what `snark-verifier` uses for BN254, with accumulator after other instances or not
+    for acc_encoding in [
+        AccumulatorEncoding::new(0, 3, 88),
+        AccumulatorEncoding::new(2, 3, 88),
+        AccumulatorEncoding::new(3, 4, 68),
+        AccumulatorEncoding::new(0, 2, 127),
+    ] {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata_bdfg21::<C>(C::min_k(), Some(acc_encoding), std_rng());
+        assert_eq!(
+            instances.len(),
+            acc_encoding.offset + 4 * acc_encoding.num_limbs
+        );
+
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_acc_encoding(Some(acc_encoding));
+        let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+        let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    }
+}
+
+#[test]
+fn acc_encoding_location_huge() {
+    run_acc_encoding_location::<halo2::huge::HugeCircuit<Bn256>>()
+}
+
+fn run_acc_encoding_location<C: halo2::TestCircuit<Fr>>() {
+    let acc_encoding = AccumulatorEncoding::new(0, 4, 68);
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), Some(acc_encoding), std_rng());
+
+    let mut evm = Evm::default();
+    for (acc_encoding, expected) in [
+        (acc_encoding, true),
+        (AccumulatorEncoding::new(4, 4, 68), false),
+        (AccumulatorEncoding::new(0, 4, 64), false),
+    ] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_acc_encoding(Some(acc_encoding));
+        let verifier_creation_code = compile_solidity(generator.render().unwrap());
+        let verifier_address = evm.create(verifier_creation_code);
+
+        let calldata = encode_calldata(None, &proof, &instances);
+        assert_eq!(evm.try_call(verifier_address, calldata).is_ok(), expected);
+    }
+}
+
+#[test]
+fn non_canonical_point_check_maingate() {
+    run_non_canonical_point_check::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_non_canonical_point_check<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_NON_CANONICAL_POINT: [u8; 4] = [0xf9, 0x22, 0x56, 0x64];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let mut non_canonical_proof = proof.clone();
+    let x = U256::from_be_slice(&proof[..0x20]) + fq_to_u256(-Fq::ONE) + U256::from(1);
+    non_canonical_proof[..0x20].copy_from_slice(&x.to_be_bytes::<0x20>());
+
+    let mut evm = Evm::default();
+    for non_canonical_point_check in [false, true] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_non_canonical_point_check(non_canonical_point_check);
+        let verifier_creation_code = compile_solidity(generator.render().unwrap());
+        let verifier_address = evm.create(verifier_creation_code);
+
+        let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+        let calldata = encode_calldata(None, &non_canonical_proof, &instances);
+        let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+        if non_canonical_point_check {
+            assert_eq!(output, ERROR_NON_CANONICAL_POINT);
+        } else {
+            assert!(output.is_empty());
+        }
+    }
+}
+
+#[test]
+fn pairing_failure_context_maingate() {
+    run_pairing_failure_context::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_pairing_failure_context<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_PAIRING_FAILED: [u8; 4] = [0x2d, 0xf3, 0x66, 0x91];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    assert_eq!(vk.cs().num_challenges(), 0);
+
+    // Replay transcript natively until `theta`
+    let theta = {
+        let mut transcript = Keccak256Transcript::<G1Affine, _>::new(proof.as_slice());
+        vk.hash_into(&mut transcript).unwrap();
+        for instance in instances.iter() {
+            transcript.common_scalar(*instance).unwrap();
+        }
+        for _ in 0..vk.cs().num_advice_columns() {
+            transcript.read_point().unwrap();
+        }
+        transcript.squeeze_challenge().get_scalar()
+    };
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_pairing_failure_context(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    // Corrupt an evaluation, which is only caught by pairing
+    let eval = generator
+        .proof_schema()
+        .into_iter()
+        .find(|field| field.kind == ProofFieldKind::Scalar)
+        .unwrap();
+    let mut bad_proof = proof.clone();
+    bad_proof[eval.range().end - 1] ^= 1;
+    let calldata = encode_calldata(None, &bad_proof, &instances);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(
+        output,
+        chain![ERROR_PAIRING_FAILED, to_u256_be_bytes(fr_to_u256(theta))].collect_vec()
+    );
+}
+
+#[test]
+fn debug_errors_maingate() {
+    run_debug_errors::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_debug_errors<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_TRANSCRIPT_MISMATCH: [u8; 4] = [0xed, 0x54, 0xd1, 0xc9];
+    const ERROR_PAIRING_FAILED: [u8; 4] = [0x2d, 0xf3, 0x66, 0x91];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_debug_errors(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    // Move a point off curve, which is caught when reading transcript
+    let schema = generator.proof_schema();
+    let point = schema
+        .iter()
+        .find(|field| field.kind == ProofFieldKind::Point)
+        .unwrap();
+    let mut bad_proof = proof.clone();
+    bad_proof[point.range().end - 1] ^= 1;
+    let calldata = encode_calldata(None, &bad_proof, &instances);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_TRANSCRIPT_MISMATCH);
+
+    // Change an instance, which only changes challenges and is caught by pairing
+    let mut bad_instances = instances.clone();
+    bad_instances[0] += Fr::ONE;
+    let calldata = encode_calldata(None, &proof, &bad_instances);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output[..4], ERROR_PAIRING_FAILED);
+}
+
+#[test]
+fn challenge_reduction_maingate() {
+    run_challenge_reduction::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_challenge_reduction<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let proof = halo2::create_proof_bdfg21_with_challenge_reduction(
+        &params,
+        &vk,
+        C::new(None, std_rng()),
+        ChallengeReduction::Wide,
+        std_rng(),
+    );
+
+    let mut evm = Evm::default();
+    for (challenge_reduction, expected) in [
+        (ChallengeReduction::Mod, false),
+        (ChallengeReduction::Wide, true),
+    ] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_challenge_reduction(challenge_reduction);
+        let verifier_creation_code = compile_solidity(generator.render().unwrap());
+        let verifier_address = evm.create(verifier_creation_code);
+
+        let calldata = encode_calldata(None, &proof, &instances);
+        assert_eq!(evm.try_call(verifier_address, calldata).is_ok(), expected);
+    }
+}
+
+#[test]
+fn quotient_chunks_huge() {
+    run_quotient_chunks::<halo2::huge::HugeCircuit<Bn256>>()
+}
+
+#[test]
+fn quotient_chunks_maingate() {
+    run_quotient_chunks::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_quotient_chunks<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let num_quotients = generator
+        .proof_schema()
+        .iter()
+        .filter(|field| field.name.starts_with("quotient_comms"))
+        .count();
+    assert_eq!(num_quotients, vk.cs().degree() - 1);
+    assert!(num_quotients > 1);
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(generator.render().unwrap(), &proof, &instances));
+}
+
+#[test]
+fn diff_proof_transcripts_maingate() {
+    run_diff_proof_transcripts::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_diff_proof_transcripts<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let another_proof = halo2::create_proof_bdfg21(
+        &params,
+        &vk,
+        C::new(None, std_rng()),
+        StdRng::seed_from_u64(1),
+    );
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let schema = generator.proof_schema();
+    assert_eq!(schema.last().unwrap().range().end, proof.len());
+
+    assert!(diff_proof_transcripts(&proof, &proof, &schema).is_empty());
+
+    // Only randomness differs, so both commitments and evaluations differ
+    let diffs = diff_proof_transcripts(&proof, &another_proof, &schema);
+    assert!(diffs
+        .iter()
+        .any(|diff| diff.field.kind == ProofFieldKind::Point));
+    assert!(diffs
+        .iter()
+        .any(|diff| diff.field.kind == ProofFieldKind::Scalar));
+    assert!(diffs
+        .iter()
+        .all(|diff| diff.lhs.is_some() && diff.rhs.is_some()));
+
+    let truncated = &proof[..proof.len() - 0x20];
+    let diffs = diff_proof_transcripts(&proof, truncated, &schema);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].field.name, "w_prime");
+    assert_eq!(diffs[0].rhs, None);
+}
+
+#[test]
+fn validate_proof_against_schema_maingate() {
+    run_validate_proof_against_schema::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_validate_proof_against_schema<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let schema = generator.proof_schema();
+    assert_eq!(validate_proof_against_schema(&proof, &schema), Ok(()));
+
+    let point = schema
+        .iter()
+        .find(|field| field.kind == ProofFieldKind::Point)
+        .unwrap();
+    let scalar = schema
+        .iter()
+        .find(|field| field.kind == ProofFieldKind::Scalar)
+        .unwrap();
+    let proof_with = |field: &ProofField, offset: usize, value: U256| {
+        let mut proof = proof.clone();
+        let offset = field.offset + offset;
+        proof[offset..offset + 0x20].copy_from_slice(&value.to_be_bytes::<0x20>());
+        proof
+    };
+
+    let off_curve = proof_with(point, 0x20, U256::from(1));
+    assert_eq!(
+        validate_proof_against_schema(&off_curve, &schema),
+        Err(ProofError::NotOnCurve(point.clone()))
+    );
+
+    let out_of_range = proof_with(scalar, 0, fr_to_u256(-Fr::ONE) + U256::from(1));
+    assert_eq!(
+        validate_proof_against_schema(&out_of_range, &schema),
+        Err(ProofError::NonCanonicalScalar(scalar.clone()))
+    );
+
+    assert_eq!(
+        validate_proof_against_schema(&proof[..proof.len() - 0x20], &schema),
+        Err(ProofError::Truncated(schema.last().unwrap().clone()))
+    );
+
+    let trailing = [proof.as_slice(), &[0; 0x20]].concat();
+    assert_eq!(
+        validate_proof_against_schema(&trailing, &schema)
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "proof has {} bytes but {} bytes are expected by schema",
+            proof.len() + 0x20,
+            proof.len()
+        )
+    );
+}
+
+#[test]
+fn annotate_proof_maingate() {
+    run_annotate_proof::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_annotate_proof<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let schema = generator.proof_schema();
+    let annotated = annotate_proof(&proof, &schema);
+    let lines = annotated.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), schema.len() + 1);
+
+    for (line, field) in lines.iter().zip(schema.iter()) {
+        let words = proof[field.range()]
+            .chunks(0x20)
+            .map(|word| format!("0x{}", hex::encode(word)))
+            .collect::<Vec<_>>();
+        let expected = match field.kind {
+            ProofFieldKind::Point => format!("(x: {}, y: {})", words[0], words[1]),
+            ProofFieldKind::Scalar => words[0].clone(),
+        };
+        assert_eq!(
+            *line,
+            format!("{:#06x} {}: {expected}", field.offset, field.name)
+        );
+    }
+    assert_eq!(
+        *lines.last().unwrap(),
+        format!("{0} bytes, {0} bytes expected by schema", proof.len())
+    );
+}
+
+#[test]
+fn render_deciding_key_from_constructor_maingate() {
+    run_render_deciding_key_from_constructor::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_deciding_key_from_constructor<C: halo2::TestCircuit<Fr>>() {
+    let mut evm = Evm::default();
+    let mut deployed_verifier_solidity = None;
+
+    // Different seeds lead to different ceremonies
+    for seed in 0..2 {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata_bdfg21::<C>(C::min_k(), None, StdRng::seed_from_u64(seed));
+
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_deciding_key_source(DecidingKeySource::Constructor);
+        let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+        let deployed_verifier_solidity =
+            deployed_verifier_solidity.get_or_insert_with(|| verifier_solidity.clone());
+        assert_eq!(deployed_verifier_solidity, &verifier_solidity);
+
+        let deciding_key = encode_deciding_key(&params.g2(), &params.s_g2());
+        let verifier_creation_code = [compile_solidity(&verifier_solidity), deciding_key].concat();
+        let verifier_address = evm.create(verifier_creation_code);
+        let vk_address = evm.create(compile_solidity(&vk_solidity));
+
+        let (_, output) = evm.call(
+            verifier_address,
+            encode_calldata(Some(vk_address.into()), &proof, &instances),
+        );
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    }
+}
+
+#[test]
+fn render_vk_from_constructor_maingate() {
+    run_render_vk_from_constructor::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_vk_from_constructor<C: halo2::TestCircuit<Fr>>() {
+    let mut evm = Evm::default();
+    let mut deployed_verifier_solidity = None;
+
+    // Different seeds lead to different ceremonies
+    for seed in 0..2 {
+        let (params, vk, instances, proof) =
+            halo2::create_testdata_bdfg21::<C>(C::min_k(), None, StdRng::seed_from_u64(seed));
+
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_vk_source(VkSource::Constructor);
+        let artifact = generator.render_artifact().unwrap();
+        let deployed_verifier_solidity =
+            deployed_verifier_solidity.get_or_insert_with(|| artifact.solidity.clone());
+        assert_eq!(deployed_verifier_solidity, &artifact.solidity);
+
+        let constructor_vk = artifact.constructor_vk.unwrap();
+        assert_eq!(constructor_vk[0].0, "vk_digest");
+        let verifier_creation_code = [
+            compile_solidity(&artifact.solidity),
+            chain![
+                [U256::from(0x20), U256::from(constructor_vk.len())],
+                constructor_vk.into_iter().map(|(_, word)| word)
+            ]
+            .flat_map(to_u256_be_bytes)
+            .collect(),
+        ]
+        .concat();
+        let verifier_address = evm.create(verifier_creation_code);
+
+        let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    }
+}
+
+#[test]
+fn reduce_instance_maingate() {
+    run_reduce_instance::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_reduce_instance<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    let instance_offset = calldata.len() - 0x20 * instances.len();
+    let calldata_with_first_instance = |instance: U256| {
+        let mut calldata = calldata.clone();
+        calldata[instance_offset..instance_offset + 0x20]
+            .copy_from_slice(&instance.to_be_bytes::<0x20>());
+        calldata
+    };
+
+    let instance = U256::from_be_slice(&calldata[instance_offset..instance_offset + 0x20]);
+    let non_canonical_instance = instance + fr_to_u256(-Fr::ONE) + U256::from(1);
+    assert_eq!(reduce_instance(non_canonical_instance), instance);
+
+    let result = evm.try_call(
+        verifier_address,
+        calldata_with_first_instance(non_canonical_instance),
+    );
+    assert!(result.is_err());
+
+    let (_, output) = evm.call(
+        verifier_address,
+        calldata_with_first_instance(reduce_instance(non_canonical_instance)),
+    );
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn calldata_bounds_check_maingate() {
+    run_calldata_bounds_check::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_calldata_bounds_check<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_calldata_bounds_check(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    // Point instances to a well-formed copy appended in the end, so ABI decoding still succeeds,
+    // then claim a huge length at the fixed offset verifier reads from.
+    let num_instances_offset = calldata.len() - 0x20 * (instances.len() + 1);
+    let calldata = {
+        let appended_offset = calldata.len() - 4;
+        let mut calldata =
+            chain![calldata.clone(), calldata[num_instances_offset..].to_vec()].collect::<Vec<_>>();
+        calldata[0x24..0x44].copy_from_slice(&to_u256_be_bytes(appended_offset));
+        calldata[num_instances_offset..num_instances_offset + 0x20]
+            .copy_from_slice(&(U256::from(1) << 64).to_be_bytes::<0x20>());
+        calldata
+    };
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(
+        output,
+        <[u8; 32]>::from(sha3::Keccak256::digest("CalldataOutOfBounds()"))[..4],
+    );
+}
+
+#[test]
+fn verify_and_get_maingate() {
+    run_verify_and_get::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_and_get<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let result_index = instances.len() - 1;
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_result_index(Some(result_index));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_verify_and_get_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(
+        output,
+        to_u256_be_bytes(fr_to_u256(&instances[result_index]))
+    );
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let calldata = encode_verify_and_get_calldata(None, &bad_proof, &instances);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+}
+
+#[test]
+fn verify_verbose_gas_maingate() {
+    run_verify_verbose_gas::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_verbose_gas<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_verbose_gas(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    for proof in [proof, bad_proof] {
+        let ok = evm
+            .try_call(verifier_address, encode_calldata(None, &proof, &instances))
+            .is_ok();
+        let calldata = encode_verify_verbose_gas_calldata(None, &proof, &instances);
+        let (_, output) = evm.call(verifier_address, calldata);
+        assert_eq!(output[..0x20], to_u256_be_bytes(U256::from(ok as u8)));
+        assert!(U256::from_be_slice(&output[0x20..0x40]) > U256::ZERO);
+    }
+}
+
+#[test]
+fn verify_many_verbose_maingate() {
+    run_verify_many_verbose::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_many_verbose<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_verbose_gas(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let mut bad_instances = instances.clone();
+    bad_instances[0] += Fr::ONE;
+    let cases = [
+        (proof.clone(), instances.clone()),
+        (bad_proof, instances.clone()),
+        (proof, bad_instances),
+    ];
+
+    let (proofs, instances): (Vec<_>, Vec<_>) = cases.iter().cloned().unzip();
+    let calldata = encode_verify_many_verbose_calldata(None, &proofs, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    let word = |idx: usize| U256::from_be_slice(&output[idx * 0x20..(idx + 1) * 0x20]);
+    let array = |offset: U256| {
+        let idx = offset.to::<usize>() / 0x20;
+        (0..word(idx).to::<usize>())
+            .map(|i| word(idx + 1 + i))
+            .collect::<Vec<_>>()
+    };
+    let (oks, gas_each) = (array(word(0)), array(word(1)));
+    assert_eq!(oks.len(), cases.len());
+    assert_eq!(gas_each.len(), cases.len());
+
+    for ((proof, instances), (ok, gas)) in cases.iter().zip(oks.into_iter().zip(gas_each)) {
+        let calldata = encode_verify_verbose_gas_calldata(None, proof, instances);
+        let (_, output) = evm.call(verifier_address, calldata);
+        assert_eq!(ok, U256::from_be_slice(&output[..0x20]));
+        // Allow small difference from memory expansion of caller.
+        let expected_gas = U256::from_be_slice(&output[0x20..0x40]);
+        assert!(gas.abs_diff(expected_gas) * U256::from(100) < expected_gas);
+    }
+    assert_eq!(
+        cases
+            .iter()
+            .map(|(proof, instances)| evm
+                .try_call(verifier_address, encode_calldata(None, proof, instances))
+                .is_ok())
+            .collect::<Vec<_>>(),
+        [true, false, false]
+    );
+}
+
+#[test]
+fn verify_memory_maingate() {
+    run_verify_memory::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_memory<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_memory_proof(true)
+        .set_dynamic_instances(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_verify_memory_calldata(None, &proof, &instances);
+    let (gas_cost, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost of verifying proof in memory: {gas_cost}");
+
+    // Dynamic instances entrypoint of `verify(uint256[],bytes)` coexists
+    let calldata = encode_verify_dynamic_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let calldata = encode_verify_memory_calldata(None, &bad_proof, &instances);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+
+    let calldata = encode_verify_memory_calldata(None, &proof[..proof.len() - 0x20], &instances);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+
+    let calldata = encode_verify_memory_calldata(None, &proof, &instances[1..]);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+}
+
+#[test]
+fn verify_batch_maingate() {
+    run_verify_batch::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_batch<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_batch_verify(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let proofs = vec![proof.clone(); 3];
+    let instances_list = vec![instances.clone(); 3];
+    let calldata = encode_verify_batch_calldata(None, &proofs, &instances_list);
+    let (batch_gas_cost, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let (gas_cost, _) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert!(batch_gas_cost < 3 * gas_cost);
+    println!("Gas cost of verifying batch of 3 proofs: {batch_gas_cost}");
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let proofs = [proof.clone(), bad_proof, proof];
+    let calldata = encode_verify_batch_calldata(None, &proofs, &instances_list);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [0; 32]);
+}
+
+#[test]
+fn return_mode_revert_entrypoints_maingate() {
+    run_return_mode_revert_entrypoints::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_return_mode_revert_entrypoints<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_return_mode(ReturnMode::Revert)
+        .set_result_index(Some(0))
+        .set_batch_verify(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+
+    let calldata = encode_verify_and_get_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, to_u256_be_bytes(fr_to_u256(instances[0])));
+    let calldata = encode_verify_and_get_calldata(None, &bad_proof, &instances);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+
+    let instances_list = vec![instances.clone(); 2];
+    let calldata =
+        encode_verify_batch_calldata(None, &[proof.clone(), proof.clone()], &instances_list);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    let calldata = encode_verify_batch_calldata(None, &[proof, bad_proof], &instances_list);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [0; 32]);
+}
+
+#[test]
+fn verify_batch_aggregated_maingate() {
+    run_verify_batch_aggregated::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_batch_aggregated<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_batch_verify(true)
+        .set_batch_aggregate(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let proofs = vec![proof.clone(); 3];
+    let instances_list = vec![instances.clone(); 3];
+    let calldata = encode_verify_batch_aggregated_calldata(None, &proofs, &instances_list);
+    let (aggregated_gas_cost, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let calldata = encode_verify_batch_calldata(None, &proofs, &instances_list);
+    let (batch_gas_cost, _) = evm.call(verifier_address, calldata);
+    assert!(aggregated_gas_cost < batch_gas_cost);
+    println!("Gas cost of verifying aggregated batch of 3 proofs: {aggregated_gas_cost}");
+
+    // Pairing input is only returned to `Halo2Verifier` itself
+    let calldata = [encode_calldata(None, &proof, &instances), vec![0; 32]].concat();
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let proofs = [proof.clone(), proof, bad_proof];
+    let calldata = encode_verify_batch_aggregated_calldata(None, &proofs, &instances_list);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [0; 32]);
+}
+
+#[test]
+fn dynamic_instances_maingate() {
+    run_dynamic_instances::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_dynamic_instances<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_dynamic_instances(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_verify_dynamic_calldata(None, &proof, &instances);
+    let (gas_cost, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    println!("Gas cost of verifying proof with dynamic instances: {gas_cost}");
+
+    let calldata = encode_verify_dynamic_calldata(None, &proof, &instances[1..]);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+}
+
+#[test]
+fn instance_bit_widths_maingate() {
+    run_instance_bit_widths::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+#[test]
+fn instance_bit_widths_invalid_maingate() {
+    run_instance_bit_widths_invalid::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_instance_bit_widths_invalid<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_instance_bit_widths(vec![(0, 254)]);
+    assert_eq!(
+        generator.render(),
+        Err(GenerateError::InvalidOption {
+            option: "instance_bit_widths",
+            reason: "bit width should be in range 1..254 but got 254".to_string(),
+        })
+    );
+}
+
+fn run_instance_bit_widths<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INSTANCE_OUT_OF_RANGE: [u8; 4] = [0x44, 0xeb, 0xe2, 0x7f];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let index = instances.len() - 1;
+    let bit_width = fr_to_u256(instances[index]).bit_len().max(2);
+
+    let mut evm = Evm::default();
+    for (bit_width, in_range) in [(bit_width, true), (bit_width - 1, false)] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_instance_bit_widths(vec![(index, bit_width)]);
+        let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+        let calldata = encode_calldata(None, &proof, &instances);
+        if in_range {
+            let (_, output) = evm.call(verifier_address, calldata);
+            assert_eq!(output, [vec![0; 31], vec![1]].concat());
+        } else {
+            let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+            assert_eq!(
+                output,
+                [
+                    ERROR_INSTANCE_OUT_OF_RANGE.to_vec(),
+                    to_u256_be_bytes(index).to_vec()
+                ]
+                .concat()
+            );
+        }
+    }
+}
+
+#[test]
+fn verify_hex_maingate() {
+    run_verify_hex::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_hex<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INVALID_HEX: [u8; 4] = [0xcb, 0xbc, 0x48, 0xa0];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let proof_hex = format!("0x{}", hex::encode(&proof));
+    assert_eq!(decode_hex_proof(&proof_hex).unwrap(), proof);
+    assert_eq!(decode_hex_proof(&proof_hex[2..]).unwrap(), proof);
+    assert!(decode_hex_proof(&proof_hex[..proof_hex.len() - 1]).is_err());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_hex_proof(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &decode_hex_proof(&proof_hex).unwrap(), &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    for proof_hex in [
+        &proof_hex[..],
+        &proof_hex[2..],
+        &proof_hex.to_uppercase()[2..],
+    ] {
+        let calldata = encode_verify_hex_calldata(None, proof_hex, &instances);
+        let (gas_cost, output) = evm.call(verifier_address, calldata);
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+        println!("Gas cost of verifying hex proof: {gas_cost}");
+    }
+
+    let mut bad_proof_hex = proof_hex.clone();
+    bad_proof_hex.replace_range(2..3, "g");
+    let calldata = encode_verify_hex_calldata(None, &bad_proof_hex, &instances);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_HEX);
+}
+
+#[test]
+fn scaffold_project_maingate() {
+    run_scaffold_project::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_scaffold_project<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let out_dir = std::env::temp_dir().join("halo2_solidity_verifier_scaffold");
+    generator
+        .scaffold_project(&out_dir, &proof, &instances)
+        .unwrap();
+
+    let verifier_solidity = std::fs::read_to_string(out_dir.join("src/Verifier.sol")).unwrap();
+    assert_eq!(verifier_solidity, generator.render().unwrap());
+    for path in ["foundry.toml", "src/IVerifier.sol", "test/Verifier.t.sol"] {
+        assert!(out_dir.join(path).exists());
+    }
+
+    #[cfg(feature = "forge")]
+    for args in [["build"], ["test"]] {
+        let status = std::process::Command::new("forge")
+            .args(args)
+            .current_dir(&out_dir)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+}
+
+#[test]
+fn foundry_test_sol_maingate() {
+    run_foundry_test_sol::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_foundry_test_sol<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    for vk_source in [VkSource::Inline, VkSource::Constructor] {
+        let generator =
+            SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_vk_source(vk_source);
+        let artifact = generator.render_artifact().unwrap();
+        let test = foundry_test_sol(&artifact, &instances, &proof).unwrap();
+        assert!(test.contains(&hex::encode(&proof)));
+        assert!(test.contains("values[0] ^= 1;"));
+        assert!(test.contains(r#""verifyProof(bytes,uint256[])""#));
+        assert_eq!(
+            test.contains("new Halo2Verifier(vk)"),
+            vk_source == VkSource::Constructor
+        );
+
+        #[cfg(feature = "forge")]
+        {
+            let out_dir = std::env::temp_dir().join("halo2_solidity_verifier_foundry_test");
+            for (path, content) in [
+                ("src/Verifier.sol", artifact.solidity.clone()),
+                ("test/Verifier.t.sol", test),
+            ] {
+                let path = out_dir.join(path);
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(path, content).unwrap();
+            }
+            let status = std::process::Command::new("forge")
+                .arg("test")
+                .current_dir(&out_dir)
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+    }
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_verify_fn_name("verify")
+        .set_transcript_prefix(true);
+    let artifact = generator.render_artifact().unwrap();
+    let test = foundry_test_sol(&artifact, &instances, &proof).unwrap();
+    assert!(test.contains(r#""verify(bytes,uint256[],bytes)""#));
+    assert!(test.contains(r#"bytes("")"#));
+}
+
+#[test]
+fn render_hardhat_deploy_script_maingate() {
+    run_render_hardhat_deploy_script::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_hardhat_deploy_script<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let script = hardhat_deploy_script(&generator.render_artifact().unwrap()).unwrap();
+    assert!(script.contains(r#"ethers.deployContract("Halo2Verifier")"#));
+    assert!(script.contains("verifier.verifyProof.staticCall(\n    proof,\n    instances,\n  );"));
+    assert!(script.contains("proof.json") && script.contains("instances.json"));
+    assert!(!script.contains("PREFIX"));
+
+    let generator = generator
+        .set_contract_name("Verifier")
+        .set_verify_fn_name("verify")
+        .set_transcript_prefix(true);
+    let script = hardhat_deploy_script(&generator.render_artifact().unwrap()).unwrap();
+    assert!(script.contains(r#"ethers.deployContract("Verifier")"#));
+    assert!(script
+        .contains("verifier.verify.staticCall(\n    proof,\n    instances,\n    PREFIX,\n  );"));
+}
+
+#[test]
+#[should_panic(expected = "Deploy script with verifier taking constructor arguments")]
+fn render_hardhat_deploy_script_constructor_vk_maingate() {
+    run_render_hardhat_deploy_script_constructor_vk::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_hardhat_deploy_script_constructor_vk<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let artifact = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_vk_source(VkSource::Constructor)
+        .render_artifact()
+        .unwrap();
+    hardhat_deploy_script(&artifact).unwrap();
+}
+
+#[test]
+fn render_cached_maingate() {
+    run_render_cached::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_cached<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let cache_dir = std::env::temp_dir().join("halo2_solidity_verifier_cache");
+    let _ = std::fs::remove_dir_all(&cache_dir);
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    assert_eq!(
+        generator.render_cached(&cache_dir).unwrap(),
+        generator.render().unwrap()
+    );
+    let entries = std::fs::read_dir(&cache_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect_vec();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].extension().unwrap(), "sol");
+
+    // Concurrent renders leave a single complete entry without temporary files
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                assert_eq!(
+                    generator.render_cached(&cache_dir).unwrap(),
+                    generator.render().unwrap()
+                );
+            });
+        }
+    });
+    assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 1);
+
+    // Cached entry is returned without rendering
+    std::fs::write(&entries[0], "cached").unwrap();
+    assert_eq!(generator.render_cached(&cache_dir).unwrap(), "cached");
+
+    // Any option change misses the cache
+    let generator = generator.set_return_mode(ReturnMode::Revert);
+    assert_eq!(
+        generator.render_cached(&cache_dir).unwrap(),
+        generator.render().unwrap()
+    );
+    assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 2);
+}
+
+#[test]
+fn verify_permuted_maingate() {
+    run_verify_permuted::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_permuted<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INVALID_PERMUTATION: [u8; 4] = [0x6d, 0x28, 0x16, 0x71];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_instance_permutation(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let permutation = (0..instances.len()).rev().collect_vec();
+    let permuted_instances = permutation.iter().map(|idx| instances[*idx]).collect_vec();
+    let calldata = encode_verify_permuted_calldata(None, &proof, &permuted_instances, &permutation);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let mut duplicate = permutation.clone();
+    duplicate[1] = duplicate[0];
+    let calldata = encode_verify_permuted_calldata(None, &proof, &permuted_instances, &duplicate);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_PERMUTATION);
+}
+
+#[test]
+fn sweep_optimizer_maingate() {
+    run_sweep_optimizer::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_sweep_optimizer<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+    let sweep = sweep_optimizer(&verifier_solidity, &[1, 200, 10000]);
+
+    for (runs, runtime_code_size, compile_time) in &sweep {
+        println!(
+            "Runs {runs}: runtime code size {runtime_code_size}, compile time {compile_time:?}"
+        );
+    }
+    assert_eq!(
+        sweep.iter().map(|(runs, ..)| *runs).collect_vec(),
+        [1, 200, 10000]
+    );
+    assert!(sweep.first().unwrap().1 <= sweep.last().unwrap().1);
+    assert!(sweep.iter().any(|(_, size, _)| *size <= 0x6000));
+}
+
+#[test]
+fn verify_signed_maingate() {
+    run_verify_signed::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_signed<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INVALID_SIGNATURE: [u8; 4] = [0x8b, 0xaa, 0x57, 0x9f];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_signed_request(true);
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let signing_key = k256::ecdsa::SigningKey::random(&mut std_rng());
+    let signer: [u8; 20] = {
+        let public_key = signing_key.verifying_key().to_encoded_point(false);
+        sha3::Keccak256::digest(&public_key.as_bytes()[1..])[12..]
+            .try_into()
+            .unwrap()
+    };
+    let sign = |proof: &[u8], nonce: u64| {
+        let digest = eip712_proof_request_digest(
+            1,
+            "Halo2Verifier",
+            verifier_address.into(),
+            proof,
+            &instances,
+            nonce,
+        );
+        let (signature, recovery_id) = signing_key.sign_prehash_recoverable(&digest).unwrap();
+        let (r, s) = signature.split_bytes();
+        (27 + recovery_id.to_byte(), r.into(), s.into())
+    };
+
+    let signature = sign(&proof, 0);
+    let calldata = encode_verify_signed_calldata(None, signer, &proof, &instances, signature);
+    let (_, output) = evm.call(verifier_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    // Nonce is consumed
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_SIGNATURE);
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let signature = sign(&proof, 1);
+    let calldata = encode_verify_signed_calldata(None, signer, &bad_proof, &instances, signature);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_SIGNATURE);
+
+    let (v, r, s) = signature;
+    let mut bad_s = s;
+    bad_s[31] ^= 1;
+    let calldata = encode_verify_signed_calldata(None, signer, &proof, &instances, (v, r, bad_s));
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_SIGNATURE);
+
+    // Malleated signature `(r, n - s)` with flipped `v` recovers the same signer but has high s
+    let n = U256::from_str_radix(
+        "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141",
+        16,
+    )
+    .unwrap();
+    let high_s = (n - U256::from_be_bytes(s)).to_be_bytes::<0x20>();
+    let calldata =
+        encode_verify_signed_calldata(None, signer, &proof, &instances, (55 - v, r, high_s));
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_SIGNATURE);
+
+    let calldata = encode_verify_signed_calldata(None, signer, &proof, &instances, signature);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn render_unoptimized_maingate() {
+    run_render_unoptimized::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_unoptimized<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_creation_code = compile_solidity_unoptimized(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    evm.set_code_size_limit(usize::MAX);
+    let verifier_address = evm.create(verifier_creation_code);
+    println!(
+        "Unoptimized verifier runtime code size: {}",
+        evm.code_size(verifier_address)
+    );
+
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn compile_and_deploy_maingate() {
+    run_compile_and_deploy::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_compile_and_deploy<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.compile_and_deploy(&verifier_solidity).unwrap();
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    evm.set_code_size_limit(0x100);
+    assert_eq!(
+        evm.compile_and_deploy(&verifier_solidity),
+        Err(DeployError::CodeSizeLimit {
+            size: evm.code_size(verifier_address),
+            limit: 0x100
+        })
+    );
+    assert!(matches!(
+        evm.compile_and_deploy("contract Broken {"),
+        Err(DeployError::Compilation(_))
+    ));
+}
+
+#[test]
+fn write_gas_snapshot_maingate() {
+    run_write_gas_snapshot::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_write_gas_snapshot<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+    // Bounds check adds gas cost on every call
+    let bloated_verifier_solidity = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_calldata_bounds_check(true)
+        .render()
+        .unwrap();
+
+    let dir = std::env::temp_dir().join("halo2_solidity_verifier_gas_snapshot");
+    std::fs::create_dir_all(&dir).unwrap();
+    let snapshot = |name: &str, solidity: &str| {
+        let path = dir.join(name);
+        write_gas_snapshot(&path, &[("maingate", solidity, &proof[..], &instances[..])]).unwrap();
+        std::fs::read_to_string(path).unwrap()
+    };
+    let gas_cost = |snapshot: &str| -> u64 {
+        let start = snapshot.find("(gas: ").unwrap() + 6;
+        let end = start + snapshot[start..].find(')').unwrap();
+        snapshot[start..end].parse().unwrap()
+    };
+
+    let lhs = snapshot("lhs", &verifier_solidity);
+    let rhs = snapshot("rhs", &verifier_solidity);
+    assert!(lhs.starts_with("maingate (gas: "));
+    assert_eq!(lhs, rhs);
+
+    let bloated = snapshot("bloated", &bloated_verifier_solidity);
+    assert_ne!(lhs, bloated);
+    assert!(gas_cost(&bloated) > gas_cost(&lhs));
+}
+
+#[test]
+fn verify_deployment_maingate() {
+    run_verify_deployment::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_deployment<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let other_params = ParamsKZG::<Bn256>::setup(C::min_k(), StdRng::seed_from_u64(1));
+
+    let mut evm = Evm::default();
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let (_, vk_solidity) = generator.render_separately().unwrap();
+    let vk_address = evm.create(compile_solidity(vk_solidity));
+    let mut runtimes = vec![evm.code(vk_address)];
+    for vk_source in [VkSource::Inline, VkSource::BytesConstant] {
+        let generator =
+            SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_vk_source(vk_source);
+        let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+        runtimes.push(evm.code(verifier_address));
+    }
+
+    for runtime in runtimes {
+        assert!(verify_deployment(&runtime, &params, &vk));
+        assert!(!verify_deployment(&runtime, &other_params, &vk));
+
+        let x = to_u256_be_bytes(g1_to_u256s(vk.fixed_commitments()[0])[0]);
+        let offset = runtime
+            .windows(0x20)
+            .position(|window| window == x)
+            .unwrap();
+        let mut altered_runtime = runtime.clone();
+        altered_runtime[offset + 0x1f] ^= 1;
+        assert!(!verify_deployment(&altered_runtime, &params, &vk));
+    }
+}
+
+#[test]
+fn verify_call_maingate() {
+    run_verify_call::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_call<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(verifier_solidity));
+    let vk_address = evm.create(compile_solidity(vk_solidity));
+
+    let u256s = instances.iter().map(fr_to_u256).collect_vec();
+    let call = VerifyCall::from_u256s(proof.clone(), &u256s)
+        .unwrap()
+        .set_vk_address(Some(vk_address.into()));
+    assert_eq!(call.instances_as_u256s(), u256s);
+    assert_eq!(
+        call.calldata(),
+        encode_calldata(Some(vk_address.into()), &proof, &instances)
+    );
+
+    let (_, output) = evm.call(verifier_address, call.calldata());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let non_canonical = [fr_to_u256(-Fr::ONE) + U256::from(1)];
+    assert_eq!(VerifyCall::from_u256s(proof, &non_canonical), None);
+}
+
+#[test]
+fn render_artifact_maingate() {
+    run_render_artifact::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_artifact<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let artifact = generator.render_artifact().unwrap();
+    assert_eq!(artifact.solidity, generator.render().unwrap());
+    assert!(artifact
+        .solidity
+        .contains(&format!("contract {} {{", artifact.contract_name)));
+    assert_eq!(artifact.num_instances, instances.len());
+    assert_eq!(artifact.proof_len, proof.len());
+    assert_eq!(generator.proof_len(), proof.len());
+    assert_eq!(artifact.proof_schema, generator.proof_schema());
+    assert_eq!(artifact.constructor_vk, None);
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&artifact.solidity));
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn verify_in_rust_maingate() {
+    run_verify_in_rust::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_in_rust<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    for vk_source in [VkSource::Inline, VkSource::Constructor] {
+        let generator =
+            SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_vk_source(vk_source);
+        let artifact = generator.render_artifact().unwrap();
+        assert!(verify_in_rust(&artifact, &[instances.clone()], &proof));
+        assert!(!verify_in_rust(&artifact, &[instances.clone()], &bad_proof));
+        assert!(!verify_in_rust(
+            &artifact,
+            &[instances[1..].to_vec()],
+            &proof
+        ));
+        assert!(!verify_in_rust(
+            &artifact,
+            &[instances.clone(), instances.clone()],
+            &proof
+        ));
+    }
+}
+
+#[test]
+fn verifier_abi_json_maingate() {
+    run_verifier_abi_json::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verifier_abi_json<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    assert_eq!(
+        generator.verifier_abi_json(false),
+        concat!(
+            r#"[{"type":"function","name":"verifyProof","inputs":["#,
+            r#"{"name":"proof","type":"bytes","internalType":"bytes"},"#,
+            r#"{"name":"instances","type":"uint256[]","internalType":"uint256[]"}],"#,
+            r#""outputs":[{"name":"","type":"bool","internalType":"bool"}],"#,
+            r#""stateMutability":"nonpayable"}]"#
+        )
+    );
+
+    let generator = generator.set_return_mode(ReturnMode::Revert);
+    assert_eq!(
+        generator.verifier_abi_json(true),
+        concat!(
+            r#"[{"type":"function","name":"verifyProof","inputs":["#,
+            r#"{"name":"vk","type":"address","internalType":"address"},"#,
+            r#"{"name":"proof","type":"bytes","internalType":"bytes"},"#,
+            r#"{"name":"instances","type":"uint256[]","internalType":"uint256[]"}],"#,
+            r#""outputs":[],"stateMutability":"nonpayable"}]"#
+        )
+    );
+}
+
+#[test]
+fn encode_verify_calldata_maingate() {
+    run_encode_verify_calldata::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_encode_verify_calldata<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    assert_eq!(
+        generator.encode_verify_calldata(None, &proof, &[instances.clone()]),
+        Ok(encode_calldata(None, &proof, &instances))
+    );
+    assert_eq!(
+        generator.encode_verify_calldata(Some([1; 20]), &proof, &[instances.clone()]),
+        Ok(encode_calldata(Some([1; 20]), &proof, &instances))
+    );
+    assert_eq!(
+        generator.encode_verify_calldata(None, &proof, &[instances.clone(), instances.clone()]),
+        Err(CalldataError::NumInstanceColumns(2))
+    );
+    assert_eq!(
+        generator.encode_verify_calldata(None, &proof, &[instances[1..].to_vec()]),
+        Err(CalldataError::NumInstances {
+            expected: instances.len(),
+            actual: instances.len() - 1,
+        })
+    );
+    assert_eq!(
+        generator.encode_verify_calldata(None, &proof[32..], &[instances.clone()]),
+        Err(CalldataError::ProofLen {
+            expected: proof.len(),
+            actual: proof.len() - 32,
+        })
+    );
+}
+
+#[test]
+fn section_comments_maingate() {
+    run_section_comments::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_section_comments<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+    assert!(!verifier_solidity.contains("// Section: "));
+
+    let generator = generator.set_section_comments(true);
+    let labeled_solidity = generator.render().unwrap();
+    let sections = ["transcript", "evaluate gates", "opening", "pairing"].map(|section| {
+        labeled_solidity
+            .find(&format!("// Section: {section}\n"))
+            .unwrap()
+    });
+    assert!(sections.windows(2).all(|window| window[0] < window[1]));
+
+    let strip = |solidity: &str| {
+        solidity
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("//"))
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert_eq!(strip(&labeled_solidity), strip(&verifier_solidity));
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&labeled_solidity, &proof, &instances));
+}
+
+#[test]
+fn render_interface_maingate() {
+    run_render_interface::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_interface<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let interface = generator.render_interface(false).unwrap();
+    assert!(interface.contains("interface IHalo2Verifier {"));
+    assert!(interface.contains(
+        "function verifyProof(\n        bytes calldata proof,\n        uint256[] calldata instances\n    ) external returns (bool);"
+    ));
+
+    let generator = generator
+        .set_return_mode(ReturnMode::Revert)
+        .set_verify_fn_name("verify");
+    let interface = generator.render_interface(true).unwrap();
+    assert!(interface.contains(
+        "function verify(\n        address vk,\n        bytes calldata proof,\n        uint256[] calldata instances\n    ) external;"
+    ));
+}
+
+#[test]
+fn contract_name_maingate() {
+    run_contract_name::<halo2::maingate::MainGateWithRange<Bn256>>("MainGateVerifier")
+}
+
+#[test]
+fn contract_name_invalid_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| generator.set_contract_name("1Verifier"),
+        GenerateError::InvalidIdentifier("1Verifier".to_string()),
+    )
+}
+
+#[test]
+fn incompatible_options_maingate() {
+    run_incompatible_options::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_incompatible_options<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_domain_constants(true);
+    assert!(generator.render().is_ok());
+    assert!(matches!(
+        generator.render_separately(),
+        Err(GenerateError::IncompatibleOptions(_))
+    ));
+    assert!(matches!(
+        generator.render_router(),
+        Err(GenerateError::IncompatibleOptions(_))
+    ));
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_vk_source(VkSource::Constructor)
+        .set_deployment_check(true);
+    assert!(matches!(
+        generator.render(),
+        Err(GenerateError::IncompatibleOptions(_))
+    ));
+}
+
+fn run_invalid_option<C: halo2::TestCircuit<Fr>>(
+    set_option: impl Fn(SolidityGenerator) -> SolidityGenerator,
+    expected: GenerateError,
+) {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = set_option(SolidityGenerator::new(
+        &params,
+        &vk,
+        Bdfg21,
+        instances.len(),
+    ));
+    assert_eq!(generator.render(), Err(expected.clone()));
+    assert_eq!(generator.render_separately(), Err(expected.clone()));
+    assert_eq!(generator.render_interface(false), Err(expected));
+}
+
+fn run_contract_name<C: halo2::TestCircuit<Fr>>(contract_name: &str) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_contract_name(contract_name);
+    let artifact = generator.render_artifact().unwrap();
+    assert_eq!(artifact.contract_name, contract_name);
+    assert!(artifact
+        .solidity
+        .contains(&format!("contract {contract_name} {{")));
+    assert!(!artifact.solidity.contains("contract Halo2Verifier {"));
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&artifact.solidity, &proof, &instances));
+}
+
+#[test]
+fn verify_fn_name_maingate() {
+    run_verify_fn_name::<halo2::maingate::MainGateWithRange<Bn256>>("verify")
+}
+
+#[test]
+fn verify_fn_name_invalid_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| generator.set_verify_fn_name("verify proof"),
+        GenerateError::InvalidIdentifier("verify proof".to_string()),
+    )
+}
+
+#[test]
+fn verify_fn_name_reserved_maingate() {
+    for name in [
+        "uint256",
+        "int",
+        "bytes32",
+        "fixed128x18",
+        "public",
+        "view",
+        "this",
+        "unchecked",
+    ] {
+        run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+            |generator| generator.set_verify_fn_name(name),
+            GenerateError::InvalidIdentifier(name.to_string()),
+        )
+    }
+}
+
+fn run_verify_fn_name<C: halo2::TestCircuit<Fr>>(verify_fn_name: &str) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    assert_eq!(generator.verify_fn_selector(false), FN_SIG_VERIFY_PROOF);
+    assert_eq!(
+        generator.verify_fn_selector(true),
+        FN_SIG_VERIFY_PROOF_WITH_VK_ADDRESS
+    );
+
+    let generator = generator.set_verify_fn_name(verify_fn_name);
+    let verifier_solidity = generator.render().unwrap();
+    assert!(verifier_solidity.contains(&format!("function {verify_fn_name}(")));
+    assert!(!verifier_solidity.contains("function verifyProof("));
+    assert!(generator
+        .verifier_abi_json(false)
+        .contains(&format!(r#""name":"{verify_fn_name}""#)));
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&verifier_solidity));
+    let mut calldata = encode_calldata(None, &proof, &instances);
+    assert!(evm.try_call(verifier_address, calldata.clone()).is_err());
+    calldata[..4].copy_from_slice(&generator.verify_fn_selector(false));
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    // Additional entrypoints call the renamed function
+    let generator = generator
+        .set_result_index(Some(0))
+        .set_dynamic_instances(true);
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+    let calldata = VerifyCall::new(proof.clone(), instances.clone())
+        .set_verify_fn_selector(Some(generator.verify_fn_selector(false)))
+        .calldata();
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    let calldata = encode_verify_and_get_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, to_u256_be_bytes(fr_to_u256(instances[0])));
+    let calldata = encode_verify_dynamic_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn pragma_maingate() {
+    run_pragma::<halo2::maingate::MainGateWithRange<Bn256>>(">=0.8.19 <0.9.0")
+}
+
+#[test]
+fn pragma_invalid_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| generator.set_pragma("latest"),
+        GenerateError::InvalidPragma("latest".to_string()),
+    )
+}
+
+#[test]
+fn pragma_below_min_version_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| generator.set_pragma(">=0.8.4 <0.9.0 || ^0.8.19"),
+        GenerateError::InvalidPragma(">=0.8.4 <0.9.0 || ^0.8.19".to_string()),
+    )
+}
+
+fn run_pragma<C: halo2::TestCircuit<Fr>>(pragma: &str) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_pragma(pragma);
+    let verifier_solidity = generator.render().unwrap();
+    let (separate_verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+    let artifact = generator.render_artifact().unwrap();
+    for solidity in [
+        &verifier_solidity,
+        &separate_verifier_solidity,
+        &vk_solidity,
+        &generator.render_pausable(false).unwrap(),
+        &generator.render_router().unwrap(),
+        &generator.render_interface(false).unwrap(),
+        &foundry_test_sol(&artifact, &instances, &proof).unwrap(),
+    ] {
+        assert!(solidity.contains(&format!("\npragma solidity {pragma};\n")));
+    }
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&verifier_solidity, &proof, &instances));
+}
+
+#[test]
+fn license_maingate() {
+    run_license::<halo2::maingate::MainGateWithRange<Bn256>>("MIT OR Apache-2.0")
+}
+
+#[test]
+fn license_invalid_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| generator.set_license("MIT\npragma solidity ^0.4.0;"),
+        GenerateError::InvalidLicense("MIT\npragma solidity ^0.4.0;".to_string()),
+    )
+}
+
+fn run_license<C: halo2::TestCircuit<Fr>>(license: &str) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_license(license);
+    let verifier_solidity = generator.render().unwrap();
+    let (separate_verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+    let artifact = generator.render_artifact().unwrap();
+    for solidity in [
+        &verifier_solidity,
+        &separate_verifier_solidity,
+        &vk_solidity,
+        &generator.render_pausable(false).unwrap(),
+        &generator.render_router().unwrap(),
+        &generator.render_interface(false).unwrap(),
+        &foundry_test_sol(&artifact, &instances, &proof).unwrap(),
+    ] {
+        assert!(solidity.starts_with(&format!("// SPDX-License-Identifier: {license}\n")));
+    }
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&verifier_solidity, &proof, &instances));
+}
+
+#[test]
+fn deployment_check_maingate() {
+    run_deployment_check::<halo2::maingate::MainGateWithRange<Bn256>>(None)
+}
+
+#[test]
+#[should_panic(expected = "Transaction reverts")]
+fn deployment_check_off_curve_g1_maingate() {
+    run_deployment_check::<halo2::maingate::MainGateWithRange<Bn256>>(Some("fixed_comms[0].y"))
+}
+
+#[test]
+#[should_panic(expected = "Transaction reverts")]
+fn deployment_check_off_curve_g2_maingate() {
+    run_deployment_check::<halo2::maingate::MainGateWithRange<Bn256>>(Some("g2_x_1"))
+}
+
+fn run_deployment_check<C: halo2::TestCircuit<Fr>>(corrupted: Option<&str>) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_deployment_check(true);
+    let mut verifier_solidity = generator.render().unwrap();
+
+    // Corrupt every occurrence of the embedded constant by flipping its lowest bit.
+    if let Some(corrupted) = corrupted {
+        let line = verifier_solidity
+            .lines()
+            .find(|line| {
+                line.trim_start().starts_with("mstore(")
+                    && line.ends_with(&format!("// {corrupted}"))
+            })
+            .unwrap();
+        let value = line.split([',', ')']).nth(1).unwrap().trim().to_string();
+        let last = u8::from_str_radix(&value[value.len() - 1..], 16).unwrap() ^ 1;
+        let corrupted_value = format!("{}{last:x}", &value[..value.len() - 1]);
+        verifier_solidity = verifier_solidity.replace(&value, &corrupted_value);
+    }
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&verifier_solidity, &proof, &instances));
+}
+
+#[test]
+fn return_mode_revert_maingate() {
+    run_return_mode_revert::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_return_mode_revert<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INVALID_PROOF: [u8; 4] = [0x09, 0xbd, 0xe3, 0x39];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_return_mode(ReturnMode::Revert);
+    let verifier_solidity = generator.render().unwrap();
+    assert!(verifier_solidity.contains("error InvalidProof();"));
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&verifier_solidity));
+
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert!(output.is_empty());
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    let mut bad_instances = instances.clone();
+    bad_instances[0] += Fr::ONE;
+    for calldata in [
+        encode_calldata(None, &bad_proof, &instances),
+        encode_calldata(None, &proof, &bad_instances),
+    ] {
+        let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+        assert_eq!(output, ERROR_INVALID_PROOF);
+    }
+}
+
+#[test]
+fn point_order_maingate() {
+    run_point_order::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_point_order<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let schema = generator.proof_schema();
+    let yx_proof = reorder_proof_points(&proof, &schema, PointOrder::Yx);
+    assert_ne!(yx_proof, proof);
+    assert_eq!(
+        reorder_proof_points(&yx_proof, &schema, PointOrder::Yx),
+        proof
+    );
+    assert_eq!(reorder_proof_points(&proof, &schema, PointOrder::Xy), proof);
+
+    let xy_solidity = generator.render().unwrap();
+    let yx_solidity = generator.set_point_order(PointOrder::Yx).render().unwrap();
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&yx_solidity, &yx_proof, &instances));
+    assert!(!evm.deploy_and_verify(&yx_solidity, &proof, &instances));
+    assert!(!evm.deploy_and_verify(&xy_solidity, &yx_proof, &instances));
+}
+
+#[test]
+fn render_core_assembly_maingate() {
+    run_render_core_assembly::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_core_assembly<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_VERIFY: [u8; 4] = [0x96, 0x49, 0xda, 0xae];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let (constants, assembly) = generator.render_core_assembly().unwrap();
+    assert!(assembly.trim_start().starts_with("assembly {"));
+    assert!(assembly.trim_end().ends_with('}'));
+
+    let solidity = format!(
+        "// SPDX-License-Identifier: MIT\n\n\
+         pragma solidity ^0.8.0;\n\n\
+         contract Core {{\n\
+         {constants}\n    \
+         function verify(bytes calldata proof, uint256[] calldata instances) public returns (bool) {{\n\
+         {assembly}    \
+         }}\n\
+         }}\n"
+    );
+
+    let mut evm = Evm::default();
+    let core_address = evm.create(compile_solidity(solidity));
+
+    let mut calldata = encode_calldata(None, &proof, &instances);
+    calldata[..4].copy_from_slice(&FN_SIG_VERIFY);
+    let (_, output) = evm.call(core_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    *calldata.last_mut().unwrap() ^= 1;
+    assert!(evm.try_call(core_address, calldata).is_err());
+}
+
+#[test]
+fn render_yul_maingate() {
+    run_render_yul::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_yul<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let yul = generator.render_yul().unwrap();
+    assert!(yul.starts_with("object \"Halo2Verifier\" {"));
+    assert!(!yul.contains("_CPTR") && !yul.contains("_MPTR"));
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_yul(yul));
+
+    let mut calldata = encode_calldata(None, &proof, &instances);
+    let (_, output) = evm.call(verifier_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    *calldata.last_mut().unwrap() ^= 1;
+    assert!(evm.try_call(verifier_address, calldata.clone()).is_err());
+
+    *calldata.last_mut().unwrap() ^= 1;
+    calldata[0] ^= 1;
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+}
+
+#[test]
+fn check_yul_instructions_maingate() {
+    run_check_yul_instructions::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_check_yul_instructions<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let yul = generator.render_yul().unwrap();
+    let instructions = check_yul_instructions(&yul).unwrap();
+    for instruction in ["calldataload", "mulmod", "staticcall", "return"] {
+        assert!(instructions.contains(instruction));
+    }
+    let (_, assembly) = generator.render_core_assembly().unwrap();
+    assert!(check_yul_instructions(&assembly).is_ok());
+
+    let mut lines = yul.lines().collect_vec();
+    let line = lines.len() - 3;
+    lines.insert(
+        line,
+        "            pop(call(gas(), caller(), 0, 0, 0, 0, 0))",
+    );
+    assert_eq!(
+        check_yul_instructions(&lines.join("\n")),
+        Err(YulError::UnsupportedInstruction {
+            name: "call".to_string(),
+            line: line + 1,
+        })
+    );
+}
+
+#[test]
+fn render_domain_constants_maingate() {
+    run_render_domain_constants::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_domain_constants<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_K: [u8; 4] = [0xa9, 0x32, 0x49, 0x2f];
+    const FN_SIG_N: [u8; 4] = [0xc9, 0xe5, 0x25, 0xdf];
+
+    let k = C::min_k();
+    let (params, vk, instances, proof) = halo2::create_testdata_bdfg21::<C>(k, None, std_rng());
+    assert_eq!(params.k(), k);
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_domain_constants(true);
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+    let (_, output) = evm.call(verifier_address, FN_SIG_K.to_vec());
+    assert_eq!(output, to_u256_be_bytes(k));
+    let (_, output) = evm.call(verifier_address, FN_SIG_N.to_vec());
+    assert_eq!(output, to_u256_be_bytes(1u64 << k));
+
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn explain_maingate() {
+    run_explain::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_explain<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let explanation = generator.explain();
+
+    // User challenges, theta, beta, gamma, y, x, and zeta, nu, mu for batch opening
+    let num_challenges = vk.cs().challenge_phase().len() + 8;
+    assert!(explanation.contains(&format!("squeezes {num_challenges} challenges")));
+    assert_eq!(explanation.matches("Perform 1 pairing check").count(), 1);
+}
+
+#[test]
+fn verify_in_allowlist_maingate() {
+    run_verify_in_allowlist::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_in_allowlist<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_NOT_IN_ALLOWLIST: [u8; 4] = [0x57, 0xaf, 0xca, 0xd4];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let allowlist_index = 0;
+
+    let keccak256 = |bytes: &[u8]| <[u8; 32]>::from(sha3::Keccak256::digest(bytes));
+    let hash_pair = |lhs: [u8; 32], rhs: [u8; 32]| {
+        let (lhs, rhs) = if lhs < rhs { (lhs, rhs) } else { (rhs, lhs) };
+        keccak256(&[lhs, rhs].concat())
+    };
+    let leaves = [
+        keccak256(&to_u256_be_bytes(fr_to_u256(&instances[allowlist_index]))),
+        keccak256(b"alice"),
+        keccak256(b"bob"),
+        keccak256(b"carol"),
+    ];
+    let nodes = [
+        hash_pair(leaves[0], leaves[1]),
+        hash_pair(leaves[2], leaves[3]),
+    ];
+    let root = hash_pair(nodes[0], nodes[1]);
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_allowlist_index(Some(allowlist_index));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(chain![verifier_creation_code, root].collect());
+
+    let calldata =
+        encode_verify_in_allowlist_calldata(None, &proof, &instances, &[leaves[1], nodes[1]]);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let calldata =
+        encode_verify_in_allowlist_calldata(None, &proof, &instances, &[leaves[2], nodes[1]]);
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_NOT_IN_ALLOWLIST);
+}
+
+#[test]
+fn verify_with_inputs_root_maingate() {
+    run_verify_with_inputs_root::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_with_inputs_root<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_UPDATE_VALID_INPUTS_ROOT: [u8; 4] = [0x0b, 0x8e, 0x38, 0x97];
+    const ERROR_INVALID_INPUTS: [u8; 4] = [0xf3, 0x4c, 0xfa, 0xb6];
+    const ERROR_UNAUTHORIZED: [u8; 4] = [0x82, 0xb4, 0x29, 0x00];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let inputs_root_index = 0;
+
+    let keccak256 = |bytes: &[u8]| <[u8; 32]>::from(sha3::Keccak256::digest(bytes));
+    let hash_pair = |lhs: [u8; 32], rhs: [u8; 32]| {
+        let (lhs, rhs) = if lhs < rhs { (lhs, rhs) } else { (rhs, lhs) };
+        keccak256(&[lhs, rhs].concat())
+    };
+    let leaf = keccak256(&to_u256_be_bytes(fr_to_u256(&instances[inputs_root_index])));
+    let sibling = keccak256(b"alice");
+    let root = hash_pair(leaf, sibling);
+    let next_root = hash_pair(keccak256(b"bob"), keccak256(b"carol"));
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_inputs_root_index(Some(inputs_root_index));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_verify_with_inputs_root_calldata(None, &proof, &instances, &[sibling]);
+    let update_root =
+        |root: [u8; 32]| -> Vec<u8> { chain![FN_SIG_UPDATE_VALID_INPUTS_ROOT, root].collect() };
+    let governance = Default::default();
+    let non_governance = [1; 20].into();
+
+    let (_, output) = evm
+        .try_call(verifier_address, calldata.clone())
+        .unwrap_err();
+    assert_eq!(output, ERROR_INVALID_INPUTS);
+
+    let (_, output) = evm
+        .try_call_from(non_governance, verifier_address, update_root(root))
+        .unwrap_err();
+    assert_eq!(output, ERROR_UNAUTHORIZED);
+
+    evm.try_call_from(governance, verifier_address, update_root(root))
+        .unwrap();
+    let (_, output) = evm.call(verifier_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    evm.try_call_from(governance, verifier_address, update_root(next_root))
+        .unwrap();
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_INVALID_INPUTS);
+}
+
+#[test]
+fn verify_with_poseidon_preimage_maingate() {
+    run_verify_with_poseidon_preimage::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_verify_with_poseidon_preimage<C: halo2::TestCircuit<Fr>>() {
+    const ERROR_INVALID_PREIMAGE: [u8; 4] = [0x19, 0x68, 0xa9, 0x02];
+    // Stand-in with the same interface as circomlib generated `PoseidonT3`, since only the call
+    // to hasher and the comparison against instance are under test.
+    const MOCK_HASHER: &str = "\
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+contract PoseidonT3 {
+    function poseidon(uint256[2] memory inputs) public pure returns (uint256) {
+        uint256 r = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+        return addmod(inputs[0], inputs[1], r);
+    }
+}";
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let instance_index = 0;
+
+    let mut evm = Evm::default();
+    let hasher_address = evm.create(compile_solidity(MOCK_HASHER));
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_poseidon_preimage(Some(
+            PoseidonPreimage::new(instance_index, 2, hasher_address.into()),
+        ));
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+
+    let preimage = [instances[instance_index] - Fr::from(7), Fr::from(7)];
+    let calldata =
+        encode_verify_with_poseidon_preimage_calldata(None, &proof, &instances, &preimage);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let wrong_preimages = [
+        vec![instances[instance_index] - Fr::from(7), Fr::from(8)],
+        vec![instances[instance_index]],
+        vec![instances[instance_index], Fr::ZERO, Fr::ZERO],
+    ];
+    for preimage in wrong_preimages {
+        let calldata =
+            encode_verify_with_poseidon_preimage_calldata(None, &proof, &instances, &preimage);
+        let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+        assert_eq!(output, ERROR_INVALID_PREIMAGE);
+    }
+}
+
+#[test]
+fn upgradeable_maingate() {
+    run_upgradeable::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_upgradeable<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_PROXIABLE_UUID: [u8; 4] = [0x52, 0xd1, 0x90, 0x2d];
+    const FN_SIG_UPGRADE_TO_AND_CALL: [u8; 4] = [0x4f, 0x1e, 0xf2, 0x86];
+    const ERROR_UNAUTHORIZED: [u8; 4] = [0x82, 0xb4, 0x29, 0x00];
+    // Minimal ERC-1967 proxy like OpenZeppelin `ERC1967Proxy`.
+    const PROXY: &str = "\
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.0;
+contract ERC1967Proxy {
+    uint256 internal constant IMPLEMENTATION_SLOT = 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
+    constructor(address implementation, bytes memory data) {
+        assembly {
+            sstore(IMPLEMENTATION_SLOT, implementation)
+        }
+        if (data.length > 0) {
+            (bool success, ) = implementation.delegatecall(data);
+            require(success);
+        }
+    }
+    fallback() external payable {
+        assembly {
+            calldatacopy(0, 0, calldatasize())
+            let success := delegatecall(gas(), sload(IMPLEMENTATION_SLOT), 0, calldatasize(), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            if iszero(success) {
+                revert(0, returndatasize())
+            }
+            return(0, returndatasize())
+        }
+    }
+}";
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let (next_params, next_vk, next_instances, next_proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k() + 1, None, std_rng());
+
+    // Admin is the deployer of implementation
+    let admin = [2; 20];
+    let mut evm = Evm::default();
+    let mut deploy_implementation = |params, vk, num_instances| {
+        let generator =
+            SolidityGenerator::new(params, vk, Bdfg21, num_instances).set_upgradeable(true);
+        evm.create_from(admin.into(), compile_solidity(generator.render().unwrap()))
+    };
+    let implementation = deploy_implementation(&params, &vk, instances.len());
+    let next_implementation = deploy_implementation(&next_params, &next_vk, next_instances.len());
+
+    let address_word =
+        |address: [u8; 20]| to_u256_be_bytes(U256::try_from_be_slice(&address).unwrap());
+    let proxy = evm.create(
+        chain![
+            compile_solidity(PROXY),
+            address_word(implementation.into()),
+            to_u256_be_bytes(0x40),
+            to_u256_be_bytes(0),
+        ]
+        .collect(),
+    );
+    let upgrade_and_call = |data: &[u8]| {
+        chain![
+            FN_SIG_UPGRADE_TO_AND_CALL,
+            address_word(next_implementation.into()),
+            to_u256_be_bytes(0x40),
+            to_u256_be_bytes(data.len()),
+            data.iter().cloned(),
+            vec![0; (32 - data.len() % 32) % 32],
+        ]
+        .collect::<Vec<_>>()
+    };
+    let upgrade = upgrade_and_call(&[]);
+    let calldata = encode_calldata(None, &proof, &instances);
+    let next_calldata = encode_calldata(None, &next_proof, &next_instances);
+
+    let (_, output) = evm.call(proxy, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    assert!(evm.try_call(proxy, next_calldata.clone()).is_err());
+
+    for (caller, address) in [(Default::default(), proxy), (admin.into(), implementation)] {
+        let (_, output) = evm
+            .try_call_from(caller, address, upgrade.clone())
+            .unwrap_err();
+        assert_eq!(output, ERROR_UNAUTHORIZED);
+    }
+
+    // Revert data of call after upgrade is bubbled up, e.g. `proxiableUUID()` through proxy
+    let (_, output) = evm
+        .try_call_from(
+            admin.into(),
+            proxy,
+            upgrade_and_call(&FN_SIG_PROXIABLE_UUID),
+        )
+        .unwrap_err();
+    assert_eq!(output, ERROR_UNAUTHORIZED);
+    let (_, output) = evm.call(proxy, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    evm.try_call_from(admin.into(), proxy, upgrade).unwrap();
+
+    let (_, output) = evm.call(proxy, next_calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    assert!(evm.try_call(proxy, calldata).is_err());
+}
+
+#[test]
+fn instance_endianness_maingate() {
+    run_instance_endianness::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_instance_endianness<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    for endianness in [Endianness::Big, Endianness::Little] {
+        for instance in instances.iter() {
+            let bytes = encode_instance(instance, endianness);
+            assert_eq!(decode_instance(&bytes, endianness), Some(*instance));
+        }
+        let encoded = instances
+            .iter()
+            .map(|instance| encode_instance(instance, endianness))
+            .collect_vec();
+        assert_eq!(
+            instances_to_tokens(&encoded, endianness),
+            Some(instances.iter().map(fr_to_u256).collect_vec())
+        );
+    }
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    let instance_offset = calldata.len() - 0x20 * instances.len();
+    let calldata_in = |endianness| {
+        let mut calldata = calldata.clone();
+        for (instance, word) in instances
+            .iter()
+            .zip(calldata[instance_offset..].chunks_mut(0x20))
+        {
+            word.copy_from_slice(&encode_instance(instance, endianness));
+        }
+        calldata
+    };
+
+    let (_, output) = evm.call(verifier_address, calldata_in(Endianness::Big));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    assert!(evm
+        .try_call(verifier_address, calldata_in(Endianness::Little))
+        .is_err());
+}
+
+#[test]
+fn size_breakdown_maingate() {
+    run_size_breakdown::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_size_breakdown<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+    let report = size_breakdown(&verifier_solidity);
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&verifier_solidity));
+    assert_eq!(report.total(), evm.code_size(verifier_address));
+    for size in [
+        report.vk,
+        report.transcript,
+        report.quotient,
+        report.msm,
+        report.pairing,
+    ] {
+        assert!(size > 0);
+    }
+}
+
+#[test]
+fn hoist_constants_maingate() {
+    run_hoist_constants::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_hoist_constants<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let mut evm = Evm::default();
+    let mut code_sizes = Vec::new();
+    for hoist_constants in [false, true] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_hoist_constants(hoist_constants);
+        let verifier_solidity = generator.render().unwrap();
+        assert!(evm.deploy_and_verify(&verifier_solidity, &proof, &instances));
+
+        let mut bad_proof = proof.clone();
+        *bad_proof.last_mut().unwrap() ^= 1;
+        assert!(!evm.deploy_and_verify(&verifier_solidity, &bad_proof, &instances));
+
+        let verifier_address = evm.create(compile_solidity(&verifier_solidity));
+        code_sizes.push(evm.code_size(verifier_address));
+    }
+    assert!(code_sizes[1] <= code_sizes[0]);
+}
+
+#[test]
+fn estimate_verify_gas_maingate() {
+    run_estimate_verify_gas::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_estimate_verify_gas<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+    let report = estimate_verify_gas(&verifier_solidity, &proof, &instances).unwrap();
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&verifier_solidity));
+    let (gas_cost, _) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(report.total, gas_cost);
+    assert_eq!(report.ec_pairing, 45000 + 34000 * 2);
+    assert!(report.ec_mul > 0 && report.ec_add > 0);
+    assert!(report.ec_add + report.ec_mul + report.ec_pairing < report.total);
+}
+
+#[test]
+fn proof_encoding_maingate() {
+    run_proof_encoding::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_proof_encoding<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let schema = generator.proof_schema();
+
+    let mut transcript = Keccak256Transcript::<G1Affine, _>::new(proof.as_slice());
+    for field in schema.iter() {
+        let words = match field.kind {
+            ProofFieldKind::Point => g1_to_u256s(transcript.read_point().unwrap()).to_vec(),
+            ProofFieldKind::Scalar => vec![fr_to_u256(transcript.read_scalar().unwrap())],
+        };
+        let bytes = words
+            .into_iter()
+            .flat_map(to_u256_be_bytes)
+            .collect::<Vec<_>>();
+        assert_eq!(proof[field.range()], bytes, "{}", field.name);
+    }
+
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    assert_eq!(calldata[0x64..0x64 + proof.len()], proof);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+}
+
+#[test]
+fn deploy_and_verify_maingate() {
+    run_deploy_and_verify::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_deploy_and_verify<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(&verifier_solidity, &proof, &instances));
+
+    let mut bad_proof = proof.clone();
+    *bad_proof.last_mut().unwrap() ^= 1;
+    assert!(!evm.deploy_and_verify(&verifier_solidity, &bad_proof, &instances));
+}
+
+#[test]
+#[should_panic(expected = "Target zkSync Era is not supported")]
+fn render_zksync_era_huge() {
+    run_render_target::<halo2::huge::HugeCircuit<Bn256>>(Target::ZkSyncEra)
+}
+
+#[test]
+fn render_evm_huge() {
+    run_render_target::<halo2::huge::HugeCircuit<Bn256>>(Target::Evm)
+}
+
+fn run_render_target<C: halo2::TestCircuit<Fr>>(target: Target) {
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    assert_target_supported(target);
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier_solidity = generator.render().unwrap();
+
+    let mut evm = Evm::default();
+    assert!(evm.deploy_and_verify(verifier_solidity, &proof, &instances));
+}
+
+#[test]
+fn render_transcript_prefix_maingate() {
+    run_render_transcript_prefix::<halo2::maingate::MainGateWithRange<Bn256>>(false)
+}
+
+#[test]
+fn render_separately_transcript_prefix_maingate() {
+    run_render_transcript_prefix::<halo2::maingate::MainGateWithRange<Bn256>>(true)
+}
+
+fn run_render_transcript_prefix<C: halo2::TestCircuit<Fr>>(separate: bool) {
+    let prefix = b"context of application";
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let proof = halo2::create_proof_bdfg21_with_prefix(
+        &params,
+        &vk,
+        C::new(None, std_rng()),
+        Some(prefix),
+        std_rng(),
+    );
+
+    let generator =
+        SolidityGenerator::new(&params, &vk, Bdfg21, instances.len()).set_transcript_prefix(true);
+
+    let mut evm = Evm::default();
+    let (verifier_address, vk_address): (_, Option<[u8; 20]>) = if separate {
+        let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+        let verifier_address = evm.create(compile_solidity(verifier_solidity));
+        let vk_address = evm.create(compile_solidity(vk_solidity));
+        (verifier_address, Some(vk_address.into()))
+    } else {
+        let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+        (verifier_address, None)
+    };
+
+    let calldata = encode_calldata_with_prefix(vk_address, &proof, &instances, prefix);
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    let calldata = encode_calldata_with_prefix(vk_address, &proof, &instances, b"another context");
+    assert!(evm.try_call(verifier_address, calldata).is_err());
+}
+
+#[test]
+fn transcript_prefix_with_entrypoints_maingate() {
+    run_invalid_option::<halo2::maingate::MainGateWithRange<Bn256>>(
+        |generator| {
+            generator
+                .set_transcript_prefix(true)
+                .set_result_index(Some(0))
+        },
+        GenerateError::IncompatibleOptions("Transcript prefix and additional entrypoints"),
+    )
+}
+
+#[test]
+fn render_pausable_maingate() {
+    run_render_pausable::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_render_pausable<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_PAUSE: [u8; 4] = [0x84, 0x56, 0xcb, 0x59];
+    const FN_SIG_UNPAUSE: [u8; 4] = [0x3f, 0x4b, 0xa8, 0x3a];
+    const ERROR_PAUSED: [u8; 4] = [0x9e, 0x87, 0xfa, 0xc8];
+    const ERROR_UNAUTHORIZED: [u8; 4] = [0x82, 0xb4, 0x29, 0x00];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    for (return_mode, accepted) in [
+        (ReturnMode::Bool, [vec![0; 31], vec![1]].concat()),
+        (ReturnMode::Revert, vec![]),
+    ] {
+        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+            .set_return_mode(return_mode);
+        let verifier_creation_code = compile_solidity(generator.render().unwrap());
+        let pausable_creation_code = compile_solidity(generator.render_pausable(false).unwrap());
+
+        let mut evm = Evm::default();
+        let verifier_address = evm.create(verifier_creation_code);
+        let verifier_address: [u8; 20] = verifier_address.into();
+        let pausable_address =
+            evm.create(chain![pausable_creation_code, [0; 12], verifier_address].collect());
+
+        let calldata = encode_calldata(None, &proof, &instances);
+        let admin = Default::default();
+        let non_admin = [1; 20].into();
+
+        let (_, output) = evm.call(pausable_address, calldata.clone());
+        assert_eq!(output, accepted);
+
+        let (_, output) = evm
+            .try_call_from(non_admin, pausable_address, FN_SIG_PAUSE.to_vec())
+            .unwrap_err();
+        assert_eq!(output, ERROR_UNAUTHORIZED);
+
+        evm.try_call_from(admin, pausable_address, FN_SIG_PAUSE.to_vec())
+            .unwrap();
+        let (_, output) = evm
+            .try_call(pausable_address, calldata.clone())
+            .unwrap_err();
+        assert_eq!(output, ERROR_PAUSED);
+
+        evm.try_call_from(admin, pausable_address, FN_SIG_UNPAUSE.to_vec())
+            .unwrap();
+        let (_, output) = evm.call(pausable_address, calldata);
+        assert_eq!(output, accepted);
+    }
+}
+
+#[test]
+fn stored_accumulator_maingate() {
+    run_stored_accumulator::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_stored_accumulator<C: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_UPDATE_ACCUMULATOR: [u8; 4] = [0x89, 0x8f, 0x5d, 0xdc];
+    const ERROR_STALE_ACCUMULATOR: [u8; 4] = [0x0e, 0x7b, 0xf6, 0xc8];
+    const ERROR_UNAUTHORIZED: [u8; 4] = [0x82, 0xb4, 0x29, 0x00];
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_stored_accumulator_index(Some(0));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = encode_calldata(None, &proof, &instances);
+    let update_accumulator = |accumulator: Fr| -> Vec<u8> {
+        chain![
+            FN_SIG_UPDATE_ACCUMULATOR,
+            to_u256_be_bytes(fr_to_u256(&accumulator))
+        ]
+        .collect()
+    };
+    let updater = Default::default();
+    let non_updater = [1; 20].into();
+
+    let (_, output) = evm
+        .try_call(verifier_address, calldata.clone())
+        .unwrap_err();
+    assert_eq!(output, ERROR_STALE_ACCUMULATOR);
+
+    let (_, output) = evm
+        .try_call_from(
+            non_updater,
+            verifier_address,
+            update_accumulator(instances[0]),
+        )
+        .unwrap_err();
+    assert_eq!(output, ERROR_UNAUTHORIZED);
+
+    evm.try_call_from(updater, verifier_address, update_accumulator(instances[0]))
+        .unwrap();
+    let (_, output) = evm.call(verifier_address, calldata.clone());
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    evm.try_call_from(
+        updater,
+        verifier_address,
+        update_accumulator(instances[0] + Fr::ONE),
+    )
+    .unwrap();
+    let (_, output) = evm.try_call(verifier_address, calldata).unwrap_err();
+    assert_eq!(output, ERROR_STALE_ACCUMULATOR);
+}
+
+#[test]
+fn patch_vk_maingate() {
+    run_patch_vk::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_patch_vk<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let existing = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .render()
+        .unwrap()
+        .replace(
+            "contract Halo2Verifier {",
+            "// Customized wrapper\ncontract Halo2Verifier {",
+        );
+
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, StdRng::seed_from_u64(1));
+    let generated = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .render()
+        .unwrap();
+    let patched = patch_vk(&existing, &generated).unwrap();
+    assert!(patched.contains("// Customized wrapper"));
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(&patched));
+    let (_, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    type Huge = halo2::huge::HugeCircuit<Bn256>;
+    let (params, vk, instances, _) = halo2::create_testdata_bdfg21::<Huge>(
+        <Huge as halo2::TestCircuit<Fr>>::min_k(),
+        None,
+        std_rng(),
+    );
+    let generated = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .render()
+        .unwrap();
+    assert!(patch_vk(&existing, &generated).is_err());
+}
+
+#[test]
+fn vk_json_maingate() {
+    run_vk_json::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_vk_json<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let expected = generator.render().unwrap();
+    let (_, expected_vk) = generator.render_separately().unwrap();
+
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, StdRng::seed_from_u64(1));
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let existing = generator.render().unwrap();
+    let (_, existing_vk) = generator.render_separately().unwrap();
+    assert_ne!(existing, expected);
+
+    let json = export_vk_json(&expected).unwrap();
+    assert!(json.contains("\"vk_digest\": \"0x"));
+    assert!(json.contains("\"fixed_comms\": ["));
+    assert_eq!(export_vk_json(&expected_vk).unwrap(), json);
+    assert_eq!(import_vk_json(&existing, &json).unwrap(), expected);
+    assert_eq!(import_vk_json(&existing_vk, &json).unwrap(), expected_vk);
+
+    let renamed = json.replacen("\"k\": ", "\"l\": ", 1);
+    assert_eq!(
+        import_vk_json(&existing, &renamed),
+        Err(PatchVkError::InvalidJson)
+    );
+    assert_eq!(
+        import_vk_json(&existing, &json[..json.len() - 2]),
+        Err(PatchVkError::InvalidJson)
+    );
+}
+
+#[test]
+fn check_num_instances_maingate() {
+    run_check_num_instances::<halo2::maingate::MainGateWithRange<Bn256>>()
+}
+
+fn run_check_num_instances<C: halo2::TestCircuit<Fr>>() {
+    let (params, vk, instances, _) =
+        halo2::create_testdata_bdfg21::<C>(C::min_k(), None, std_rng());
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len());
+    let verifier = generator.render().unwrap();
+    let (_, vk_contract) = generator.render_separately().unwrap();
+
+    assert_eq!(check_num_instances(&verifier, instances.len()), Ok(()));
+    assert_eq!(check_num_instances(&vk_contract, instances.len()), Ok(()));
+    assert_eq!(
+        check_num_instances(&verifier, instances.len() + 1),
+        Err(PatchVkError::InvalidInstanceCount {
+            expected: instances.len(),
+            got: instances.len() + 1,
+        })
+    );
+    assert_eq!(
+        check_num_instances("contract Halo2Verifier {}", instances.len()),
+        Err(PatchVkError::MissingVk)
+    );
+}
+
+#[test]
+fn render_router_maingate_and_multi_point() {
+    run_render_router::<
+        halo2::maingate::MainGateWithRange<Bn256>,
+        halo2::multi_point::MultiPointCircuit<Bn256>,
+    >()
+}
+
+fn run_render_router<C0: halo2::TestCircuit<Fr>, C1: halo2::TestCircuit<Fr>>() {
+    const FN_SIG_REGISTER: [u8; 4] = [0xe5, 0xfc, 0x1f, 0x6c];
+    const ERROR_UNREGISTERED_CIRCUIT: [u8; 4] = [0x9b, 0xf9, 0x12, 0x2e];
+
+    for (return_mode, accepted) in [
+        (ReturnMode::Bool, [vec![0; 31], vec![1]].concat()),
+        (ReturnMode::Revert, vec![]),
+    ] {
+        let mut evm = Evm::default();
+        let mut deploy = |(params, vk, instances, proof): (_, _, Vec<Fr>, Vec<u8>)| {
+            let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+                .set_return_mode(return_mode);
+            let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
+            let verifier_address = evm.create(compile_solidity(verifier_solidity));
+            let vk_address = evm.create(compile_solidity(vk_solidity));
+            let router_solidity = generator.render_router().unwrap();
+            (
+                router_solidity,
+                verifier_address,
+                vk_address,
+                instances,
+                proof,
+            )
+        };
+        // Circuits of different shape, which can't share the same verifier
+        let circuits = [
+            deploy(halo2::create_testdata_bdfg21::<C0>(
+                C0::min_k(),
+                None,
+                std_rng(),
+            )),
+            deploy(halo2::create_testdata_bdfg21::<C1>(
+                C1::min_k(),
+                None,
+                std_rng(),
+            )),
+        ];
+        assert_eq!(circuits[0].0, circuits[1].0);
+        let router_address = evm.create(compile_solidity(&circuits[0].0));
+
+        for (idx, (_, verifier_address, vk_address, _, _)) in circuits.iter().enumerate() {
+            let verifier_address: [u8; 20] = (*verifier_address).into();
+            let vk_address: [u8; 20] = (*vk_address).into();
+            let calldata = chain![
+                FN_SIG_REGISTER,
+                [idx as u8; 32],
+                [0; 12],
+                verifier_address,
+                [0; 12],
+                vk_address
+            ]
+            .collect();
+            evm.call(router_address, calldata);
+        }
+
+        for (idx, (_, _, _, instances, proof)) in circuits.iter().enumerate() {
+            let (_, output) = evm.call(
+                router_address,
+                encode_router_calldata([idx as u8; 32], proof, instances),
+            );
+            assert_eq!(output, accepted);
+        }
+
+        let [_, (_, _, _, instances, proof)] = &circuits;
+        let calldata = encode_router_calldata([0; 32], proof, instances);
+        assert!(evm.try_call(router_address, calldata).is_err());
+
+        let calldata = encode_router_calldata([0xff; 32], proof, instances);
+        let (_, output) = evm.try_call(router_address, calldata).unwrap_err();
+        assert_eq!(output, ERROR_UNREGISTERED_CIRCUIT);
+    }
+}
+
+#[test]
+fn verify_with_nonce_multi_point() {
+    const ERROR_INVALID_NONCE: [u8; 4] = [0x75, 0x66, 0x88, 0xfe];
+
+    let mut rng = std_rng();
+    let [testdata_0, testdata_1, testdata_2] = [0, 1, 2].map(|nonce| {
+        let instances = vec![Fr::from(nonce), Fr::random(&mut rng)];
+        let circuit = halo2::multi_point::MultiPointCircuit::<Bn256>(instances);
+        halo2::create_testdata_bdfg21_with_circuit(6, circuit, std_rng())
+    });
+
+    let (params, vk, instances, _) = &testdata_0;
+    let generator =
+        SolidityGenerator::new(params, vk, Bdfg21, instances.len()).set_nonce_index(Some(0));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
+
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(verifier_creation_code);
+
+    let calldata = |(_, _, instances, proof): &(_, _, Vec<Fr>, Vec<u8>)| {
+        encode_verify_with_nonce_calldata(None, proof, instances)
+    };
+    let (_, output) = evm.call(verifier_address, calldata(&testdata_0));
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
+
+    for replayed_or_out_of_sequence in [&testdata_0, &testdata_2] {
+        let (_, output) = evm
+            .try_call(verifier_address, calldata(replayed_or_out_of_sequence))
+            .unwrap_err();
+        assert_eq!(output, ERROR_INVALID_NONCE);
+    }
+
+    for testdata in [&testdata_1, &testdata_2] {
+        let (_, output) = evm.call(verifier_address, calldata(testdata));
+        assert_eq!(output, [vec![0; 31], vec![1]].concat());
+    }
+}
+
+#[test]
+fn verify_nullifier_multi_point() {
+    let mut rng = std_rng();
+    let [signal, root, nullifier_hash] = [(); 3].map(|_| Fr::random(&mut rng));
+    let circuit =
+        halo2::multi_point::MultiPointCircuit::<Bn256>(vec![root, nullifier_hash, signal]);
     let (params, vk, instances, proof) =
-        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+        halo2::create_testdata_bdfg21_with_circuit(6, circuit, std_rng());
 
     let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
-        .set_acc_encoding(acc_encoding);
-    let verifier_solidity = generator.render().unwrap();
-    let verifier_creation_code = compile_solidity(verifier_solidity);
-    let verifier_creation_code_size = verifier_creation_code.len();
+        .set_nullifier_interface(Some(NullifierInterface::new(2, 0, 1)));
+    let verifier_creation_code = compile_solidity(generator.render().unwrap());
 
     let mut evm = Evm::default();
     let verifier_address = evm.create(verifier_creation_code);
-    let verifier_runtime_code_size = evm.code_size(verifier_address);
-
-    println!("Verifier creation code size: {verifier_creation_code_size}");
-    println!("Verifier runtime code size: {verifier_runtime_code_size}");
 
-    let (gas_cost, output) = evm.call(verifier_address, encode_calldata(None, &proof, &instances));
+    let calldata = encode_nullifier_calldata(None, signal, root, nullifier_hash, &proof);
+    let (_, output) = evm.call(verifier_address, calldata);
     assert_eq!(output, [vec![0; 31], vec![1]].concat());
-    println!("Gas cost: {gas_cost}");
+
+    let calldata = encode_nullifier_calldata(None, root, signal, nullifier_hash, &proof);
+    assert!(evm.try_call(verifier_address, calldata).is_err());
 }
 
-fn run_render_separately<C: halo2::TestCircuit<Fr>>() {
-    let acc_encoding = AccumulatorEncoding::new(0, 4, 68).into();
-    let (params, vk, instances, _) =
-        halo2::create_testdata_bdfg21::<C>(C::min_k(), acc_encoding, std_rng());
+#[test]
+fn render_encoder_multi_point() {
+    const FN_SIG_ENCODE_NULLIFIER: [u8; 4] = [0xb4, 0x5e, 0xb1, 0x44];
+    const FN_SIG_ENCODE_NULLIFIER_WITH_VK_ADDRESS: [u8; 4] = [0xcf, 0x7c, 0x51, 0xe5];
 
-    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
-        .set_acc_encoding(acc_encoding);
-    let (verifier_solidity, _vk_solidity) = generator.render_separately().unwrap();
-    let verifier_creation_code = compile_solidity(&verifier_solidity);
-    let verifier_creation_code_size = verifier_creation_code.len();
+    let mut rng = std_rng();
+    let [signal, root, nullifier_hash] = [(); 3].map(|_| Fr::random(&mut rng));
+    let circuit =
+        halo2::multi_point::MultiPointCircuit::<Bn256>(vec![root, nullifier_hash, signal]);
+    let (params, vk, instances, proof) =
+        halo2::create_testdata_bdfg21_with_circuit(6, circuit, std_rng());
 
-    let mut evm = Evm::default();
-    let verifier_address = evm.create(verifier_creation_code);
-    let verifier_runtime_code_size = evm.code_size(verifier_address);
+    let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
+        .set_nullifier_interface(Some(NullifierInterface::new(2, 0, 1)));
 
-    println!("Verifier creation code size: {verifier_creation_code_size}");
-    println!("Verifier runtime code size: {verifier_runtime_code_size}");
+    // Wrap library into a harness contract exposing both overloads of `encode`
+    let harness_solidity = generator.render_encoder().unwrap().replacen(
+        "library VerifyEncoder {",
+        "contract EncoderHarness {
+    function encodeNullifier(uint256 signal, uint256 root, uint256 nullifierHash, bytes calldata proof) external pure returns (bytes memory) {
+        return encode(PublicInputs(signal, root, nullifierHash), proof);
+    }
 
-    let deployed_verifier_solidity = verifier_solidity;
+    function encodeNullifier(address vk, uint256 signal, uint256 root, uint256 nullifierHash, bytes calldata proof) external pure returns (bytes memory) {
+        return encode(vk, PublicInputs(signal, root, nullifierHash), proof);
+    }
+",
+        1,
+    );
 
-    for k in C::min_k()..C::min_k() + 4 {
-        let (params, vk, instances, proof) =
-            halo2::create_testdata_bdfg21::<C>(k, acc_encoding, std_rng());
-        let generator = SolidityGenerator::new(&params, &vk, Bdfg21, instances.len())
-            .set_acc_encoding(acc_encoding);
+    let mut evm = Evm::default();
+    let verifier_address = evm.create(compile_solidity(generator.render().unwrap()));
+    let harness_address = evm.create(compile_solidity(harness_solidity));
 
-        let (verifier_solidity, vk_solidity) = generator.render_separately().unwrap();
-        assert_eq!(deployed_verifier_solidity, verifier_solidity);
+    let mut encode_on_chain = |vk_address: Option<[u8; 20]>| {
+        let mut calldata =
+            encode_nullifier_calldata(vk_address, signal, root, nullifier_hash, &proof);
+        calldata[..4].copy_from_slice(if vk_address.is_some() {
+            &FN_SIG_ENCODE_NULLIFIER_WITH_VK_ADDRESS
+        } else {
+            &FN_SIG_ENCODE_NULLIFIER
+        });
+        let (_, output) = evm.call(harness_address, calldata);
+        let len = U256::from_be_slice(&output[0x20..0x40]).to::<usize>();
+        output[0x40..0x40 + len].to_vec()
+    };
+    let calldata = encode_on_chain(None);
+    let calldata_with_vk_address = encode_on_chain(Some([1; 20]));
 
-        let vk_creation_code = compile_solidity(&vk_solidity);
-        let vk_address = evm.create(vk_creation_code);
+    assert_eq!(
+        calldata,
+        encode_nullifier_calldata(None, signal, root, nullifier_hash, &proof)
+    );
+    assert_eq!(
+        calldata_with_vk_address,
+        encode_nullifier_calldata(Some([1; 20]), signal, root, nullifier_hash, &proof)
+    );
 
-        let (gas_cost, output) = evm.call(
-            verifier_address,
-            encode_calldata(Some(vk_address.into()), &proof, &instances),
-        );
-        assert_eq!(output, [vec![0; 31], vec![1]].concat());
-        println!("Gas cost: {gas_cost}");
-    }
+    let (_, output) = evm.call(verifier_address, calldata);
+    assert_eq!(output, [vec![0; 31], vec![1]].concat());
 }
 
 fn std_rng() -> impl RngCore + Clone {
@@ -131,7 +3315,10 @@ fn save_generated(verifier: &str, vk: Option<&str>) {
 }
 
 mod halo2 {
-    use crate::{codegen::AccumulatorEncoding, transcript::Keccak256Transcript};
+    use crate::{
+        codegen::AccumulatorEncoding,
+        transcript::{ChallengeReduction, Keccak256Transcript},
+    };
     use halo2_proofs::{
         arithmetic::CurveAffine,
         halo2curves::{
@@ -151,7 +3338,7 @@ mod halo2 {
     use itertools::Itertools;
     use rand::RngCore;
     use ruint::aliases::U256;
-    use std::borrow::Borrow;
+    use std::{borrow::Borrow, iter};
 
     pub trait TestCircuit<F: Field>: Circuit<F> {
         fn min_k() -> u32;
@@ -173,16 +3360,82 @@ mod halo2 {
         Vec<u8>,
     ) {
         let circuit = C::new(acc_encoding, rng.clone());
+        create_testdata_bdfg21_with_circuit(k, circuit, rng)
+    }
+
+    pub fn create_testdata_bdfg21_with_circuit<C: TestCircuit<bn256::Fr>>(
+        k: u32,
+        circuit: C,
+        mut rng: impl RngCore,
+    ) -> (
+        ParamsKZG<bn256::Bn256>,
+        VerifyingKey<bn256::G1Affine>,
+        Vec<bn256::Fr>,
+        Vec<u8>,
+    ) {
         let instances = circuit.instances();
 
         let params = ParamsKZG::<bn256::Bn256>::setup(k, &mut rng);
         let vk = keygen_vk(&params, &circuit).unwrap();
-        let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+        let proof = create_proof_bdfg21(&params, &vk, circuit, &mut rng);
+
+        (params, vk, instances, proof)
+    }
 
+    pub fn create_proof_bdfg21<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        vk: &VerifyingKey<bn256::G1Affine>,
+        circuit: C,
+        rng: impl RngCore,
+    ) -> Vec<u8> {
+        create_proof_bdfg21_with_prefix(params, vk, circuit, None, rng)
+    }
+
+    pub fn create_proof_bdfg21_with_prefix<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        vk: &VerifyingKey<bn256::G1Affine>,
+        circuit: C,
+        prefix: Option<&[u8]>,
+        rng: impl RngCore,
+    ) -> Vec<u8> {
+        create_proof_bdfg21_with_transcript(
+            params,
+            vk,
+            circuit,
+            prefix,
+            ChallengeReduction::default(),
+            rng,
+        )
+    }
+
+    pub fn create_proof_bdfg21_with_challenge_reduction<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        vk: &VerifyingKey<bn256::G1Affine>,
+        circuit: C,
+        challenge_reduction: ChallengeReduction,
+        rng: impl RngCore,
+    ) -> Vec<u8> {
+        create_proof_bdfg21_with_transcript(params, vk, circuit, None, challenge_reduction, rng)
+    }
+
+    fn create_proof_bdfg21_with_transcript<C: TestCircuit<bn256::Fr>>(
+        params: &ParamsKZG<bn256::Bn256>,
+        vk: &VerifyingKey<bn256::G1Affine>,
+        circuit: C,
+        prefix: Option<&[u8]>,
+        challenge_reduction: ChallengeReduction,
+        mut rng: impl RngCore,
+    ) -> Vec<u8> {
+        let instances = circuit.instances();
+        let pk = keygen_pk(params, vk.clone(), &circuit).unwrap();
         let proof = {
-            let mut transcript = Keccak256Transcript::new(Vec::new());
+            let mut transcript = match prefix {
+                Some(prefix) => Keccak256Transcript::new_with_prefix(Vec::new(), prefix),
+                None => Keccak256Transcript::new(Vec::new()),
+            }
+            .set_challenge_reduction(challenge_reduction);
             create_proof::<_, ProverSHPLONK<_>, _, _, _, _>(
-                &params,
+                params,
                 &pk,
                 &[circuit],
                 &[&[&instances]],
@@ -194,18 +3447,22 @@ mod halo2 {
         };
 
         let result = {
-            let mut transcript = Keccak256Transcript::new(proof.as_slice());
+            let mut transcript = match prefix {
+                Some(prefix) => Keccak256Transcript::new_with_prefix(proof.as_slice(), prefix),
+                None => Keccak256Transcript::new(proof.as_slice()),
+            }
+            .set_challenge_reduction(challenge_reduction);
             verify_proof::<_, VerifierSHPLONK<_>, _, _, SingleStrategy<_>>(
-                &params,
+                params,
                 pk.get_vk(),
-                SingleStrategy::new(&params),
+                SingleStrategy::new(params),
                 &[&[&instances]],
                 &mut transcript,
             )
         };
         assert!(result.is_ok());
 
-        (params, vk, instances, proof)
+        proof
     }
 
     fn random_accumulator_limbs<M>(
@@ -231,9 +3488,12 @@ mod halo2 {
                 .is_identity()
         ));
 
-        [lhs, rhs]
+        let limbs = [lhs, rhs]
             .into_iter()
-            .flat_map(|ec_point| ec_point_to_limbs(ec_point, acc_encoding.num_limb_bits))
+            .flat_map(|ec_point| ec_point_to_limbs(ec_point, acc_encoding.num_limb_bits));
+        iter::repeat_with(|| M::Scalar::random(&mut rng))
+            .take(acc_encoding.offset)
+            .chain(limbs)
             .collect()
     }
 
@@ -449,6 +3709,248 @@ mod halo2 {
         }
     }
 
+    pub mod multi_point {
+        use crate::{
+            codegen::AccumulatorEncoding,
+            test::halo2::{random_accumulator_limbs, TestCircuit},
+        };
+        use halo2_proofs::{
+            arithmetic::CurveAffine,
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            halo2curves::{
+                ff::{Field, PrimeField},
+                pairing::MultiMillerLoop,
+            },
+            plonk::{self, Advice, Circuit, Column, ConstraintSystem, Instance, Selector},
+            poly::Rotation,
+        };
+        use itertools::{izip, Itertools};
+        use rand::RngCore;
+        use std::iter;
+
+        /// Circuit which opens a single advice column at many points.
+        #[derive(Clone, Debug, Default)]
+        pub struct MultiPointCircuit<M: MultiMillerLoop>(pub Vec<M::Scalar>);
+
+        impl<M: MultiMillerLoop> TestCircuit<M::Scalar> for MultiPointCircuit<M>
+        where
+            M: MultiMillerLoop,
+            <M::G1Affine as CurveAffine>::Base: PrimeField<Repr = [u8; 0x20]>,
+            M::Scalar: PrimeField<Repr = [u8; 0x20]>,
+        {
+            fn min_k() -> u32 {
+                6
+            }
+
+            fn new(acc_encoding: Option<AccumulatorEncoding>, mut rng: impl RngCore) -> Self {
+                let instances = if let Some(acc_encoding) = acc_encoding {
+                    random_accumulator_limbs::<M>(acc_encoding, rng)
+                } else {
+                    iter::repeat_with(|| M::Scalar::random(&mut rng))
+                        .take(10)
+                        .collect()
+                };
+                Self(instances)
+            }
+
+            fn instances(&self) -> Vec<M::Scalar> {
+                self.0.clone()
+            }
+        }
+
+        impl<M: MultiMillerLoop> Circuit<M::Scalar> for MultiPointCircuit<M>
+        where
+            M::Scalar: PrimeField,
+        {
+            type Config = (Selector, Column<Advice>, Column<Instance>);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<M::Scalar>) -> Self::Config {
+                let selector = meta.selector();
+                let advice = meta.advice_column();
+                let instance = meta.instance_column();
+
+                // Current value equals to the sum of 3 previous and 3 next values, which makes
+                // the advice column opened at 7 different points.
+                meta.create_gate("", |meta| {
+                    let q = meta.query_selector(selector);
+                    let cur = meta.query_advice(advice, Rotation::cur());
+                    let sum = [-3, -2, -1, 1, 2, 3]
+                        .map(|rotation| meta.query_advice(advice, Rotation(rotation)))
+                        .into_iter()
+                        .reduce(|acc, value| acc + value)
+                        .unwrap();
+                    vec![q * (sum - cur)]
+                });
+
+                meta.enable_equality(advice);
+                meta.enable_equality(instance);
+
+                (selector, advice, instance)
+            }
+
+            fn synthesize(
+                &self,
+                (selector, advice, instance): Self::Config,
+                mut layouter: impl Layouter<M::Scalar>,
+            ) -> Result<(), plonk::Error> {
+                let assigneds = layouter.assign_region(
+                    || "",
+                    |mut region| {
+                        for (offset, value) in [1, 2, 3, 12, 1, 2, 3].into_iter().enumerate() {
+                            let value = Value::known(M::Scalar::from(value));
+                            region.assign_advice(|| "", advice, offset, || value)?;
+                        }
+                        selector.enable(&mut region, 3)?;
+                        izip!(7.., &self.0)
+                            .map(|(offset, value)| {
+                                let value = Value::known(*value);
+                                region.assign_advice(|| "", advice, offset, || value)
+                            })
+                            .try_collect::<_, Vec<_>, _>()
+                    },
+                )?;
+                for (idx, assigned) in izip!(0.., assigneds) {
+                    layouter.constrain_instance(assigned.cell(), instance, idx)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub mod selector {
+        use crate::{
+            codegen::AccumulatorEncoding,
+            test::halo2::{random_accumulator_limbs, TestCircuit},
+        };
+        use halo2_proofs::{
+            arithmetic::CurveAffine,
+            circuit::{Layouter, SimpleFloorPlanner, Value},
+            halo2curves::{
+                ff::{Field, PrimeField},
+                pairing::MultiMillerLoop,
+            },
+            plonk::{self, Advice, Circuit, Column, ConstraintSystem, Instance, Selector},
+            poly::Rotation,
+        };
+        use itertools::{izip, Itertools};
+        use rand::RngCore;
+        use std::{iter, marker::PhantomData};
+
+        /// Circuit with 4 selectors never enabled on the same row, which are simple selectors
+        /// combined into a single fixed column by selector compression at keygen if `COMPRESS`,
+        /// otherwise complex selectors kept as 4 fixed columns as if keygen skips compression.
+        #[derive(Clone, Debug, Default)]
+        pub struct SelectorCircuit<M: MultiMillerLoop, const COMPRESS: bool>(
+            pub Vec<M::Scalar>,
+            PhantomData<M>,
+        );
+
+        impl<M: MultiMillerLoop, const COMPRESS: bool> TestCircuit<M::Scalar>
+            for SelectorCircuit<M, COMPRESS>
+        where
+            M: MultiMillerLoop,
+            <M::G1Affine as CurveAffine>::Base: PrimeField<Repr = [u8; 0x20]>,
+            M::Scalar: PrimeField<Repr = [u8; 0x20]>,
+        {
+            fn min_k() -> u32 {
+                6
+            }
+
+            fn new(acc_encoding: Option<AccumulatorEncoding>, mut rng: impl RngCore) -> Self {
+                let instances = if let Some(acc_encoding) = acc_encoding {
+                    random_accumulator_limbs::<M>(acc_encoding, rng)
+                } else {
+                    iter::repeat_with(|| M::Scalar::random(&mut rng))
+                        .take(10)
+                        .collect()
+                };
+                Self(instances, PhantomData)
+            }
+
+            fn instances(&self) -> Vec<M::Scalar> {
+                self.0.clone()
+            }
+        }
+
+        impl<M: MultiMillerLoop, const COMPRESS: bool> Circuit<M::Scalar> for SelectorCircuit<M, COMPRESS>
+        where
+            M::Scalar: PrimeField,
+        {
+            type Config = ([Selector; 4], Column<Advice>, Column<Instance>);
+            type FloorPlanner = SimpleFloorPlanner;
+            #[cfg(feature = "halo2_circuit_params")]
+            type Params = ();
+
+            fn without_witnesses(&self) -> Self {
+                unimplemented!()
+            }
+
+            fn configure(meta: &mut ConstraintSystem<M::Scalar>) -> Self::Config {
+                let selectors = [(); 4].map(|_| {
+                    if COMPRESS {
+                        meta.selector()
+                    } else {
+                        meta.complex_selector()
+                    }
+                });
+                let advice = meta.advice_column();
+                let instance = meta.instance_column();
+
+                // Next value equals to current value plus `idx + 1` where `selectors[idx]` is
+                // enabled.
+                for (idx, selector) in izip!(1.., selectors) {
+                    meta.create_gate("", |meta| {
+                        let q = meta.query_selector(selector);
+                        let cur = meta.query_advice(advice, Rotation::cur());
+                        let next = meta.query_advice(advice, Rotation::next());
+                        vec![q * (next - cur - plonk::Expression::Constant(M::Scalar::from(idx)))]
+                    });
+                }
+
+                meta.enable_equality(advice);
+                meta.enable_equality(instance);
+
+                (selectors, advice, instance)
+            }
+
+            fn synthesize(
+                &self,
+                (selectors, advice, instance): Self::Config,
+                mut layouter: impl Layouter<M::Scalar>,
+            ) -> Result<(), plonk::Error> {
+                let assigneds = layouter.assign_region(
+                    || "",
+                    |mut region| {
+                        for (offset, value) in [0, 1, 3, 6, 10].into_iter().enumerate() {
+                            let value = Value::known(M::Scalar::from(value));
+                            region.assign_advice(|| "", advice, offset, || value)?;
+                        }
+                        for (offset, selector) in selectors.iter().enumerate() {
+                            selector.enable(&mut region, offset)?;
+                        }
+                        izip!(5.., &self.0)
+                            .map(|(offset, value)| {
+                                let value = Value::known(*value);
+                                region.assign_advice(|| "", advice, offset, || value)
+                            })
+                            .try_collect::<_, Vec<_>, _>()
+                    },
+                )?;
+                for (idx, assigned) in izip!(0.., assigneds) {
+                    layouter.constrain_instance(assigned.cell(), instance, idx)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
     pub mod maingate {
         use crate::{
             codegen::AccumulatorEncoding,
@@ -15,22 +15,88 @@ use std::{
 };
 
 /// Transcript using Keccak256 as hash function in Fiat-Shamir transformation.
+///
+/// Proof bytes returned by `finalize` are exactly what `Halo2Verifier` expects as `proof`
+/// argument, without any re-encoding:
+/// - EC point is written as `x` then `y`, each coordinate in 32 bytes big-endian.
+/// - Scalar is written in 32 bytes big-endian. Packing into 31 bytes is not supported since BN254
+///   scalar field element takes up to 254 bits.
+/// - Fields are written in the order of [`SolidityGenerator::proof_schema`].
+///
+/// Challenges are derived in the same sequence as `Halo2Verifier` hashes memory:
+/// - Absorbing appends the 32 bytes big-endian encoding of each scalar, or of `x` and `y` of
+///   each EC point, to a buffer.
+/// - Squeezing hashes the buffer by `keccak256`, with a trailing byte `0x01` if nothing was
+///   absorbed since last squeeze, then resets the buffer to the hash so the next round chains on
+///   it, and maps the hash to challenge by [`ChallengeReduction`].
+///
+/// [`SolidityGenerator::proof_schema`]: crate::SolidityGenerator::proof_schema
 #[derive(Debug, Default)]
 pub struct Keccak256Transcript<C, S> {
     stream: S,
     buf: Vec<u8>,
+    challenge_reduction: ChallengeReduction,
     _marker: PhantomData<C>,
 }
 
+/// How a squeezed `keccak256` hash is mapped to a challenge in scalar field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChallengeReduction {
+    /// Hash as 32 bytes big-endian integer reduced by scalar field modulus `r`.
+    #[default]
+    Mod,
+    /// Concatenation `hash || keccak256(hash)` as 64 bytes big-endian integer reduced by scalar
+    /// field modulus `r`, which has negligible bias compared to [`ChallengeReduction::Mod`].
+    Wide,
+}
+
+impl ChallengeReduction {
+    /// Return challenge reduced from squeezed `hash`.
+    pub fn reduce<F>(&self, hash: [u8; 0x20]) -> F
+    where
+        F: PrimeField<Repr = [u8; 0x20]>,
+    {
+        let hi = u256_to_fe::<F>(U256::from_be_bytes(hash));
+        match self {
+            ChallengeReduction::Mod => hi,
+            ChallengeReduction::Wide => {
+                let lo =
+                    u256_to_fe::<F>(U256::from_be_bytes::<0x20>(Keccak256::digest(hash).into()));
+                let two_to_256 = u256_to_fe::<F>(U256::MAX) + F::ONE;
+                hi * two_to_256 + lo
+            }
+        }
+    }
+}
+
 impl<C, S> Keccak256Transcript<C, S> {
     /// Return a `Keccak256Transcript` with empty buffer.
     pub fn new(stream: S) -> Self {
         Self {
             stream,
             buf: Vec::new(),
+            challenge_reduction: ChallengeReduction::default(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Return a `Keccak256Transcript` with `keccak256(prefix)` absorbed, which matches
+    /// `Halo2Verifier` generated with `SolidityGenerator::set_transcript_prefix`.
+    pub fn new_with_prefix(stream: S, prefix: &[u8]) -> Self {
+        Self {
+            stream,
+            buf: Keccak256::digest(prefix).to_vec(),
+            challenge_reduction: ChallengeReduction::default(),
             _marker: PhantomData,
         }
     }
+
+    /// Set `ChallengeReduction`, which should match `Halo2Verifier` generated with
+    /// `SolidityGenerator::set_challenge_reduction`.
+    pub fn set_challenge_reduction(mut self, challenge_reduction: ChallengeReduction) -> Self {
+        self.challenge_reduction = challenge_reduction;
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -69,7 +135,7 @@ where
         .collect_vec();
         let hash: [u8; 0x20] = Keccak256::digest(data).into();
         self.buf = hash.to_vec();
-        ChallengeEvm::new(&hash)
+        ChallengeEvm(self.challenge_reduction.reduce(hash))
     }
 
     fn common_point(&mut self, ec_point: C) -> io::Result<()> {
@@ -184,7 +250,7 @@ where
     F::from_repr(value.to_le_bytes::<0x20>()).unwrap()
 }
 
-fn modulus<F>() -> U256
+pub(crate) fn modulus<F>() -> U256
 where
     F: PrimeField<Repr = [u8; 0x20]>,
 {
@@ -1,545 +1,856 @@
-// SPDX-License-Identifier: MIT
-
-pragma solidity ^0.8.0;
-
-contract Halo2Verifier {
-    uint256 internal constant    PROOF_LEN_CPTR = {{ proof_cptr - 1 }};
-    uint256 internal constant        PROOF_CPTR = {{ proof_cptr }};
-    uint256 internal constant NUM_INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) }};
-    uint256 internal constant     INSTANCE_CPTR = {{ proof_cptr + (proof_len / 32) + 1 }};
-
-    uint256 internal constant FIRST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr }};
-    uint256 internal constant  LAST_QUOTIENT_X_CPTR = {{ quotient_comm_cptr + 2 * (num_quotients - 1) }};
-
-    uint256 internal constant                VK_MPTR = {{ vk_mptr }};
-    uint256 internal constant         VK_DIGEST_MPTR = {{ vk_mptr }};
-    uint256 internal constant                 K_MPTR = {{ vk_mptr + 1 }};
-    uint256 internal constant             N_INV_MPTR = {{ vk_mptr + 2 }};
-    uint256 internal constant             OMEGA_MPTR = {{ vk_mptr + 3 }};
-    uint256 internal constant         OMEGA_INV_MPTR = {{ vk_mptr + 4 }};
-    uint256 internal constant    OMEGA_INV_TO_L_MPTR = {{ vk_mptr + 5 }};
-    uint256 internal constant     NUM_INSTANCES_MPTR = {{ vk_mptr + 6 }};
-    uint256 internal constant   HAS_ACCUMULATOR_MPTR = {{ vk_mptr + 7 }};
-    uint256 internal constant        ACC_OFFSET_MPTR = {{ vk_mptr + 8 }};
-    uint256 internal constant     NUM_ACC_LIMBS_MPTR = {{ vk_mptr + 9 }};
-    uint256 internal constant NUM_ACC_LIMB_BITS_MPTR = {{ vk_mptr + 10 }};
-    uint256 internal constant              G1_X_MPTR = {{ vk_mptr + 11 }};
-    uint256 internal constant              G1_Y_MPTR = {{ vk_mptr + 12 }};
-    uint256 internal constant            G2_X_1_MPTR = {{ vk_mptr + 13 }};
-    uint256 internal constant            G2_X_2_MPTR = {{ vk_mptr + 14 }};
-    uint256 internal constant            G2_Y_1_MPTR = {{ vk_mptr + 15 }};
-    uint256 internal constant            G2_Y_2_MPTR = {{ vk_mptr + 16 }};
-    uint256 internal constant      NEG_S_G2_X_1_MPTR = {{ vk_mptr + 17 }};
-    uint256 internal constant      NEG_S_G2_X_2_MPTR = {{ vk_mptr + 18 }};
-    uint256 internal constant      NEG_S_G2_Y_1_MPTR = {{ vk_mptr + 19 }};
-    uint256 internal constant      NEG_S_G2_Y_2_MPTR = {{ vk_mptr + 20 }};
-
-    uint256 internal constant CHALLENGE_MPTR = {{ challenge_mptr }};
-
-    uint256 internal constant THETA_MPTR = {{ theta_mptr }};
-    uint256 internal constant  BETA_MPTR = {{ theta_mptr + 1 }};
-    uint256 internal constant GAMMA_MPTR = {{ theta_mptr + 2 }};
-    uint256 internal constant     Y_MPTR = {{ theta_mptr + 3 }};
-    uint256 internal constant     X_MPTR = {{ theta_mptr + 4 }};
-    {%- match scheme %}
-    {%- when Bdfg21 %}
-    uint256 internal constant  ZETA_MPTR = {{ theta_mptr + 5 }};
-    uint256 internal constant    NU_MPTR = {{ theta_mptr + 6 }};
-    uint256 internal constant    MU_MPTR = {{ theta_mptr + 7 }};
-    {%- when Gwc19 %}
-    // TODO
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+contract {{ contract_name }} {
+{% include "Halo2VerifierConstants.sol" %}
+    {%- match vk %}
+    {%- when Some with (vk) %}
+    {%- match vk_source %}
+    {%- when BytesConstant %}
+
+    bytes internal constant VK_DATA =
+        {%- for (name, chunk) in vk.constants %}
+        hex"{{ chunk|hex_word }}" // {{ name }}
+        {%- endfor %}
+        {%- for (x, y) in vk.fixed_comms %}
+        hex"{{ x|hex_word }}" // fixed_comms[{{ loop.index0 }}].x
+        hex"{{ y|hex_word }}" // fixed_comms[{{ loop.index0 }}].y
+        {%- endfor %}
+        {%- for (x, y) in vk.permutation_comms %}
+        hex"{{ x|hex_word }}" // permutation_comms[{{ loop.index0 }}].x
+        hex"{{ y|hex_word }}" // permutation_comms[{{ loop.index0 }}].y
+        {%- endfor %}
+        ;
+    {%- when Inline %}
+    {%- when VkSource::Constructor %}
+
+    uint256[{{ vk_len / 32 }}] internal vk_words;
     {%- endmatch %}
-
-    uint256 internal constant       ACC_LHS_X_MPTR = {{ theta_mptr + 8 }};
-    uint256 internal constant       ACC_LHS_Y_MPTR = {{ theta_mptr + 9 }};
-    uint256 internal constant       ACC_RHS_X_MPTR = {{ theta_mptr + 10 }};
-    uint256 internal constant       ACC_RHS_Y_MPTR = {{ theta_mptr + 11 }};
-    uint256 internal constant             X_N_MPTR = {{ theta_mptr + 12 }};
-    uint256 internal constant X_N_MINUS_1_INV_MPTR = {{ theta_mptr + 13 }};
-    uint256 internal constant          L_LAST_MPTR = {{ theta_mptr + 14 }};
-    uint256 internal constant         L_BLIND_MPTR = {{ theta_mptr + 15 }};
-    uint256 internal constant             L_0_MPTR = {{ theta_mptr + 16 }};
-    uint256 internal constant   INSTANCE_EVAL_MPTR = {{ theta_mptr + 17 }};
-    uint256 internal constant   QUOTIENT_EVAL_MPTR = {{ theta_mptr + 18 }};
-    uint256 internal constant      QUOTIENT_X_MPTR = {{ theta_mptr + 19 }};
-    uint256 internal constant      QUOTIENT_Y_MPTR = {{ theta_mptr + 20 }};
-    uint256 internal constant          R_EVAL_MPTR = {{ theta_mptr + 21 }};
-    uint256 internal constant   PAIRING_LHS_X_MPTR = {{ theta_mptr + 22 }};
-    uint256 internal constant   PAIRING_LHS_Y_MPTR = {{ theta_mptr + 23 }};
-    uint256 internal constant   PAIRING_RHS_X_MPTR = {{ theta_mptr + 24 }};
-    uint256 internal constant   PAIRING_RHS_Y_MPTR = {{ theta_mptr + 25 }};
-
-    function verifyProof(
+    {%- when None %}
+    {%- endmatch %}
+    {%- if deciding_key_source == DecidingKeySource::Constructor %}
+
+    uint256 internal immutable G2_X_1;
+    uint256 internal immutable G2_X_2;
+    uint256 internal immutable G2_Y_1;
+    uint256 internal immutable G2_Y_2;
+    uint256 internal immutable NEG_S_G2_X_1;
+    uint256 internal immutable NEG_S_G2_X_2;
+    uint256 internal immutable NEG_S_G2_Y_1;
+    uint256 internal immutable NEG_S_G2_Y_2;
+    {%- endif %}
+    {%- if allowlist_index.is_some() %}
+
+    bytes32 public immutable ALLOWLIST_ROOT;
+    {%- endif %}
+    {%- if (vk.is_some() && vk_source == VkSource::Constructor) || deciding_key_source == DecidingKeySource::Constructor || allowlist_index.is_some() || deployment_check %}
+
+    constructor(
+        {%- if vk.is_some() && vk_source == VkSource::Constructor %}
+        uint256[] memory vk{% if deciding_key_source == DecidingKeySource::Constructor || allowlist_index.is_some() %},{% endif %}
+        {%- endif %}
+        {%- if deciding_key_source == DecidingKeySource::Constructor %}
+        uint256[4] memory g2,
+        uint256[4] memory s_g2{% if allowlist_index.is_some() %},{% endif %}
+        {%- endif %}
+        {%- if allowlist_index.is_some() %}
+        bytes32 allowlist_root
+        {%- endif %}
+    ) {
         {%- match vk %}
         {%- when Some with (vk) %}
+        {%- if vk_source == VkSource::Constructor %}
+        // Verifying key is given word by word as:
+        {%- for (name, _) in vk.words() %}
+        // - vk[{{ loop.index0 }}]: {{ name }}
+        {%- endfor %}
+        if (vk.length != {{ vk_len / 32 }}) revert InvalidVerifyingKey();
+        for (uint256 i; i < {{ vk_len / 32 }}; ++i) {
+            vk_words[i] = vk[i];
+        }
+        {%- endif %}
         {%- when None %}
-        address vk,
         {%- endmatch %}
-        bytes calldata proof,
-        uint256[] calldata instances
-    ) public returns (bool) {
+        {%- if allowlist_index.is_some() %}
+        // Root of merkle tree with sorted pair hashing, whose leaves are
+        // `keccak256(abi.encodePacked(instance))`
+        ALLOWLIST_ROOT = allowlist_root;
+        {%- endif %}
+        {%- if deciding_key_source == DecidingKeySource::Constructor %}
+        // Both `g2` and `s_g2` are given as (x_1, x_2, y_1, y_2), where each coordinate in Fq2 is
+        // c_1 * u + c_2 with u^2 = -1, in the same order as pairing precompile expects
+        uint256 q = 21888242871839275222246405745257275088696311157297823662689037894645226208583;
+
+        G2_X_1 = g2[0];
+        G2_X_2 = g2[1];
+        G2_Y_1 = g2[2];
+        G2_Y_2 = g2[3];
+        NEG_S_G2_X_1 = s_g2[0];
+        NEG_S_G2_X_2 = s_g2[1];
+        NEG_S_G2_Y_1 = (q - s_g2[2]) % q;
+        NEG_S_G2_Y_2 = (q - s_g2[3]) % q;
+
+        // Check e(g1, g2) * e(-g1, g2) * e(g1, s_g2) * e(-g1, s_g2) == 1, which fails if any
+        // given point is not on curve or not in subgroup
+        bytes memory input = abi.encodePacked(
+            uint256(1), uint256(2), g2,
+            uint256(1), q - 2, g2,
+            uint256(1), uint256(2), s_g2,
+            uint256(1), q - 2, s_g2
+        );
+        (bool success, bytes memory output) = address(0x08).staticcall(input);
+        require(success && abi.decode(output, (bool)));
+        {%- endif %}
+        {%- if deployment_check %}
+        {%- match vk %}
+        {%- when Some with (vk) %}
+
+        // Check embedded G1 points are on curve or identity, and embedded G2 points are on curve
+        // and in subgroup, to revert deployment with corrupted constants
         assembly {
-            // Read EC point (x, y) at (proof_cptr, proof_cptr + 0x20),
-            // and check if the point is on affine plane,
-            // and store them in (hash_mptr, hash_mptr + 0x20).
-            // Return updated (success, proof_cptr, hash_mptr).
-            function read_ec_point(success, proof_cptr, hash_mptr, q) -> ret0, ret1, ret2 {
-                let x := calldataload(proof_cptr)
-                let y := calldataload(add(proof_cptr, 0x20))
-                ret0 := and(success, lt(x, q))
-                ret0 := and(ret0, lt(y, q))
-                ret0 := and(ret0, eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q)))
-                mstore(hash_mptr, x)
-                mstore(add(hash_mptr, 0x20), y)
-                ret1 := add(proof_cptr, 0x40)
-                ret2 := add(hash_mptr, 0x40)
+            let q_mod := 21888242871839275222246405745257275088696311157297823662689037894645226208583
+            let valid := 1
+            {%- for (x, y) in vk.g1_points() %}
+            {
+                let x := {{ x|hex_padded(64) }}
+                let y := {{ y|hex_padded(64) }}
+                let on_curve := and(and(lt(x, q_mod), lt(y, q_mod)), eq(mulmod(y, y, q_mod), addmod(mulmod(x, mulmod(x, x, q_mod), q_mod), 3, q_mod)))
+                valid := and(valid, or(on_curve, and(iszero(x), iszero(y))))
             }
-
-            // Squeeze challenge by keccak256(memory[0..hash_mptr]),
-            // and store hash mod r as challenge in challenge_mptr,
-            // and push back hash in 0x00 as the first input for next squeeze.
-            // Return updated (challenge_mptr, hash_mptr).
-            function squeeze_challenge(challenge_mptr, hash_mptr, r) -> ret0, ret1 {
-                let hash := keccak256(0x00, hash_mptr)
-                mstore(challenge_mptr, mod(hash, r))
-                mstore(0x00, hash)
-                ret0 := add(challenge_mptr, 0x20)
-                ret1 := 0x20
+            {%- endfor %}
+            let ptr := mload(0x40)
+            {%- for word in vk.g2_check_pairing_input() %}
+            mstore(add(ptr, {{ loop.index0 * 32 }}), {{ word|hex_padded(64) }})
+            {%- endfor %}
+            valid := and(valid, staticcall(gas(), 0x08, ptr, 0x300, ptr, 0x20))
+            valid := and(valid, eq(mload(ptr), 1))
+            if iszero(valid) {
+                revert(0, 0)
             }
+        }
+        {%- when None %}
+        {%- endmatch %}
+        {%- endif %}
+    }
+    {%- endif %}
+    {%- match domain %}
+    {%- when Some with (domain) %}
+
+    // Domain size the verifying key is generated for
+    uint256 public constant K = {{ domain.0 }};
+    uint256 public constant N = {{ domain.1 }};
+    {%- when None %}
+    {%- endmatch %}
+    {%- if vk.is_some() && vk_source == VkSource::Constructor %}
 
-            // Squeeze challenge without absorbing new input from calldata,
-            // by putting an extra 0x01 in memory[0x20] and squeeze by keccak256(memory[0..21]),
-            // and store hash mod r as challenge in challenge_mptr,
-            // and push back hash in 0x00 as the first input for next squeeze.
-            // Return updated (challenge_mptr).
-            function squeeze_challenge_cont(challenge_mptr, r) -> ret {
-                mstore8(0x20, 0x01)
-                let hash := keccak256(0x00, 0x21)
-                mstore(challenge_mptr, mod(hash, r))
-                mstore(0x00, hash)
-                ret := add(challenge_mptr, 0x20)
-            }
+    error InvalidVerifyingKey();
+    {%- endif %}
+    {%- if !instance_bit_widths.is_empty() %}
 
-            // Batch invert values in memory[mptr_start..mptr_end] in place.
-            // Return updated (success).
-            function batch_invert(success, mptr_start, mptr_end, r) -> ret {
-                let gp_mptr := mptr_end
-                let gp := mload(mptr_start)
-                let mptr := add(mptr_start, 0x20)
-                for
-                    {}
-                    lt(mptr, sub(mptr_end, 0x20))
-                    {}
-                {
-                    gp := mulmod(gp, mload(mptr), r)
-                    mstore(gp_mptr, gp)
-                    mptr := add(mptr, 0x20)
-                    gp_mptr := add(gp_mptr, 0x20)
-                }
-                gp := mulmod(gp, mload(mptr), r)
-
-                mstore(gp_mptr, 0x20)
-                mstore(add(gp_mptr, 0x20), 0x20)
-                mstore(add(gp_mptr, 0x40), 0x20)
-                mstore(add(gp_mptr, 0x60), gp)
-                mstore(add(gp_mptr, 0x80), sub(r, 2))
-                mstore(add(gp_mptr, 0xa0), r)
-                ret := and(success, staticcall(gas(), 0x05, gp_mptr, 0xc0, gp_mptr, 0x20))
-                let all_inv := mload(gp_mptr)
-
-                let first_mptr := mptr_start
-                let second_mptr := add(first_mptr, 0x20)
-                gp_mptr := sub(gp_mptr, 0x20)
-                for
-                    {}
-                    lt(second_mptr, mptr)
-                    {}
-                {
-                    let inv := mulmod(all_inv, mload(gp_mptr), r)
-                    all_inv := mulmod(all_inv, mload(mptr), r)
-                    mstore(mptr, inv)
-                    mptr := sub(mptr, 0x20)
-                    gp_mptr := sub(gp_mptr, 0x20)
-                }
-                let inv_first := mulmod(all_inv, mload(second_mptr), r)
-                let inv_second := mulmod(all_inv, mload(first_mptr), r)
-                mstore(first_mptr, inv_first)
-                mstore(second_mptr, inv_second)
-            }
+    error InstanceOutOfRange(uint256 index);
+    {%- endif %}
+    {%- if calldata_bounds_check %}
 
-            // Add (x, y) into point at (0x00, 0x20).
-            // Return updated (success).
-            function ec_add_acc(success, x, y) -> ret {
-                mstore(0x40, x)
-                mstore(0x60, y)
-                ret := and(success, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
-            }
+    error CalldataOutOfBounds();
+    {%- endif %}
+    {%- if non_canonical_point_check %}
 
-            // Scale point at (0x00, 0x20) by scalar.
-            function ec_mul_acc(success, scalar) -> ret {
-                mstore(0x40, scalar)
-                ret := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
-            }
+    error NonCanonicalPoint();
+    {%- endif %}
+    {%- if pairing_failure_context %}
 
-            // Add (x, y) into point at (0x80, 0xa0).
-            // Return updated (success).
-            function ec_add_tmp(success, x, y) -> ret {
-                mstore(0xc0, x)
-                mstore(0xe0, y)
-                ret := and(success, staticcall(gas(), 0x06, 0x80, 0x80, 0x80, 0x40))
-            }
+    error PairingFailed(uint256 theta);
+    {%- endif %}
+    {%- if debug_errors %}
 
-            // Scale point at (0x80, 0xa0) by scalar.
-            // Return updated (success).
-            function ec_mul_tmp(success, scalar) -> ret {
-                mstore(0xc0, scalar)
-                ret := and(success, staticcall(gas(), 0x07, 0x80, 0x60, 0x80, 0x40))
-            }
+    error TranscriptMismatch();
+    error OpeningFailed();
+    {%- endif %}
+    {%- if return_mode == ReturnMode::Revert %}
 
-            // Perform pairing check.
-            // Return updated (success).
-            function ec_pairing(success, lhs_x, lhs_y, rhs_x, rhs_y) -> ret {
-                mstore(0x00, lhs_x)
-                mstore(0x20, lhs_y)
-                mstore(0x40, mload(G2_X_1_MPTR))
-                mstore(0x60, mload(G2_X_2_MPTR))
-                mstore(0x80, mload(G2_Y_1_MPTR))
-                mstore(0xa0, mload(G2_Y_2_MPTR))
-                mstore(0xc0, rhs_x)
-                mstore(0xe0, rhs_y)
-                mstore(0x100, mload(NEG_S_G2_X_1_MPTR))
-                mstore(0x120, mload(NEG_S_G2_X_2_MPTR))
-                mstore(0x140, mload(NEG_S_G2_Y_1_MPTR))
-                mstore(0x160, mload(NEG_S_G2_Y_2_MPTR))
-                ret := and(success, staticcall(gas(), 0x08, 0x00, 0x180, 0x00, 0x20))
-                ret := and(ret, mload(0x00))
-            }
+    error InvalidProof();
+    {%- endif %}
+    {%- if stored_accumulator_index.is_some() || inputs_root_index.is_some() || upgradeable %}
 
-            // Modulus
-            let q := 21888242871839275222246405745257275088696311157297823662689037894645226208583 // BN254 base field
-            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617 // BN254 scalar field
+    error Unauthorized();
+    {%- endif %}
+    {%- if stored_accumulator_index.is_some() %}
 
-            // Initialize success as true
-            let success := true
+    // Accumulator commitment that the bound instance is checked against
+    uint256 public accumulator;
+    address public immutable ACCUMULATOR_UPDATER = msg.sender;
 
-            {
-                {%- match vk %}
-                {%- when Some with (vk) %}
-                // Load vk into memory
-                {%- for (name, chunk) in vk.constants %}
-                mstore({{ vk_mptr + loop.index0 }}, {{ chunk|hex_padded(64) }}) // {{ name }}
-                {%- endfor %}
-                {%- for (x, y) in vk.fixed_comms %}
-                {%- let offset = vk.constants.len() %}
-                mstore({{ vk_mptr + offset + 2 * loop.index0 }}, {{ x|hex_padded(64) }}) // fixed_comms[{{ loop.index0 }}].x
-                mstore({{ vk_mptr + offset + 2 * loop.index0 + 1 }}, {{ y|hex_padded(64) }}) // fixed_comms[{{ loop.index0 }}].y
-                {%- endfor %}
-                {%- for (x, y) in vk.permutation_comms %}
-                {%- let offset = vk.constants.len() + 2 * vk.fixed_comms.len() %}
-                mstore({{ vk_mptr + offset + 2 * loop.index0 }}, {{ x|hex_padded(64) }}) // permutation_comms[{{ loop.index0 }}].x
-                mstore({{ vk_mptr + offset + 2 * loop.index0 + 1 }}, {{ y|hex_padded(64) }}) // permutation_comms[{{ loop.index0 }}].y
-                {%- endfor %}
-                {%- when None %}
-                // Copy vk into memory
-                extcodecopy(vk, VK_MPTR, 0x00, {{ vk_len|hex() }})
-                {%- endmatch %}
-
-                // Check valid length of proof
-                success := and(success, eq({{ proof_len|hex() }}, calldataload(PROOF_LEN_CPTR)))
-
-                // Check valid length of instances
-                let num_instances := mload(NUM_INSTANCES_MPTR)
-                success := and(success, eq(num_instances, calldataload(NUM_INSTANCE_CPTR)))
-
-                // Absorb vk diegst
-                mstore(0x00, mload(VK_DIGEST_MPTR))
-
-                // Read instances and witness commitments and generate challenges
-                let hash_mptr := 0x20
-                let instance_cptr := INSTANCE_CPTR
-                for
-                    { let instance_cptr_end := add(instance_cptr, mul(0x20, num_instances)) }
-                    lt(instance_cptr, instance_cptr_end)
-                    {}
-                {
-                    let instance := calldataload(instance_cptr)
-                    success := and(success, lt(instance, r))
-                    mstore(hash_mptr, instance)
-                    instance_cptr := add(instance_cptr, 0x20)
-                    hash_mptr := add(hash_mptr, 0x20)
-                }
+    error StaleAccumulator();
 
-                let proof_cptr := PROOF_CPTR
-                let challenge_mptr := CHALLENGE_MPTR
-                {%- for num_advices in num_advices %}
-                {%- let num_challenges = num_challenges[loop.index0] %}
-
-                // Phase {{ loop.index }}
-                for
-                    { let proof_cptr_end := add(proof_cptr, {{ (2 * 32 * num_advices)|hex() }}) }
-                    lt(proof_cptr, proof_cptr_end)
-                    {}
-                {
-                    success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q)
-                }
+    function updateAccumulator(uint256 new_accumulator) public {
+        if (msg.sender != ACCUMULATOR_UPDATER) revert Unauthorized();
+        accumulator = new_accumulator;
+    }
+    {%- endif %}
+    {%- if upgradeable %}
 
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)
-                {%- for _ in 0..num_challenges - 1 %}
-                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)
-                {%- endfor %}
-                {%- endfor %}
-
-                // Read evaluations
-                for
-                    { let proof_cptr_end := add(proof_cptr, {{ (32 * num_evals)|hex() }}) }
-                    lt(proof_cptr, proof_cptr_end)
-                    {}
-                {
-                    let eval := calldataload(proof_cptr)
-                    success := and(success, lt(eval, r))
-                    mstore(hash_mptr, eval)
-                    proof_cptr := add(proof_cptr, 0x20)
-                    hash_mptr := add(hash_mptr, 0x20)
-                }
+    // ERC-1967 slot `uint256(keccak256("eip1967.proxy.implementation")) - 1` in proxy storage
+    uint256 internal constant IMPLEMENTATION_SLOT = 0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc;
 
-                // Read batch opening proof and generate challenges
-                {%- match scheme %}
-                {%- when Bdfg21 %}
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // zeta
-                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)                        // nu
-
-                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W
-
-                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // mu
-
-                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W'
-                {%- when Gwc19 %}
-                // TODO
-                {%- endmatch %}
-
-                // Read accumulator from instances
-                if mload(HAS_ACCUMULATOR_MPTR) {
-                    let num_limbs := mload(NUM_ACC_LIMBS_MPTR)
-                    let num_limb_bits := mload(NUM_ACC_LIMB_BITS_MPTR)
-
-                    let cptr := add(INSTANCE_CPTR, mul(mload(ACC_OFFSET_MPTR), 0x20))
-                    let lhs_y_off := mul(num_limbs, 0x20)
-                    let rhs_x_off := mul(lhs_y_off, 2)
-                    let rhs_y_off := mul(lhs_y_off, 3)
-                    let lhs_x := calldataload(cptr)
-                    let lhs_y := calldataload(add(cptr, lhs_y_off))
-                    let rhs_x := calldataload(add(cptr, rhs_x_off))
-                    let rhs_y := calldataload(add(cptr, rhs_y_off))
-                    for
-                        {
-                            let cptr_end := add(cptr, mul(0x20, num_limbs))
-                            let shift := num_limb_bits
-                        }
-                        lt(cptr, cptr_end)
-                        {}
-                    {
-                        cptr := add(cptr, 0x20)
-                        lhs_x := add(lhs_x, shl(shift, calldataload(cptr)))
-                        lhs_y := add(lhs_y, shl(shift, calldataload(add(cptr, lhs_y_off))))
-                        rhs_x := add(rhs_x, shl(shift, calldataload(add(cptr, rhs_x_off))))
-                        rhs_y := add(rhs_y, shl(shift, calldataload(add(cptr, rhs_y_off))))
-                        shift := add(shift, num_limb_bits)
-                    }
-
-                    success := and(success, eq(mulmod(lhs_y, lhs_y, q), addmod(mulmod(lhs_x, mulmod(lhs_x, lhs_x, q), q), 3, q)))
-                    success := and(success, eq(mulmod(rhs_y, rhs_y, q), addmod(mulmod(rhs_x, mulmod(rhs_x, rhs_x, q), q), 3, q)))
-
-                    mstore(ACC_LHS_X_MPTR, lhs_x)
-                    mstore(ACC_LHS_Y_MPTR, lhs_y)
-                    mstore(ACC_RHS_X_MPTR, rhs_x)
-                    mstore(ACC_RHS_Y_MPTR, rhs_y)
-                }
+    address private immutable SELF = address(this);
+    // Deployer of this implementation, who is the only one allowed to upgrade proxy away from it.
+    // It's in code instead of proxy storage, so there is nothing to initialize or front-run.
+    address public immutable ADMIN = msg.sender;
 
-                pop(q)
-            }
+    error InvalidImplementation();
 
-            // Revert earlier if anything from calldata is invalid
-            if iszero(success) {
-                revert(0, 0)
-            }
+    event Upgraded(address indexed implementation);
 
-            // Compute lagrange evaluations and instance evaluation
-            {
-                let k := mload(K_MPTR)
-                let x := mload(X_MPTR)
-                let x_n := x
-                for
-                    { let idx := 0 }
-                    lt(idx, k)
-                    { idx := add(idx, 1) }
-                {
-                    x_n := mulmod(x_n, x_n, r)
+    function proxiableUUID() external view returns (bytes32) {
+        if (address(this) != SELF) revert Unauthorized();
+        return bytes32(IMPLEMENTATION_SLOT);
+    }
+
+    function upgradeToAndCall(address new_implementation, bytes calldata data) external payable {
+        if (address(this) == SELF) revert Unauthorized();
+        _authorizeUpgrade(new_implementation);
+        (bool success, bytes memory output) = new_implementation.staticcall(
+            abi.encodeWithSignature("proxiableUUID()")
+        );
+        if (!success || output.length != 0x20 || abi.decode(output, (uint256)) != IMPLEMENTATION_SLOT) {
+            revert InvalidImplementation();
+        }
+        assembly {
+            sstore(IMPLEMENTATION_SLOT, new_implementation)
+        }
+        emit Upgraded(new_implementation);
+        if (data.length > 0) {
+            (success, output) = new_implementation.delegatecall(data);
+            if (!success) {
+                assembly {
+                    revert(add(output, 0x20), mload(output))
                 }
+            }
+        }
+    }
 
-                let omega := mload(OMEGA_MPTR)
+    function _authorizeUpgrade(address) internal view {
+        if (msg.sender != ADMIN) revert Unauthorized();
+    }
+    {%- endif %}
 
-                let mptr := X_N_MPTR
-                let mptr_end := add(mptr, mul(0x20, add(mload(NUM_INSTANCES_MPTR), {{ num_neg_lagranges }})))
-                if iszero(mload(NUM_INSTANCES_MPTR)) {
-                    mptr_end := add(mptr_end, 0x20)
-                }
-                for
-                    { let pow_of_omega := mload(OMEGA_INV_TO_L_MPTR) }
-                    lt(mptr, mptr_end)
-                    { mptr := add(mptr, 0x20) }
-                {
-                    mstore(mptr, addmod(x, sub(r, pow_of_omega), r))
-                    pow_of_omega := mulmod(pow_of_omega, omega, r)
-                }
-                let x_n_minus_1 := addmod(x_n, sub(r, 1), r)
-                mstore(mptr_end, x_n_minus_1)
-                success := batch_invert(success, X_N_MPTR, add(mptr_end, 0x20), r)
-
-                mptr := X_N_MPTR
-                let l_i_common := mulmod(x_n_minus_1, mload(N_INV_MPTR), r)
-                for
-                    { let pow_of_omega := mload(OMEGA_INV_TO_L_MPTR) }
-                    lt(mptr, mptr_end)
-                    { mptr := add(mptr, 0x20) }
-                {
-                    mstore(mptr, mulmod(l_i_common, mulmod(mload(mptr), pow_of_omega, r), r))
-                    pow_of_omega := mulmod(pow_of_omega, omega, r)
-                }
+    function {{ verify_fn_name }}(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        bytes calldata proof,
+        uint256[] calldata instances
+        {%- if transcript_prefix %},
+        bytes calldata prefix
+        {%- endif %}
+    ) public {%- if return_mode == ReturnMode::Bool %} returns (bool) {%- endif %} {
+        {%- include "Halo2VerifierPrelude.sol" %}
+        {%- if transcript_prefix %}
+        bytes32 prefix_hash = keccak256(prefix);
+        {%- endif %}
+{% include "Halo2VerifierAssembly.sol" %}
+    }
+    {%- if has_additional_entrypoints %}
+
+    // Return whether call to `{{ verify_fn_name }}` accepts proof, which returns `true` with
+    // `ReturnMode::Bool` and nothing with `ReturnMode::Revert`, and reverts otherwise.
+    function is_accepted(bool success, bytes memory output) internal pure returns (bool) {
+        {%- if return_mode == ReturnMode::Revert %}
+        return success && output.length == 0;
+        {%- else %}
+        return success && output.length == 0x20 && abi.decode(output, (bool));
+        {%- endif %}
+    }
+    {%- endif %}
+    {%- match result_index %}
+    {%- when Some with (result_index) %}
 
-                let l_blind := mload(add(X_N_MPTR, 0x20))
-                let l_i_cptr := add(X_N_MPTR, 0x40)
-                for
-                    { let l_i_cptr_end := add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}) }
-                    lt(l_i_cptr, l_i_cptr_end)
-                    { l_i_cptr := add(l_i_cptr, 0x20) }
-                {
-                    l_blind := addmod(l_blind, mload(l_i_cptr), r)
-                }
+    // Verify proof by calling `{{ verify_fn_name }}` with canonical calldata encoding, then return the
+    // instance at index {{ result_index }} as result.
+    function verifyAndGet(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof
+    ) public view returns (uint256 result) {
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        result = instances[{{ result_index }}];
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- if verbose_gas %}
 
-                let instance_eval := 0
-                for
-                    {
-                        let instance_cptr := INSTANCE_CPTR
-                        let instance_cptr_end := add(instance_cptr, mul(0x20, mload(NUM_INSTANCES_MPTR)))
-                    }
-                    lt(instance_cptr, instance_cptr_end)
-                    {
-                        instance_cptr := add(instance_cptr, 0x20)
-                        l_i_cptr := add(l_i_cptr, 0x20)
-                    }
-                {
-                    instance_eval := addmod(instance_eval, mulmod(mload(l_i_cptr), calldataload(instance_cptr), r), r)
-                }
+    // Verify proof by calling `{{ verify_fn_name }}` with canonical calldata encoding, and return whether
+    // it succeeds with gas it consumes. The gas is indicative since it's measured in a static call.
+    function verifyVerboseGas(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof
+    ) public view returns (bool ok, uint256 gas_used) {
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        uint256 gas_before = gasleft();
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        gas_used = gas_before - gasleft();
+        ok = is_accepted(success, output);
+    }
 
-                let x_n_minus_1_inv := mload(mptr_end)
-                let l_last := mload(X_N_MPTR)
-                let l_0 := mload(add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}))
+    // Verify each proof by calling `{{ verify_fn_name }}` with canonical calldata encoding, and return
+    // whether each succeeds with gas it consumes, to profile many proofs in a single call.
+    function verifyManyVerbose(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[][] calldata instances,
+        bytes[] calldata proofs
+    ) public view returns (bool[] memory oks, uint256[] memory gas_each) {
+        require(instances.length == proofs.length);
+        oks = new bool[](proofs.length);
+        gas_each = new uint256[](proofs.length);
+        for (uint256 i = 0; i < proofs.length; i++) {
+            {%- match vk %}
+            {%- when Some with (vk) %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proofs[i], instances[i]);
+            {%- when None %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proofs[i], instances[i]);
+            {%- endmatch %}
+            uint256 gas_before = gasleft();
+            (bool success, bytes memory output) = address(this).staticcall(input);
+            gas_each[i] = gas_before - gasleft();
+            oks[i] = is_accepted(success, output);
+        }
+    }
+    {%- endif %}
+    {%- if batch_verify %}
 
-                mstore(X_N_MPTR, x_n)
-                mstore(X_N_MINUS_1_INV_MPTR, x_n_minus_1_inv)
-                mstore(L_LAST_MPTR, l_last)
-                mstore(L_BLIND_MPTR, l_blind)
-                mstore(L_0_MPTR, l_0)
-                mstore(INSTANCE_EVAL_MPTR, instance_eval)
-            }
+    // Verify each proof by calling `{{ verify_fn_name }}` with canonical calldata encoding, and return
+    // false as soon as any fails, to verify many proofs of the same circuit in a single call.
+    function verifyBatch(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[][] calldata pubInputsList,
+        bytes[] calldata proofs
+    ) public view returns (bool) {
+        require(pubInputsList.length == proofs.length);
+        for (uint256 i = 0; i < proofs.length; i++) {
+            {%- match vk %}
+            {%- when Some with (vk) %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proofs[i], pubInputsList[i]);
+            {%- when None %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proofs[i], pubInputsList[i]);
+            {%- endmatch %}
+            (bool success, bytes memory output) = address(this).staticcall(input);
+            if (!is_accepted(success, output)) return false;
+        }
+        return true;
+    }
+    {%- endif %}
+    {%- if batch_aggregate %}
+
+    // Verify each proof but pairing by calling `{{ verify_fn_name }}` with canonical calldata encoding
+    // followed by a word, which returns its pairing input instead, then combine the pairing inputs
+    // by random linear combination with challenge derived from all proofs and instances, and
+    // perform a single pairing for the whole batch.
+    function verifyBatchAggregated(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[][] calldata pubInputsList,
+        bytes[] calldata proofs
+    ) public view returns (bool) {
+        require(pubInputsList.length == proofs.length && proofs.length > 0);
+        uint256[12][] memory pairings = new uint256[12][](proofs.length);
+        for (uint256 i = 0; i < proofs.length; i++) {
+            {%- match vk %}
+            {%- when Some with (vk) %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proofs[i], pubInputsList[i]);
+            {%- when None %}
+            bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proofs[i], pubInputsList[i]);
+            {%- endmatch %}
+            input = abi.encodePacked(input, uint256(0));
+            (bool success, bytes memory output) = address(this).staticcall(input);
+            if (!success || output.length != 0x180) return false;
+            pairings[i] = abi.decode(output, (uint256[12]));
+        }
 
-            // Compute quotient evavluation
-            {
-                let quotient_eval_numer
-                let delta := 4131629893567559867359510883348571134090853742863529169391034518566172092834
-                let y := mload(Y_MPTR)
-
-                {%- for code_block in quotient_eval_numer_computations %}
-                {
-                    {%- for line in code_block %}
-                    {{ line }}
-                    {%- endfor %}
-                }
-                {%- endfor %}
+        uint256 challenge = uint256(keccak256(abi.encode(pubInputsList, proofs, pairings)));
+        return aggregate_pairing(pairings, challenge);
+    }
 
-                pop(y)
-                pop(delta)
+    // Combine pairing inputs by powers of challenge and perform a single pairing.
+    function aggregate_pairing(
+        uint256[12][] memory pairings,
+        uint256 challenge
+    ) internal view returns (bool ok) {
+        uint256 r = 21888242871839275222246405745257275088548364400416034343698204186575808495617;
+        challenge = challenge % r;
+        uint256[2] memory lhs = [pairings[0][0], pairings[0][1]];
+        uint256[2] memory rhs = [pairings[0][6], pairings[0][7]];
+        uint256 scalar = 1;
+        ok = true;
+        for (uint256 i = 1; i < pairings.length; i++) {
+            scalar = mulmod(scalar, challenge, r);
+            ok = ok && ec_mul_add(lhs, pairings[i][0], pairings[i][1], scalar);
+            ok = ok && ec_mul_add(rhs, pairings[i][6], pairings[i][7], scalar);
+        }
 
-                let quotient_eval := mulmod(quotient_eval_numer, mload(X_N_MINUS_1_INV_MPTR), r)
-                mstore(QUOTIENT_EVAL_MPTR, quotient_eval)
-            }
+        uint256[12] memory pairing = pairings[0];
+        (pairing[0], pairing[1], pairing[6], pairing[7]) = (lhs[0], lhs[1], rhs[0], rhs[1]);
+        uint256[1] memory result;
+        assembly {
+            ok := and(ok, staticcall(gas(), 0x08, pairing, 0x180, result, 0x20))
+        }
+        ok = ok && result[0] == 1;
+    }
 
-            // Compute quotient commitment
-            {
-                mstore(0x00, calldataload(LAST_QUOTIENT_X_CPTR))
-                mstore(0x20, calldataload(add(LAST_QUOTIENT_X_CPTR, 0x20)))
-                let x_n := mload(X_N_MPTR)
-                for
-                    {
-                        let cptr := sub(LAST_QUOTIENT_X_CPTR, 0x40)
-                        let cptr_end := sub(FIRST_QUOTIENT_X_CPTR, 0x40)
-                    }
-                    lt(cptr_end, cptr)
-                    {}
-                {
-                    success := ec_mul_acc(success, x_n)
-                    success := ec_add_acc(success, calldataload(cptr), calldataload(add(cptr, 0x20)))
-                    cptr := sub(cptr, 0x40)
-                }
-                mstore(QUOTIENT_X_MPTR, mload(0x00))
-                mstore(QUOTIENT_Y_MPTR, mload(0x20))
-            }
+    // Add scalar * (x, y) into point `acc`.
+    function ec_mul_add(
+        uint256[2] memory acc,
+        uint256 x,
+        uint256 y,
+        uint256 scalar
+    ) internal view returns (bool success) {
+        assembly {
+            let ptr := mload(0x40)
+            mstore(ptr, x)
+            mstore(add(ptr, 0x20), y)
+            mstore(add(ptr, 0x40), scalar)
+            success := staticcall(gas(), 0x07, ptr, 0x60, add(ptr, 0x40), 0x40)
+            mstore(ptr, mload(acc))
+            mstore(add(ptr, 0x20), mload(add(acc, 0x20)))
+            success := and(success, staticcall(gas(), 0x06, ptr, 0x80, acc, 0x40))
+        }
+    }
+    {%- endif %}
+    {%- match allowlist_index %}
+    {%- when Some with (allowlist_index) %}
 
-            // Compute pairing lhs and rhs
-            {
-                {%- for code_block in pcs_computations %}
-                {
-                    {%- for line in code_block %}
-                    {{ line }}
-                    {%- endfor %}
-                }
-                {%- endfor %}
-            }
+    error NotInAllowlist();
 
-            // Random linear combine with accumulator
-            if mload(HAS_ACCUMULATOR_MPTR) {
-                mstore(0x00, mload(ACC_LHS_X_MPTR))
-                mstore(0x20, mload(ACC_LHS_Y_MPTR))
-                mstore(0x40, mload(ACC_RHS_X_MPTR))
-                mstore(0x60, mload(ACC_RHS_Y_MPTR))
-                mstore(0x80, mload(PAIRING_LHS_X_MPTR))
-                mstore(0xa0, mload(PAIRING_LHS_Y_MPTR))
-                mstore(0xc0, mload(PAIRING_RHS_X_MPTR))
-                mstore(0xe0, mload(PAIRING_RHS_Y_MPTR))
-                let challenge := mod(keccak256(0x00, 0x100), r)
-
-                // [pairing_lhs] += challenge * [acc_lhs]
-                success := ec_mul_acc(success, challenge)
-                success := ec_add_acc(success, mload(PAIRING_LHS_X_MPTR), mload(PAIRING_LHS_Y_MPTR))
-                mstore(PAIRING_LHS_X_MPTR, mload(0x00))
-                mstore(PAIRING_LHS_Y_MPTR, mload(0x20))
-
-                // [pairing_rhs] += challenge * [acc_rhs]
-                mstore(0x00, mload(ACC_RHS_X_MPTR))
-                mstore(0x20, mload(ACC_RHS_Y_MPTR))
-                success := ec_mul_acc(success, challenge)
-                success := ec_add_acc(success, mload(PAIRING_RHS_X_MPTR), mload(PAIRING_RHS_Y_MPTR))
-                mstore(PAIRING_RHS_X_MPTR, mload(0x00))
-                mstore(PAIRING_RHS_Y_MPTR, mload(0x20))
-            }
+    // Verify proof by calling `{{ verify_fn_name }}` with canonical calldata encoding, and check the
+    // instance at index {{ allowlist_index }} is a leaf of merkle tree with root `ALLOWLIST_ROOT`.
+    function verifyInAllowlist(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof,
+        bytes32[] calldata merkle_proof
+    ) public view returns (bool) {
+        bytes32 node = keccak256(abi.encodePacked(instances[{{ allowlist_index }}]));
+        for (uint256 i = 0; i < merkle_proof.length; i++) {
+            bytes32 sibling = merkle_proof[i];
+            node = node < sibling
+                ? keccak256(abi.encodePacked(node, sibling))
+                : keccak256(abi.encodePacked(sibling, node));
+        }
+        if (node != ALLOWLIST_ROOT) revert NotInAllowlist();
 
-            // Perform pairing
-            success := ec_pairing(
-                success,
-                mload(PAIRING_LHS_X_MPTR),
-                mload(PAIRING_LHS_Y_MPTR),
-                mload(PAIRING_RHS_X_MPTR),
-                mload(PAIRING_RHS_Y_MPTR)
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- match nullifier_interface %}
+    {%- when Some with (nullifier_interface) %}
+
+    // Map fields of Semaphore-style external nullifier interface to instances, then verify proof
+    // by calling `{{ verify_fn_name }}` with canonical calldata encoding.
+    function verify(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256 signal,
+        uint256 root,
+        uint256 nullifier_hash,
+        bytes calldata proof
+    ) public view returns (bool) {
+        uint256[] memory instances = new uint256[](3);
+        instances[{{ nullifier_interface.signal }}] = signal;
+        instances[{{ nullifier_interface.root }}] = root;
+        instances[{{ nullifier_interface.nullifier_hash }}] = nullifier_hash;
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- if instance_permutation %}
+
+    error InvalidPermutation();
+
+    // Move `instances[i]` to index `permutation[i]` after checking the permutation is a bijection,
+    // then verify proof by calling `{{ verify_fn_name }}` with canonical calldata encoding.
+    function verifyPermuted(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata permuted_instances,
+        uint256[] calldata permutation,
+        bytes calldata proof
+    ) public view returns (bool) {
+        uint256 num_instances = permuted_instances.length;
+        if (permutation.length != num_instances) revert InvalidPermutation();
+        uint256[] memory instances = new uint256[](num_instances);
+        bool[] memory seen = new bool[](num_instances);
+        for (uint256 i = 0; i < num_instances; i++) {
+            uint256 index = permutation[i];
+            if (index >= num_instances || seen[index]) revert InvalidPermutation();
+            seen[index] = true;
+            instances[index] = permuted_instances[i];
+        }
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- endif %}
+    {%- if signed_request %}
+
+    error InvalidSignature();
+
+    bytes32 internal constant EIP712_DOMAIN_TYPEHASH =
+        keccak256("EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+    bytes32 internal constant PROOF_REQUEST_TYPEHASH =
+        keccak256("ProofRequest(uint256[] instances,bytes32 proofHash,uint256 nonce)");
+
+    // Next nonce expected in request signed by each signer
+    mapping(address => uint256) public signerNonces;
+
+    // Check `(v, r, s)` is signature of EIP-712 typed data `ProofRequest` by `signer`, which binds
+    // instances, hash of proof and next nonce of `signer`, then consume the nonce so the request
+    // can't be replayed, and verify proof by calling `{{ verify_fn_name }}` with canonical calldata
+    // encoding.
+    function verifySigned(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        address signer,
+        uint256[] calldata instances,
+        bytes calldata proof,
+        uint8 v,
+        bytes32 r,
+        bytes32 s
+    ) public returns (bool) {
+        // Reject malleable signature with high s
+        if (uint256(s) > 0x7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0) {
+            revert InvalidSignature();
+        }
+        bytes32 domain_separator = keccak256(
+            abi.encode(
+                EIP712_DOMAIN_TYPEHASH,
+                keccak256("{{ contract_name }}"),
+                keccak256("1"),
+                block.chainid,
+                address(this)
             )
+        );
+        bytes32 struct_hash = keccak256(
+            abi.encode(
+                PROOF_REQUEST_TYPEHASH,
+                keccak256(abi.encodePacked(instances)),
+                keccak256(proof),
+                signerNonces[signer]
+            )
+        );
+        bytes32 digest = keccak256(abi.encodePacked("\x19\x01", domain_separator, struct_hash));
+        address recovered = ecrecover(digest, v, r, s);
+        if (recovered == address(0) || recovered != signer) revert InvalidSignature();
+        signerNonces[signer]++;
 
-            // Revert if anything fails
-            if iszero(success) {
-                revert(0x00, 0x00)
-            }
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- endif %}
+    {%- match nonce_index %}
+    {%- when Some with (nonce_index) %}
+
+    error InvalidNonce();
+
+    // Next nonce expected from each submitter
+    mapping(address => uint256) public nonces;
+
+    // Check the instance at index {{ nonce_index }} is the next nonce of `msg.sender`, then verify
+    // proof by calling `{{ verify_fn_name }}` with canonical calldata encoding and increment the nonce.
+    // The nonce only prevents replay of the same proof by the same submitter: the proof doesn't
+    // bind the submitter, so anyone observing a pending call can submit it first from their own
+    // address. Circuits that need to prevent front-running should also expose `msg.sender` as an
+    // instance and have callers check it.
+    function verifyWithNonce(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof
+    ) public returns (bool) {
+        if (instances[{{ nonce_index }}] != nonces[msg.sender]) revert InvalidNonce();
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        nonces[msg.sender] += 1;
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- match inputs_root_index %}
+    {%- when Some with (inputs_root_index) %}
+
+    error InvalidInputs();
+
+    // Root of merkle tree of authorized inputs with sorted pair hashing, whose leaves are
+    // `keccak256(abi.encodePacked(instance))`
+    bytes32 public validInputsRoot;
+    address public immutable INPUTS_ROOT_GOVERNANCE = msg.sender;
+
+    function updateValidInputsRoot(bytes32 new_root) public {
+        if (msg.sender != INPUTS_ROOT_GOVERNANCE) revert Unauthorized();
+        validInputsRoot = new_root;
+    }
 
-            // Return 1 as result if everything succeeds
-            mstore(0x00, 1)
-            return(0x00, 0x20)
+    // Check the instance at index {{ inputs_root_index }} is a leaf of merkle tree with root
+    // `validInputsRoot`, then verify proof by calling `{{ verify_fn_name }}` with canonical calldata
+    // encoding.
+    function verifyWithInputsRoot(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof,
+        bytes32[] calldata merkle_proof
+    ) public view returns (bool) {
+        bytes32 node = keccak256(abi.encodePacked(instances[{{ inputs_root_index }}]));
+        for (uint256 i = 0; i < merkle_proof.length; i++) {
+            bytes32 sibling = merkle_proof[i];
+            node = node < sibling
+                ? keccak256(abi.encodePacked(node, sibling))
+                : keccak256(abi.encodePacked(sibling, node));
         }
+        if (node != validInputsRoot) revert InvalidInputs();
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
     }
+    {%- when None %}
+    {%- endmatch %}
+    {%- match poseidon_preimage %}
+    {%- when Some with (poseidon_preimage) %}
+
+    error InvalidPreimage();
+
+    // Contract computing Poseidon hash by `poseidon(uint256[{{ poseidon_preimage.preimage_len }}])`, whose parameters should
+    // match the ones used in circuit
+    address internal constant POSEIDON_HASHER = {{ poseidon_preimage.hasher|checksum_address }};
+
+    // Check the instance at index {{ poseidon_preimage.instance_index }} is Poseidon hash of `preimage` computed by
+    // `POSEIDON_HASHER`, then verify proof by calling `{{ verify_fn_name }}` with canonical calldata
+    // encoding.
+    function verifyWithPoseidonPreimage(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        bytes calldata proof,
+        uint256[] calldata preimage
+    ) public view returns (bool) {
+        if (preimage.length != {{ poseidon_preimage.preimage_len }}) revert InvalidPreimage();
+        uint256[{{ poseidon_preimage.preimage_len }}] memory inputs;
+        for (uint256 i = 0; i < {{ poseidon_preimage.preimage_len }}; i++) {
+            inputs[i] = preimage[i];
+        }
+        (bool hashed, bytes memory hash) = POSEIDON_HASHER.staticcall(
+            abi.encodeWithSignature("poseidon(uint256[{{ poseidon_preimage.preimage_len }}])", inputs)
+        );
+        require(hashed && hash.length == 0x20);
+        if (abi.decode(hash, (uint256)) != instances[{{ poseidon_preimage.instance_index }}]) {
+            revert InvalidPreimage();
+        }
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- if hex_proof %}
+
+    error InvalidHex();
+
+    // Decode proof from hex string with optional `0x` prefix, then verify proof by calling
+    // `{{ verify_fn_name }}` with canonical calldata encoding. Decoding costs gas proportional to length of
+    // proof, so decoding off-chain and calling `{{ verify_fn_name }}` directly should be preferred.
+    function verifyHex(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata instances,
+        string calldata proof_hex
+    ) public view returns (bool) {
+        bytes calldata chars = bytes(proof_hex);
+        uint256 start = chars.length >= 2 && chars[0] == "0" && chars[1] == "x" ? 2 : 0;
+        if ((chars.length - start) % 2 != 0) revert InvalidHex();
+        bytes memory proof = new bytes((chars.length - start) / 2);
+        for (uint256 i = 0; i < proof.length; i++) {
+            uint8 hi = hex_nibble(chars[start + 2 * i]);
+            uint8 lo = hex_nibble(chars[start + 2 * i + 1]);
+            proof[i] = bytes1((hi << 4) | lo);
+        }
+
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, instances);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, instances);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+
+    function hex_nibble(bytes1 char) internal pure returns (uint8) {
+        uint8 c = uint8(char);
+        if (c >= 0x30 && c <= 0x39) return c - 0x30;
+        if (c >= 0x61 && c <= 0x66) return c - 0x57;
+        if (c >= 0x41 && c <= 0x46) return c - 0x37;
+        revert InvalidHex();
+    }
+    {%- endif %}
+    {%- match memory_core %}
+    {%- when Some with (memory_core) %}
+
+    // Verify proof already in memory, e.g. decoded by a contract inheriting this one, by the same
+    // verification core as `{{ verify_fn_name }}` but reading proof and instances from memory, which
+    // it copies without their length prefix into the layout of calldata. Memory overwritten by
+    // verification is restored before returning, so it can be called internally.
+    function verifyMemory(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] memory instances,
+        bytes memory proof
+    ) public view returns (bool) {
+        {%- include "Halo2VerifierPrelude.sol" %}
+{{ memory_core }}
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
+    {%- match dynamic_instances %}
+    {%- when Some with (num_instances) %}
+
+    // Verify proof with instances as dynamic array, which has the same ABI for circuits of any
+    // number of instances. Number of instances is checked before touching proof.
+    function verify(
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- when None %}
+        address vk,
+        {%- endmatch %}
+        uint256[] calldata pubInputs,
+        bytes calldata proof
+    ) external view returns (bool) {
+        require(pubInputs.length == {{ num_instances }});
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", proof, pubInputs);
+        {%- when None %}
+        bytes memory input = abi.encodeWithSignature("{{ verify_fn_signature }}", vk, proof, pubInputs);
+        {%- endmatch %}
+        (bool success, bytes memory output) = address(this).staticcall(input);
+        require(is_accepted(success, output));
+        return true;
+    }
+    {%- when None %}
+    {%- endmatch %}
 }
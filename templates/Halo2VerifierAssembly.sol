@@ -0,0 +1,697 @@
+        assembly {
+            {%- match point_order %}
+            {%- when PointOrder::Xy %}
+            // Read EC point (x, y) at (proof_cptr, proof_cptr + 0x20),
+            {%- when PointOrder::Yx %}
+            // Read EC point (x, y) at (proof_cptr + 0x20, proof_cptr),
+            {%- endmatch %}
+            // and check if the point is on affine plane,
+            // and store them in (hash_mptr, hash_mptr + 0x20).
+            // Return updated (success, proof_cptr, hash_mptr).
+            function read_ec_point(success, proof_cptr, hash_mptr, q) -> ret0, ret1, ret2 {
+                {%- match point_order %}
+                {%- when PointOrder::Xy %}
+                let x := {{ self.proof_load() }}(proof_cptr)
+                let y := {{ self.proof_load() }}(add(proof_cptr, 0x20))
+                {%- when PointOrder::Yx %}
+                let x := {{ self.proof_load() }}(add(proof_cptr, 0x20))
+                let y := {{ self.proof_load() }}(proof_cptr)
+                {%- endmatch %}
+                {%- if non_canonical_point_check %}
+                if iszero(and(lt(x, q), lt(y, q))) {
+                    // NonCanonicalPoint()
+                    mstore(0x00, 0xf922566400000000000000000000000000000000000000000000000000000000)
+                    revert(0x00, 0x04)
+                }
+                {%- endif %}
+                ret0 := and(success, lt(x, q))
+                ret0 := and(ret0, lt(y, q))
+                ret0 := and(ret0, eq(mulmod(y, y, q), addmod(mulmod(x, mulmod(x, x, q), q), 3, q)))
+                mstore(hash_mptr, x)
+                mstore(add(hash_mptr, 0x20), y)
+                ret1 := add(proof_cptr, 0x40)
+                ret2 := add(hash_mptr, 0x40)
+            }
+
+            // Squeeze challenge by keccak256(memory[0..hash_mptr]),
+            // and store reduced hash as challenge in challenge_mptr,
+            // and push back hash in 0x00 as the first input for next squeeze.
+            // Return updated (challenge_mptr, hash_mptr).
+            function squeeze_challenge(challenge_mptr, hash_mptr, r) -> ret0, ret1 {
+                let hash := keccak256(0x00, hash_mptr)
+                mstore(0x00, hash)
+                mstore(challenge_mptr, reduce_challenge(hash, r))
+                ret0 := add(challenge_mptr, 0x20)
+                ret1 := 0x20
+            }
+
+            // Squeeze challenge without absorbing new input from calldata,
+            // by putting an extra 0x01 in memory[0x20] and squeeze by keccak256(memory[0..21]),
+            // and store reduced hash as challenge in challenge_mptr,
+            // and push back hash in 0x00 as the first input for next squeeze.
+            // Return updated (challenge_mptr).
+            function squeeze_challenge_cont(challenge_mptr, r) -> ret {
+                mstore8(0x20, 0x01)
+                let hash := keccak256(0x00, 0x21)
+                mstore(0x00, hash)
+                mstore(challenge_mptr, reduce_challenge(hash, r))
+                ret := add(challenge_mptr, 0x20)
+            }
+
+            {%- if challenge_reduction == ChallengeReduction::Mod %}
+
+            // Reduce hash as challenge by hash mod r.
+            // Return challenge.
+            function reduce_challenge(hash, r) -> ret {
+                ret := mod(hash, r)
+            }
+            {%- else %}
+
+            // Reduce hash as challenge by (hash || keccak256(hash)) mod r, where hash is expected
+            // in memory[0x00..0x20].
+            // Return challenge.
+            function reduce_challenge(hash, r) -> ret {
+                // 2^256 mod r
+                let r_2_256 := 0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb
+                ret := addmod(mulmod(hash, r_2_256, r), keccak256(0x00, 0x20), r)
+            }
+            {%- endif %}
+
+            // Batch invert values in memory[mptr_start..mptr_end] in place.
+            // Return updated (success).
+            function batch_invert(success, mptr_start, mptr_end, r) -> ret {
+                let gp_mptr := mptr_end
+                let gp := mload(mptr_start)
+                let mptr := add(mptr_start, 0x20)
+                for
+                    {}
+                    lt(mptr, sub(mptr_end, 0x20))
+                    {}
+                {
+                    gp := mulmod(gp, mload(mptr), r)
+                    mstore(gp_mptr, gp)
+                    mptr := add(mptr, 0x20)
+                    gp_mptr := add(gp_mptr, 0x20)
+                }
+                gp := mulmod(gp, mload(mptr), r)
+
+                mstore(gp_mptr, 0x20)
+                mstore(add(gp_mptr, 0x20), 0x20)
+                mstore(add(gp_mptr, 0x40), 0x20)
+                mstore(add(gp_mptr, 0x60), gp)
+                mstore(add(gp_mptr, 0x80), sub(r, 2))
+                mstore(add(gp_mptr, 0xa0), r)
+                ret := and(success, staticcall(gas(), 0x05, gp_mptr, 0xc0, gp_mptr, 0x20))
+                let all_inv := mload(gp_mptr)
+
+                let first_mptr := mptr_start
+                let second_mptr := add(first_mptr, 0x20)
+                gp_mptr := sub(gp_mptr, 0x20)
+                for
+                    {}
+                    lt(second_mptr, mptr)
+                    {}
+                {
+                    let inv := mulmod(all_inv, mload(gp_mptr), r)
+                    all_inv := mulmod(all_inv, mload(mptr), r)
+                    mstore(mptr, inv)
+                    mptr := sub(mptr, 0x20)
+                    gp_mptr := sub(gp_mptr, 0x20)
+                }
+                let inv_first := mulmod(all_inv, mload(second_mptr), r)
+                let inv_second := mulmod(all_inv, mload(first_mptr), r)
+                mstore(first_mptr, inv_first)
+                mstore(second_mptr, inv_second)
+            }
+
+            // Add (x, y) into point at (0x00, 0x20).
+            // Return updated (success).
+            function ec_add_acc(success, x, y) -> ret {
+                mstore(0x40, x)
+                mstore(0x60, y)
+                ret := and(success, staticcall(gas(), 0x06, 0x00, 0x80, 0x00, 0x40))
+            }
+
+            // Scale point at (0x00, 0x20) by scalar.
+            function ec_mul_acc(success, scalar) -> ret {
+                mstore(0x40, scalar)
+                ret := and(success, staticcall(gas(), 0x07, 0x00, 0x60, 0x00, 0x40))
+            }
+
+            // Add (x, y) into point at (0x80, 0xa0).
+            // Return updated (success).
+            function ec_add_tmp(success, x, y) -> ret {
+                mstore(0xc0, x)
+                mstore(0xe0, y)
+                ret := and(success, staticcall(gas(), 0x06, 0x80, 0x80, 0x80, 0x40))
+            }
+
+            // Scale point at (0x80, 0xa0) by scalar.
+            // Return updated (success).
+            function ec_mul_tmp(success, scalar) -> ret {
+                mstore(0xc0, scalar)
+                ret := and(success, staticcall(gas(), 0x07, 0x80, 0x60, 0x80, 0x40))
+            }
+
+            // Perform pairing check.
+            // Return updated (success).
+            function ec_pairing(success, lhs_x, lhs_y, rhs_x, rhs_y) -> ret {
+                mstore(0x00, lhs_x)
+                mstore(0x20, lhs_y)
+                mstore(0x40, mload(G2_X_1_MPTR))
+                mstore(0x60, mload(G2_X_2_MPTR))
+                mstore(0x80, mload(G2_Y_1_MPTR))
+                mstore(0xa0, mload(G2_Y_2_MPTR))
+                mstore(0xc0, rhs_x)
+                mstore(0xe0, rhs_y)
+                mstore(0x100, mload(NEG_S_G2_X_1_MPTR))
+                mstore(0x120, mload(NEG_S_G2_X_2_MPTR))
+                mstore(0x140, mload(NEG_S_G2_Y_1_MPTR))
+                mstore(0x160, mload(NEG_S_G2_Y_2_MPTR))
+                ret := and(success, staticcall(gas(), 0x08, 0x00, 0x180, 0x00, 0x20))
+                ret := and(ret, mload(0x00))
+            }
+
+            // Modulus
+            let q := 21888242871839275222246405745257275088696311157297823662689037894645226208583 // BN254 base field
+            let r := 21888242871839275222246405745257275088548364400416034343698204186575808495617 // BN254 scalar field
+
+            // Initialize success as true
+            let success := true
+            {%- if self.proof_in_memory() %}
+
+            // Save memory up to either free memory pointer or the end of proof and instances
+            // copied below, which are overwritten by verification and restored before returning
+            let saved_mptr := mload(0x40)
+            if lt(saved_mptr, {{ self.memory_end() }}) {
+                saved_mptr := {{ self.memory_end() }}
+            }
+            success := and(success, staticcall(gas(), 0x04, 0x00, saved_mptr, saved_mptr, saved_mptr))
+            proof := add(proof, saved_mptr)
+            instances := add(instances, saved_mptr)
+            {%- if deciding_key_source == DecidingKeySource::Constructor %}
+            deciding_key := add(deciding_key, saved_mptr)
+            {%- endif %}
+            {%- if vk.is_some() && vk_source == VkSource::BytesConstant %}
+            vk_data := add(vk_data, saved_mptr)
+            {%- endif %}
+
+            // Copy proof and instances from saved memory without their length prefix into the
+            // layout of calldata, and copy their lengths as is to be checked as in calldata
+            mstore({{ self.proof_ptr("PROOF_LEN_CPTR") }}, mload(proof))
+            success := and(success, staticcall(gas(), 0x04, add(proof, 0x20), {{ proof_len|hex() }}, {{ self.proof_ptr("PROOF_CPTR") }}, {{ proof_len|hex() }}))
+            mstore({{ self.proof_ptr("NUM_INSTANCE_CPTR") }}, mload(instances))
+            success := and(success, staticcall(gas(), 0x04, add(instances, 0x20), {{ (32 * num_instances)|hex() }}, {{ self.proof_ptr("INSTANCE_CPTR") }}, {{ (32 * num_instances)|hex() }}))
+            {%- endif %}
+            {%- if section_comments %}
+
+            // -------------------------------------------------------------------------------------
+            // Section: transcript
+            // Load verifying key, read proof and instances, and generate challenges
+            // -------------------------------------------------------------------------------------
+            {%- endif %}
+
+            {
+                {%- match vk %}
+                {%- when Some with (vk) %}
+                {%- match vk_source %}
+                {%- when Inline %}
+                // Load vk into memory
+                {%- for (name, chunk) in vk.constants %}
+                mstore({{ vk_mptr + loop.index0 }}, {{ chunk|hex_padded(64) }}) // {{ name }}
+                {%- endfor %}
+                {%- for (x, y) in vk.fixed_comms %}
+                {%- let offset = vk.constants.len() %}
+                mstore({{ vk_mptr + offset + 2 * loop.index0 }}, {{ x|hex_padded(64) }}) // fixed_comms[{{ loop.index0 }}].x
+                mstore({{ vk_mptr + offset + 2 * loop.index0 + 1 }}, {{ y|hex_padded(64) }}) // fixed_comms[{{ loop.index0 }}].y
+                {%- endfor %}
+                {%- for (x, y) in vk.permutation_comms %}
+                {%- let offset = vk.constants.len() + 2 * vk.fixed_comms.len() %}
+                mstore({{ vk_mptr + offset + 2 * loop.index0 }}, {{ x|hex_padded(64) }}) // permutation_comms[{{ loop.index0 }}].x
+                mstore({{ vk_mptr + offset + 2 * loop.index0 + 1 }}, {{ y|hex_padded(64) }}) // permutation_comms[{{ loop.index0 }}].y
+                {%- endfor %}
+                {%- when BytesConstant %}
+                // Copy vk from `bytes` constant (already copied into free memory by codecopy) into place
+                success := and(success, staticcall(gas(), 0x04, add(vk_data, 0x20), {{ vk_len|hex() }}, VK_MPTR, {{ vk_len|hex() }}))
+                {%- when VkSource::Constructor %}
+                // Copy vk from storage into memory
+                for
+                    {
+                        let slot := vk_words.slot
+                        let mptr := VK_MPTR
+                        let mptr_end := add(VK_MPTR, {{ vk_len|hex() }})
+                    }
+                    lt(mptr, mptr_end)
+                    {
+                        slot := add(slot, 1)
+                        mptr := add(mptr, 0x20)
+                    }
+                {
+                    mstore(mptr, sload(slot))
+                }
+                {%- endmatch %}
+                {%- when None %}
+                // Copy vk into memory
+                extcodecopy(vk, VK_MPTR, 0x00, {{ vk_len|hex() }})
+                {%- endmatch %}
+                {%- if deciding_key_source == DecidingKeySource::Constructor %}
+
+                // Overwrite deciding key by the one given in constructor
+                for
+                    { let i := 0 }
+                    lt(i, 0x100)
+                    { i := add(i, 0x20) }
+                {
+                    mstore(add(G2_X_1_MPTR, i), mload(add(deciding_key, i)))
+                }
+                {%- endif %}
+
+                // Check valid length of proof
+                success := and(success, eq({{ proof_len|hex() }}, {{ self.proof_load() }}({{ self.proof_ptr("PROOF_LEN_CPTR") }})))
+                {%- if calldata_bounds_check && !self.proof_in_memory() %}
+
+                // Check declared length of instances fits within calldata
+                if or(
+                    lt(calldatasize(), INSTANCE_CPTR),
+                    gt(calldataload(NUM_INSTANCE_CPTR), div(sub(calldatasize(), INSTANCE_CPTR), 0x20))
+                ) {
+                    mstore(0x00, 0x742638b400000000000000000000000000000000000000000000000000000000)
+                    revert(0x00, 0x04)
+                }
+                {%- endif %}
+
+                // Check valid length of instances
+                let num_instances := mload(NUM_INSTANCES_MPTR)
+                success := and(success, eq(num_instances, {{ self.proof_load() }}({{ self.proof_ptr("NUM_INSTANCE_CPTR") }})))
+
+                {%- if transcript_prefix %}
+                // Absorb hash of prefix and vk diegst
+                mstore(0x00, prefix_hash)
+                mstore(0x20, mload(VK_DIGEST_MPTR))
+
+                // Read instances and witness commitments and generate challenges
+                let hash_mptr := 0x40
+                {%- else %}
+                // Absorb vk diegst
+                mstore(0x00, mload(VK_DIGEST_MPTR))
+
+                // Read instances and witness commitments and generate challenges
+                let hash_mptr := 0x20
+                {%- endif %}
+                let instance_cptr := {{ self.proof_ptr("INSTANCE_CPTR") }}
+                for
+                    { let instance_cptr_end := add(instance_cptr, mul(0x20, num_instances)) }
+                    lt(instance_cptr, instance_cptr_end)
+                    {}
+                {
+                    let instance := {{ self.proof_load() }}(instance_cptr)
+                    success := and(success, lt(instance, r))
+                    mstore(hash_mptr, instance)
+                    instance_cptr := add(instance_cptr, 0x20)
+                    hash_mptr := add(hash_mptr, 0x20)
+                }
+
+                let proof_cptr := {{ self.proof_ptr("PROOF_CPTR") }}
+                let challenge_mptr := CHALLENGE_MPTR
+                {%- for num_advices in num_advices %}
+                {%- let num_challenges = num_challenges[loop.index0] %}
+
+                // Phase {{ loop.index }}
+                for
+                    { let proof_cptr_end := add(proof_cptr, {{ (2 * 32 * num_advices)|hex() }}) }
+                    lt(proof_cptr, proof_cptr_end)
+                    {}
+                {
+                    success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q)
+                }
+
+                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)
+                {%- for _ in 0..num_challenges - 1 %}
+                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)
+                {%- endfor %}
+                {%- endfor %}
+
+                // Read evaluations
+                for
+                    { let proof_cptr_end := add(proof_cptr, {{ (32 * num_evals)|hex() }}) }
+                    lt(proof_cptr, proof_cptr_end)
+                    {}
+                {
+                    let eval := {{ self.proof_load() }}(proof_cptr)
+                    success := and(success, lt(eval, r))
+                    mstore(hash_mptr, eval)
+                    proof_cptr := add(proof_cptr, 0x20)
+                    hash_mptr := add(hash_mptr, 0x20)
+                }
+
+                // Read batch opening proof and generate challenges
+                {%- match scheme %}
+                {%- when Bdfg21 %}
+                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // zeta
+                challenge_mptr := squeeze_challenge_cont(challenge_mptr, r)                        // nu
+
+                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W
+
+                challenge_mptr, hash_mptr := squeeze_challenge(challenge_mptr, hash_mptr, r)       // mu
+
+                success, proof_cptr, hash_mptr := read_ec_point(success, proof_cptr, hash_mptr, q) // W'
+                {%- when Gwc19 %}
+                // TODO
+                {%- endmatch %}
+
+                // Read accumulator from instances
+                if mload(HAS_ACCUMULATOR_MPTR) {
+                    let num_limbs := mload(NUM_ACC_LIMBS_MPTR)
+                    let num_limb_bits := mload(NUM_ACC_LIMB_BITS_MPTR)
+
+                    let cptr := add({{ self.proof_ptr("INSTANCE_CPTR") }}, mul(mload(ACC_OFFSET_MPTR), 0x20))
+                    let lhs_y_off := mul(num_limbs, 0x20)
+                    let rhs_x_off := mul(lhs_y_off, 2)
+                    let rhs_y_off := mul(lhs_y_off, 3)
+                    let lhs_x := {{ self.proof_load() }}(cptr)
+                    let lhs_y := {{ self.proof_load() }}(add(cptr, lhs_y_off))
+                    let rhs_x := {{ self.proof_load() }}(add(cptr, rhs_x_off))
+                    let rhs_y := {{ self.proof_load() }}(add(cptr, rhs_y_off))
+                    for
+                        {
+                            let cptr_end := add(cptr, mul(0x20, num_limbs))
+                            let shift := num_limb_bits
+                        }
+                        lt(cptr, cptr_end)
+                        {}
+                    {
+                        cptr := add(cptr, 0x20)
+                        lhs_x := add(lhs_x, shl(shift, {{ self.proof_load() }}(cptr)))
+                        lhs_y := add(lhs_y, shl(shift, {{ self.proof_load() }}(add(cptr, lhs_y_off))))
+                        rhs_x := add(rhs_x, shl(shift, {{ self.proof_load() }}(add(cptr, rhs_x_off))))
+                        rhs_y := add(rhs_y, shl(shift, {{ self.proof_load() }}(add(cptr, rhs_y_off))))
+                        shift := add(shift, num_limb_bits)
+                    }
+
+                    success := and(success, eq(mulmod(lhs_y, lhs_y, q), addmod(mulmod(lhs_x, mulmod(lhs_x, lhs_x, q), q), 3, q)))
+                    success := and(success, eq(mulmod(rhs_y, rhs_y, q), addmod(mulmod(rhs_x, mulmod(rhs_x, rhs_x, q), q), 3, q)))
+
+                    mstore(ACC_LHS_X_MPTR, lhs_x)
+                    mstore(ACC_LHS_Y_MPTR, lhs_y)
+                    mstore(ACC_RHS_X_MPTR, rhs_x)
+                    mstore(ACC_RHS_Y_MPTR, rhs_y)
+                }
+
+                pop(q)
+            }
+
+            // Revert earlier if anything from calldata is invalid
+            if iszero(success) {
+                {%- if debug_errors %}
+                // TranscriptMismatch()
+                mstore(0x00, 0xed54d1c900000000000000000000000000000000000000000000000000000000)
+                revert(0x00, 0x04)
+                {%- else if return_mode == ReturnMode::Revert %}
+                // InvalidProof()
+                mstore(0x00, 0x09bde33900000000000000000000000000000000000000000000000000000000)
+                revert(0x00, 0x04)
+                {%- else %}
+                revert(0, 0)
+                {%- endif %}
+            }
+            {%- if section_comments %}
+
+            // -------------------------------------------------------------------------------------
+            // Section: evaluate gates
+            // Evaluate gates, permutation and lookups at challenge x into quotient evaluation
+            // -------------------------------------------------------------------------------------
+            {%- endif %}
+
+            // Compute lagrange evaluations and instance evaluation
+            {
+                let k := mload(K_MPTR)
+                let x := mload(X_MPTR)
+                let x_n := x
+                for
+                    { let idx := 0 }
+                    lt(idx, k)
+                    { idx := add(idx, 1) }
+                {
+                    x_n := mulmod(x_n, x_n, r)
+                }
+
+                let omega := mload(OMEGA_MPTR)
+
+                let mptr := X_N_MPTR
+                let mptr_end := add(mptr, mul(0x20, add(mload(NUM_INSTANCES_MPTR), {{ num_neg_lagranges }})))
+                if iszero(mload(NUM_INSTANCES_MPTR)) {
+                    mptr_end := add(mptr_end, 0x20)
+                }
+                for
+                    { let pow_of_omega := mload(OMEGA_INV_TO_L_MPTR) }
+                    lt(mptr, mptr_end)
+                    { mptr := add(mptr, 0x20) }
+                {
+                    mstore(mptr, addmod(x, sub(r, pow_of_omega), r))
+                    pow_of_omega := mulmod(pow_of_omega, omega, r)
+                }
+                let x_n_minus_1 := addmod(x_n, sub(r, 1), r)
+                mstore(mptr_end, x_n_minus_1)
+                success := batch_invert(success, X_N_MPTR, add(mptr_end, 0x20), r)
+
+                mptr := X_N_MPTR
+                let l_i_common := mulmod(x_n_minus_1, mload(N_INV_MPTR), r)
+                for
+                    { let pow_of_omega := mload(OMEGA_INV_TO_L_MPTR) }
+                    lt(mptr, mptr_end)
+                    { mptr := add(mptr, 0x20) }
+                {
+                    mstore(mptr, mulmod(l_i_common, mulmod(mload(mptr), pow_of_omega, r), r))
+                    pow_of_omega := mulmod(pow_of_omega, omega, r)
+                }
+
+                let l_blind := mload(add(X_N_MPTR, 0x20))
+                let l_i_cptr := add(X_N_MPTR, 0x40)
+                for
+                    { let l_i_cptr_end := add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}) }
+                    lt(l_i_cptr, l_i_cptr_end)
+                    { l_i_cptr := add(l_i_cptr, 0x20) }
+                {
+                    l_blind := addmod(l_blind, mload(l_i_cptr), r)
+                }
+
+                let instance_eval := 0
+                for
+                    {
+                        let instance_cptr := {{ self.proof_ptr("INSTANCE_CPTR") }}
+                        let instance_cptr_end := add(instance_cptr, mul(0x20, mload(NUM_INSTANCES_MPTR)))
+                    }
+                    lt(instance_cptr, instance_cptr_end)
+                    {
+                        instance_cptr := add(instance_cptr, 0x20)
+                        l_i_cptr := add(l_i_cptr, 0x20)
+                    }
+                {
+                    instance_eval := addmod(instance_eval, mulmod(mload(l_i_cptr), {{ self.proof_load() }}(instance_cptr), r), r)
+                }
+
+                let x_n_minus_1_inv := mload(mptr_end)
+                let l_last := mload(X_N_MPTR)
+                let l_0 := mload(add(X_N_MPTR, {{ (num_neg_lagranges * 32)|hex() }}))
+
+                mstore(X_N_MPTR, x_n)
+                mstore(X_N_MINUS_1_INV_MPTR, x_n_minus_1_inv)
+                mstore(L_LAST_MPTR, l_last)
+                mstore(L_BLIND_MPTR, l_blind)
+                mstore(L_0_MPTR, l_0)
+                mstore(INSTANCE_EVAL_MPTR, instance_eval)
+            }
+
+            // Compute quotient evavluation
+            {
+                let quotient_eval_numer
+                let delta := 4131629893567559867359510883348571134090853742863529169391034518566172092834
+                let y := mload(Y_MPTR)
+                {%- for (ptr, constant) in hoisted_constants %}
+                mstore({{ ptr }}, {{ constant }})
+                {%- endfor %}
+
+                {%- for code_block in quotient_eval_numer_computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+
+                pop(y)
+                pop(delta)
+
+                let quotient_eval := mulmod(quotient_eval_numer, mload(X_N_MINUS_1_INV_MPTR), r)
+                mstore(QUOTIENT_EVAL_MPTR, quotient_eval)
+            }
+            {%- if section_comments %}
+
+            // -------------------------------------------------------------------------------------
+            // Section: opening
+            // Compute quotient commitment and batch opening into pairing lhs and rhs
+            // -------------------------------------------------------------------------------------
+            {%- endif %}
+
+            // Compute quotient commitment
+            {
+                {%- match point_order %}
+                {%- when PointOrder::Xy %}
+                mstore(0x00, {{ self.proof_load() }}({{ self.proof_ptr("LAST_QUOTIENT_X_CPTR") }}))
+                mstore(0x20, {{ self.proof_load() }}(add({{ self.proof_ptr("LAST_QUOTIENT_X_CPTR") }}, 0x20)))
+                {%- when PointOrder::Yx %}
+                mstore(0x00, {{ self.proof_load() }}(add({{ self.proof_ptr("LAST_QUOTIENT_X_CPTR") }}, 0x20)))
+                mstore(0x20, {{ self.proof_load() }}({{ self.proof_ptr("LAST_QUOTIENT_X_CPTR") }}))
+                {%- endmatch %}
+                let x_n := mload(X_N_MPTR)
+                for
+                    {
+                        let cptr := sub({{ self.proof_ptr("LAST_QUOTIENT_X_CPTR") }}, 0x40)
+                        let cptr_end := sub({{ self.proof_ptr("FIRST_QUOTIENT_X_CPTR") }}, 0x40)
+                    }
+                    lt(cptr_end, cptr)
+                    {}
+                {
+                    success := ec_mul_acc(success, x_n)
+                    {%- match point_order %}
+                    {%- when PointOrder::Xy %}
+                    success := ec_add_acc(success, {{ self.proof_load() }}(cptr), {{ self.proof_load() }}(add(cptr, 0x20)))
+                    {%- when PointOrder::Yx %}
+                    success := ec_add_acc(success, {{ self.proof_load() }}(add(cptr, 0x20)), {{ self.proof_load() }}(cptr))
+                    {%- endmatch %}
+                    cptr := sub(cptr, 0x40)
+                }
+                mstore(QUOTIENT_X_MPTR, mload(0x00))
+                mstore(QUOTIENT_Y_MPTR, mload(0x20))
+            }
+
+            // Compute pairing lhs and rhs
+            {
+                {%- for code_block in pcs_computations %}
+                {
+                    {%- for line in code_block %}
+                    {{ line }}
+                    {%- endfor %}
+                }
+                {%- endfor %}
+            }
+            {%- if section_comments %}
+
+            // -------------------------------------------------------------------------------------
+            // Section: pairing
+            // Combine accumulator if any, then perform pairing check
+            // -------------------------------------------------------------------------------------
+            {%- endif %}
+
+            // Random linear combine with accumulator
+            if mload(HAS_ACCUMULATOR_MPTR) {
+                mstore(0x00, mload(ACC_LHS_X_MPTR))
+                mstore(0x20, mload(ACC_LHS_Y_MPTR))
+                mstore(0x40, mload(ACC_RHS_X_MPTR))
+                mstore(0x60, mload(ACC_RHS_Y_MPTR))
+                mstore(0x80, mload(PAIRING_LHS_X_MPTR))
+                mstore(0xa0, mload(PAIRING_LHS_Y_MPTR))
+                mstore(0xc0, mload(PAIRING_RHS_X_MPTR))
+                mstore(0xe0, mload(PAIRING_RHS_Y_MPTR))
+                let challenge := mod(keccak256(0x00, 0x100), r)
+
+                // [pairing_lhs] += challenge * [acc_lhs]
+                success := ec_mul_acc(success, challenge)
+                success := ec_add_acc(success, mload(PAIRING_LHS_X_MPTR), mload(PAIRING_LHS_Y_MPTR))
+                mstore(PAIRING_LHS_X_MPTR, mload(0x00))
+                mstore(PAIRING_LHS_Y_MPTR, mload(0x20))
+
+                // [pairing_rhs] += challenge * [acc_rhs]
+                mstore(0x00, mload(ACC_RHS_X_MPTR))
+                mstore(0x20, mload(ACC_RHS_Y_MPTR))
+                success := ec_mul_acc(success, challenge)
+                success := ec_add_acc(success, mload(PAIRING_RHS_X_MPTR), mload(PAIRING_RHS_Y_MPTR))
+                mstore(PAIRING_RHS_X_MPTR, mload(0x00))
+                mstore(PAIRING_RHS_Y_MPTR, mload(0x20))
+            }
+
+            {%- if pairing_failure_context %}
+
+            // Revert with `PairingFailed(theta)` if everything but pairing succeeds
+            if iszero(success) {
+                {%- if debug_errors %}
+                // OpeningFailed()
+                mstore(0x00, 0x7770928100000000000000000000000000000000000000000000000000000000)
+                revert(0x00, 0x04)
+                {%- else if return_mode == ReturnMode::Revert %}
+                // InvalidProof()
+                mstore(0x00, 0x09bde33900000000000000000000000000000000000000000000000000000000)
+                revert(0x00, 0x04)
+                {%- else %}
+                revert(0x00, 0x00)
+                {%- endif %}
+            }
+            {%- endif %}
+            {%- if batch_aggregate && !self.proof_in_memory() %}
+
+            // Return pairing input instead of performing pairing if called by
+            // `verifyBatchAggregated`, which appends a word to canonical calldata
+            if and(
+                eq(caller(), address()),
+                eq(calldatasize(), add(INSTANCE_CPTR, mul(0x20, add(mload(NUM_INSTANCES_MPTR), 1))))
+            ) {
+                if iszero(success) {
+                    revert(0x00, 0x00)
+                }
+                mstore(0x00, mload(PAIRING_LHS_X_MPTR))
+                mstore(0x20, mload(PAIRING_LHS_Y_MPTR))
+                mstore(0x40, mload(G2_X_1_MPTR))
+                mstore(0x60, mload(G2_X_2_MPTR))
+                mstore(0x80, mload(G2_Y_1_MPTR))
+                mstore(0xa0, mload(G2_Y_2_MPTR))
+                mstore(0xc0, mload(PAIRING_RHS_X_MPTR))
+                mstore(0xe0, mload(PAIRING_RHS_Y_MPTR))
+                mstore(0x100, mload(NEG_S_G2_X_1_MPTR))
+                mstore(0x120, mload(NEG_S_G2_X_2_MPTR))
+                mstore(0x140, mload(NEG_S_G2_Y_1_MPTR))
+                mstore(0x160, mload(NEG_S_G2_Y_2_MPTR))
+                return(0x00, 0x180)
+            }
+            {%- endif %}
+
+            // Perform pairing
+            success := ec_pairing(
+                success,
+                mload(PAIRING_LHS_X_MPTR),
+                mload(PAIRING_LHS_Y_MPTR),
+                mload(PAIRING_RHS_X_MPTR),
+                mload(PAIRING_RHS_Y_MPTR)
+            )
+
+            // Revert if anything fails
+            if iszero(success) {
+                {%- if pairing_failure_context %}
+                // PairingFailed(uint256)
+                mstore(0x00, 0x2df3669100000000000000000000000000000000000000000000000000000000)
+                mstore(0x04, mload(THETA_MPTR))
+                revert(0x00, 0x24)
+                {%- else if return_mode == ReturnMode::Revert %}
+                // InvalidProof()
+                mstore(0x00, 0x09bde33900000000000000000000000000000000000000000000000000000000)
+                revert(0x00, 0x04)
+                {%- else %}
+                revert(0x00, 0x00)
+                {%- endif %}
+            }
+            {%- if self.proof_in_memory() %}
+
+            // Restore saved memory and continue as `verifyMemory` if everything succeeds
+            if iszero(staticcall(gas(), 0x04, saved_mptr, {{ self.memory_end() }}, 0x00, {{ self.memory_end() }})) {
+                revert(0x00, 0x00)
+            }
+            {%- else if return_mode == ReturnMode::Revert %}
+
+            // Return nothing if everything succeeds
+            return(0x00, 0x00)
+            {%- else %}
+
+            // Return 1 as result if everything succeeds
+            mstore(0x00, 1)
+            return(0x00, 0x20)
+            {%- endif %}
+        }
@@ -0,0 +1,35 @@
+import { readFileSync } from "fs";
+import { ethers } from "hardhat";
+
+// Proof as hex string, e.g. "0x1234...".
+const PROOF_PATH = process.env.PROOF_PATH ?? "proof.json";
+// Instances as array of decimal or hex strings, e.g. ["0x01", "2"].
+const INSTANCES_PATH = process.env.INSTANCES_PATH ?? "instances.json";
+{%- if transcript_prefix %}
+// Transcript prefix as hex string, empty by default.
+const PREFIX = process.env.PREFIX ?? "0x";
+{%- endif %}
+
+async function main() {
+  const proof: string = JSON.parse(readFileSync(PROOF_PATH, "utf8"));
+  const instances: string[] = JSON.parse(readFileSync(INSTANCES_PATH, "utf8"));
+
+  const verifier = await ethers.deployContract("{{ contract_name }}");
+  await verifier.waitForDeployment();
+  console.log(`{{ contract_name }} deployed to ${await verifier.getAddress()}`);
+
+  // Reverts if proof is rejected with ReturnMode::Revert
+  const accepted = await verifier.{{ verify_fn_name }}.staticCall(
+    proof,
+    instances,
+    {%- if transcript_prefix %}
+    PREFIX,
+    {%- endif %}
+  );
+  console.log(`{{ verify_fn_name }} returned ${accepted}`);
+}
+
+main().catch((error) => {
+  console.error(error);
+  process.exitCode = 1;
+});
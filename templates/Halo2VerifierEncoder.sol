@@ -0,0 +1,31 @@
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+library VerifyEncoder {
+    // Public inputs of Semaphore-style external nullifier interface
+    struct PublicInputs {
+        uint256 signal;
+        uint256 root;
+        uint256 nullifierHash;
+    }
+
+    // Encode calldata of `verify(uint256,uint256,uint256,bytes)` of `Halo2Verifier` with
+    // verifying key embedded.
+    function encode(
+        PublicInputs memory pi,
+        bytes memory proof
+    ) internal pure returns (bytes memory) {
+        return abi.encodeWithSelector(bytes4(0x2e8facba), pi.signal, pi.root, pi.nullifierHash, proof);
+    }
+
+    // Encode calldata of `verify(address,uint256,uint256,uint256,bytes)` of `Halo2Verifier`
+    // rendered separately, with `Halo2VerifyingKey` deployed at `vk`.
+    function encode(
+        address vk,
+        PublicInputs memory pi,
+        bytes memory proof
+    ) internal pure returns (bytes memory) {
+        return abi.encodeWithSelector(bytes4(0x26b28edf), vk, pi.signal, pi.root, pi.nullifierHash, proof);
+    }
+}
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+interface IHalo2Verifier {
+    function {{ verify_fn_name }}(
+        {%- if separate %}
+        address vk,
+        {%- endif %}
+        bytes calldata proof,
+        uint256[] calldata instances
+        {%- if transcript_prefix %},
+        bytes calldata prefix
+        {%- endif %}
+    ) external {%- if return_mode == ReturnMode::Bool %} returns (bool) {%- endif %};
+}
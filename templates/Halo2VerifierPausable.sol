@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+contract Halo2VerifierPausable {
+    error Paused();
+    error Unauthorized();
+
+    address public immutable VERIFIER;
+    address public immutable ADMIN;
+    bool public paused;
+
+    // The deployer becomes admin, who is the only one allowed to pause and unpause.
+    constructor(address verifier) {
+        VERIFIER = verifier;
+        ADMIN = msg.sender;
+    }
+
+    function pause() external {
+        if (msg.sender != ADMIN) revert Unauthorized();
+        paused = true;
+    }
+
+    function unpause() external {
+        if (msg.sender != ADMIN) revert Unauthorized();
+        paused = false;
+    }
+
+    // Forward calldata as is to `Halo2Verifier`, which relies on its canonical encoding.
+    function {{ verify_fn_name }}(
+        {%- if separate %}
+        address,
+        {%- endif %}
+        bytes calldata,
+        uint256[] calldata
+        {%- if transcript_prefix %},
+        bytes calldata
+        {%- endif %}
+    ) external view {%- if return_mode == ReturnMode::Bool %} returns (bool) {%- endif %} {
+        if (paused) revert Paused();
+        (bool success, bytes memory output) = VERIFIER.staticcall(msg.data);
+        if (!success) {
+            assembly {
+                revert(add(output, 0x20), mload(output))
+            }
+        }
+        {%- if return_mode == ReturnMode::Bool %}
+        return abi.decode(output, (bool));
+        {%- endif %}
+    }
+}
@@ -0,0 +1,23 @@
+        {%- if deciding_key_source == DecidingKeySource::Constructor %}
+        uint256[8] memory deciding_key = [
+            G2_X_1, G2_X_2, G2_Y_1, G2_Y_2, NEG_S_G2_X_1, NEG_S_G2_X_2, NEG_S_G2_Y_1, NEG_S_G2_Y_2
+        ];
+        {%- endif %}
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        {%- match vk_source %}
+        {%- when BytesConstant %}
+        bytes memory vk_data = VK_DATA;
+        {%- when Inline %}
+        {%- when VkSource::Constructor %}
+        {%- endmatch %}
+        {%- when None %}
+        {%- endmatch %}
+        {%- match stored_accumulator_index %}
+        {%- when Some with (stored_accumulator_index) %}
+        if (instances[{{ stored_accumulator_index }}] != accumulator) revert StaleAccumulator();
+        {%- when None %}
+        {%- endmatch %}
+        {%- for (index, bit_width) in instance_bit_widths %}
+        if (instances[{{ index }}] >> {{ bit_width }} != 0) revert InstanceOutOfRange({{ index }});
+        {%- endfor %}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+contract Halo2VerifierRouter {
+    error Unauthorized();
+    error UnregisteredCircuit();
+
+    struct Circuit {
+        address verifier;
+        address vk;
+    }
+
+    address public immutable ADMIN;
+    mapping(bytes32 => Circuit) public registry;
+
+    // The deployer becomes admin, who is the only one allowed to register circuits.
+    constructor() {
+        ADMIN = msg.sender;
+    }
+
+    // Register `Halo2Verifier` and `Halo2VerifyingKey` of a circuit deployed at `verifier` and
+    // `vk` under `circuit_hash`, or unregister it by giving zero addresses. Circuits of different
+    // shape have different verifiers, which must be rendered with the same options as router.
+    function register(bytes32 circuit_hash, address verifier, address vk) external {
+        if (msg.sender != ADMIN) revert Unauthorized();
+        registry[circuit_hash] = Circuit(verifier, vk);
+    }
+
+    // Look up verifier and verifying key registered under `circuit_hash`, then call the verifier
+    // with canonical calldata encoding.
+    function verify(
+        bytes32 circuit_hash,
+        uint256[] calldata instances,
+        bytes calldata proof
+        {%- if transcript_prefix %},
+        bytes calldata prefix
+        {%- endif %}
+    ) external view {%- if return_mode == ReturnMode::Bool %} returns (bool) {%- endif %} {
+        Circuit memory circuit = registry[circuit_hash];
+        if (circuit.verifier == address(0)) revert UnregisteredCircuit();
+        {%- if transcript_prefix %}
+        bytes memory input = abi.encodeWithSignature(
+            "{{ verify_fn_name }}(address,bytes,uint256[],bytes)", circuit.vk, proof, instances, prefix
+        );
+        {%- else %}
+        bytes memory input = abi.encodeWithSignature(
+            "{{ verify_fn_name }}(address,bytes,uint256[])", circuit.vk, proof, instances
+        );
+        {%- endif %}
+        (bool success, bytes memory output) = circuit.verifier.staticcall(input);
+        if (!success) {
+            assembly {
+                revert(add(output, 0x20), mload(output))
+            }
+        }
+        {%- if return_mode == ReturnMode::Bool %}
+        return abi.decode(output, (bool));
+        {%- endif %}
+    }
+}
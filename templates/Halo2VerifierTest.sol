@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: {{ license }}
+
+pragma solidity {{ pragma }};
+
+import { {{- contract_name -}} } from "../src/Verifier.sol";
+
+contract {{ contract_name }}Test {
+    address internal verifier;
+
+    bytes internal constant PROOF = hex"{{ proof }}";
+
+    function setUp() public {
+        {%- match vk %}
+        {%- when Some with (vk) %}
+        uint256[] memory vk = new uint256[]({{ vk.len() }});
+        {%- for (name, word) in vk %}
+        vk[{{ loop.index0 }}] = {{ word }}; // {{ name }}
+        {%- endfor %}
+        verifier = address(new {{ contract_name }}(vk));
+        {%- when None %}
+        verifier = address(new {{ contract_name }}());
+        {%- endmatch %}
+    }
+
+    function instances() internal pure returns (uint256[] memory values) {
+        values = new uint256[]({{ instances.len() }});
+        {%- for instance in instances %}
+        values[{{ loop.index0 }}] = {{ instance }};
+        {%- endfor %}
+    }
+
+    function verify(bytes memory proof, uint256[] memory values) internal view returns (bool) {
+        (bool success, bytes memory output) = verifier.staticcall(
+            abi.encodeWithSignature(
+                "{{ verify_fn_name }}(bytes,uint256[]{% if transcript_prefix %},bytes{% endif %})",
+                proof,
+                values
+                {%- if transcript_prefix %},
+                bytes("")
+                {%- endif %}
+            )
+        );
+        // ReturnMode::Revert returns nothing if proof is accepted
+        return success && (output.length == 0 || abi.decode(output, (bool)));
+    }
+
+    function testVerifyProof() public view {
+        require(verify(PROOF, instances()));
+    }
+
+    function testRejectTamperedProof() public view {
+        bytes memory proof = PROOF;
+        proof[proof.length - 1] ^= 0x01;
+        require(!verify(proof, instances()));
+    }
+    {%- if !instances.is_empty() %}
+
+    function testRejectTamperedInstance() public view {
+        uint256[] memory values = instances();
+        values[0] ^= 1;
+        require(!verify(PROOF, values));
+    }
+    {%- endif %}
+}
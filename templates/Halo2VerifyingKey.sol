@@ -1,6 +1,6 @@
-// SPDX-License-Identifier: MIT
+// SPDX-License-Identifier: {{ license }}
 
-pragma solidity ^0.8.0;
+pragma solidity {{ pragma }};
 
 contract Halo2VerifyingKey {
     constructor() {